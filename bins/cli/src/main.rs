@@ -0,0 +1,88 @@
+//! # merge-whitespace
+//!
+//! A command-line filter that removes multiple consecutive whitespaces from its input,
+//! replacing them with a single space.
+//!
+//! ```text
+//! echo "Hello     World!" | merge-whitespace
+//! merge-whitespace --quote '"' --escape '\' query.graphql
+//! ```
+
+#![forbid(unsafe_code)]
+
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Parser;
+use merge_whitespace_utils::{try_merge_whitespace_with_options, MergeOptions};
+
+/// Removes multiple consecutive whitespaces from the input, replacing them with a single space.
+#[derive(Debug, Parser)]
+#[command(version, about)]
+struct Args {
+    /// The file to read. Reads from standard input if omitted or `-`.
+    file: Option<PathBuf>,
+
+    /// The quote character. Quoted text is kept as-is.
+    #[arg(long, value_name = "CHAR")]
+    quote: Option<char>,
+
+    /// The escape character. The character following it is kept as-is.
+    #[arg(long, value_name = "CHAR")]
+    escape: Option<char>,
+
+    /// Keep line breaks, removing the whitespace surrounding them.
+    #[arg(long)]
+    preserve_newlines: bool,
+
+    /// The string each run of whitespace is replaced with.
+    #[arg(long, value_name = "STRING", default_value = " ")]
+    replacement: String,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+
+    let input = match read_input(args.file.as_ref()) {
+        Ok(input) => input,
+        Err(e) => {
+            eprintln!("Failed to read input: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut options = MergeOptions::new()
+        .replacement(&args.replacement)
+        .preserve_newlines(args.preserve_newlines);
+    options.quote_char = args.quote;
+    options.escape_char = args.escape;
+
+    let output = match try_merge_whitespace_with_options(&input, &options) {
+        Ok(output) => output,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let mut stdout = std::io::stdout().lock();
+    match writeln!(stdout, "{output}").and_then(|()| stdout.flush()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Failed to write output: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Reads the whole input from the file, or from standard input.
+fn read_input(file: Option<&PathBuf>) -> std::io::Result<String> {
+    match file {
+        Some(path) if path.as_os_str() != "-" => std::fs::read_to_string(path),
+        _ => {
+            let mut input = String::new();
+            std::io::stdin().read_to_string(&mut input)?;
+            Ok(input)
+        }
+    }
+}
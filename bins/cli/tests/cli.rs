@@ -0,0 +1,62 @@
+use assert_cmd::Command;
+
+fn cli() -> Command {
+    Command::cargo_bin("merge-whitespace").expect("binary exists")
+}
+
+#[test]
+fn merges_stdin() {
+    cli()
+        .write_stdin("  Hello     World!\r\n   How  are  you?\n")
+        .assert()
+        .success()
+        .stdout("Hello World! How are you?\n");
+}
+
+#[test]
+fn merges_file() {
+    let file =
+        std::env::temp_dir().join(format!("merge-whitespace-cli-{}.txt", std::process::id()));
+    std::fs::write(&file, "query {\n  users {\n    id\n  }\n}\n").unwrap();
+
+    let assert = cli().arg(&file).assert();
+    std::fs::remove_file(&file).ok();
+    assert.success().stdout("query { users { id } }\n");
+}
+
+#[test]
+fn honors_quote_and_escape() {
+    cli()
+        .args(["--quote", "\"", "--escape", "\\"])
+        .write_stdin(r#"users (name: "Froozle   \"78\"   Frobnik")   {   id   }"#)
+        .assert()
+        .success()
+        .stdout("users (name: \"Froozle   \\\"78\\\"   Frobnik\") { id }\n");
+}
+
+#[test]
+fn honors_preserve_newlines_and_replacement() {
+    cli()
+        .args(["--preserve-newlines", "--replacement", "_"])
+        .write_stdin("a   b  \n\n   c d\n")
+        .assert()
+        .success()
+        .stdout("a_b\n\nc_d\n");
+}
+
+#[test]
+fn rejects_invalid_arguments() {
+    cli().args(["--quote", "ab"]).assert().failure();
+    cli().arg("this/file/does/not/exist.txt").assert().failure();
+}
+
+#[test]
+fn rejects_invalid_options() {
+    cli()
+        .args(["--quote", "\"", "--escape", "\""])
+        .write_stdin("a   b")
+        .assert()
+        .failure()
+        .stdout("")
+        .stderr("Invalid options: `quote_char` and `escape_char` are the same character\n");
+}
@@ -0,0 +1,7 @@
+fn main() {
+    // Provides the input for the `merge_whitespace_env!` tests. Build script directives
+    // are line-based, so the value uses tabs and spaces only.
+    println!(
+        "cargo:rustc-env=MERGE_WHITESPACE_DEMO_QUERY=  query {{\tusers (name: \"Froozle   Frobnik\") {{    id\t}}   }}  "
+    );
+}
@@ -1,7 +1,9 @@
 #[cfg(test)]
 mod tests {
     use merge_whitespace::*;
-    use merge_whitespace_utils::merge_whitespace;
+    use merge_whitespace_utils::{
+        assert_no_double_space, merge_whitespace, merge_whitespace_with_quotes,
+    };
 
     const OUTPUT: &str = merge_whitespace!("This   is   an\r\n  example  \t string.");
 
@@ -72,4 +74,104 @@ mod tests {
         let output = merge_whitespace(INPUT);
         assert_eq!(output, "Hello World! '\"How are\"' you?");
     }
+
+    #[test]
+    fn test_env() {
+        const QUERY: &str = merge_whitespace_env!("MERGE_WHITESPACE_DEMO_QUERY", quote_char = '"');
+        assert_eq!(
+            QUERY,
+            r#"query { users (name: "Froozle   Frobnik") { id } }"#
+        );
+
+        let output = merge_whitespace_env!("MERGE_WHITESPACE_DEMO_QUERY");
+        assert_eq!(
+            output,
+            r#"query { users (name: "Froozle Frobnik") { id } }"#
+        );
+    }
+
+    #[test]
+    fn test_assert_no_double_space() {
+        const QUERY: &str = assert_no_double_space(merge_whitespace!(
+            r#"
+                query {
+                  users (name: "Froozle   Frobnik") {
+                    id
+                  }
+                }
+                "#,
+            quote_char = '"'
+        ));
+        assert_eq!(
+            QUERY,
+            r#"query { users (name: "Froozle   Frobnik") { id } }"#
+        );
+    }
+
+    #[test]
+    fn test_raw_and_regular_literals() {
+        // Both literals resolve to the same characters.
+        let raw = merge_whitespace!(r#"say   "a \"b\"   c"   \ "#, '"', '\\');
+        let regular = merge_whitespace!("say   \"a \\\"b\\\"   c\"   \\ ", '"', '\\');
+        assert_eq!(raw, regular);
+        assert_eq!(raw, r#"say "a \"b\"   c" \ "#);
+
+        // Escape sequences are resolved in regular literals only.
+        let raw = merge_whitespace!(r"a \t\t b");
+        let regular = merge_whitespace!("a \t\t b");
+        assert_eq!(raw, r"a \t\t b");
+        assert_eq!(regular, "a b");
+
+        // The escape character applies to the resolved text.
+        let raw = merge_whitespace!(r"a\   b", escape_char = '\\');
+        let regular = merge_whitespace!("a\\   b", escape_char = '\\');
+        assert_eq!(raw, regular);
+        assert_eq!(raw, r"a\  b");
+
+        let regular = merge_whitespace!("a\\\t\t  b", escape_char = '\\');
+        assert_eq!(regular, "a\\\t b");
+    }
+
+    #[test]
+    fn test_tokens() {
+        macro_rules! tokens {
+            ($macro:ident) => {
+                $macro! {
+                    query {
+                        users (limit: 1, name: "Froozle   \"78\"   Frobnik") {
+                            id
+                            name
+                            todos(order_by: {created_at: desc}, limit: 5) {
+                                id
+                                title
+                            }
+                        }
+                    }
+                }
+            };
+        }
+
+        // The spacing between tokens is determined by the tokenizer, so only the invariants
+        // are checked: string literals are kept verbatim and no other spaces are doubled.
+        const QUERY: &str = assert_no_double_space(tokens!(merge_whitespace_tokens));
+        assert!(QUERY.contains(r#""Froozle   \"78\"   Frobnik""#));
+        assert_eq!(
+            QUERY,
+            merge_whitespace_with_quotes(tokens!(stringify), Some('"'), Some('\\'))
+        );
+    }
+
+    #[test]
+    fn test_options_struct() {
+        const QUERY: &str = merge_whitespace!(
+            "SELECT   'it''s   here'   AS   x\n\n",
+            MergeOptions {
+                quote_char: Some('\''),
+                doubled_quote_escapes: true,
+                keep_trailing_newline: true,
+                ..MergeOptions::new()
+            }
+        );
+        assert_eq!(QUERY, "SELECT 'it''s   here' AS x\n");
+    }
 }
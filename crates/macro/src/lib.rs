@@ -29,13 +29,16 @@
 
 #![forbid(unsafe_code)]
 
+use std::borrow::Cow;
+
 use proc_macro::TokenStream;
 use quote::quote;
 use syn::parse_macro_input;
 
-use crate::macro_input::MacroInput;
+use crate::macro_input::{AssertMergedInput, MacroInput};
 
 mod macro_input;
+mod options_input;
 
 /// This is a procedural macro that removes multiple consecutive whitespaces from a given string
 /// literal and replaces them with a single space. Quoted text will be ignored and kept as-is.
@@ -57,6 +60,70 @@ mod macro_input;
 /// assert_eq!(output, "Hello World! \"How        are\" you?");
 /// ```
 ///
+/// The characters can also be passed as positional arguments, in which case the first one is
+/// the quote character and the second one the escape character. Since the two are easily mixed
+/// up, passing the `strict` flag rejects the second positional argument in favor of the named
+/// `escape_char = ...` form.
+///
+/// ```
+/// # use merge_whitespace::merge_whitespace;
+/// let output = merge_whitespace!(r#"a   "b   c"   d"#, strict, '"');
+/// assert_eq!(output, r#"a "b   c" d"#);
+/// ```
+///
+/// ```compile_fail
+/// # use merge_whitespace::merge_whitespace;
+/// let output = merge_whitespace!(r#"a   "b   c"   d"#, strict, '"', '\\');
+/// ```
+///
+/// ## Raw and regular string literals
+///
+/// The macro operates on the *value* of the string literal, i.e. after Rust has resolved the
+/// escape sequences of a regular literal. Raw and regular literals describing the same characters
+/// therefore produce the same output. In particular, the `quote_char` and `escape_char` arguments
+/// apply to the resolved characters: to pass a backslash escape character through a regular
+/// literal, it has to be written as `\\`, whereas a raw literal contains it verbatim.
+///
+/// ```
+/// # use merge_whitespace::merge_whitespace;
+/// let raw = merge_whitespace!(r#"say   "a \"b\"   c""#, quote_char = '"', escape_char = '\\');
+/// let regular = merge_whitespace!("say   \"a \\\"b\\\"   c\"", quote_char = '"', escape_char = '\\');
+/// assert_eq!(raw, regular);
+/// assert_eq!(raw, r#"say "a \"b\"   c""#);
+/// ```
+///
+/// Escape sequences such as `\t` or `\n` in a regular literal are resolved to whitespace
+/// characters before merging, while in a raw literal they are kept as a backslash followed by a
+/// letter.
+///
+/// ## Options struct
+///
+/// Instead of the `quote_char` and `escape_char` arguments, a `MergeOptions` struct expression
+/// can be passed to configure the merge. Since the merge happens at compile time, the expression
+/// is interpreted by the macro rather than evaluated, and `MergeOptions` does not need to be
+/// in scope. Each field must be set to a literal, `Some(literal)`, `None` or an enum variant
+/// such as `Trim::Start`, and the struct may end in `..Default::default()`. Each field may only
+/// be set once. The fields `trim_chars`, `tight_before`, `tight_after`, `verbatim_span`,
+/// `verbatim_spans`, `heredoc_marker`, `only_inside`, `truncate_to`, `ellipsis` and `word_char`
+/// are not supported, nor are the fields requiring crate features, i.e. `strip_format_chars`,
+/// `category_replacement`, `case_unquoted` and `case_quoted`.
+/// The options are [validated](merge_whitespace_utils::MergeOptions::validate), and invalid
+/// combinations as well as violated policies such as `forbid_tabs` fail the compilation.
+///
+/// ```
+/// # use merge_whitespace::merge_whitespace;
+/// let output = merge_whitespace!(
+///     "  a  \"b   c\"  d  ",
+///     MergeOptions {
+///         quote_char: Some('"'),
+///         replacement: "_",
+///         trim: Trim::End,
+///         ..Default::default()
+///     }
+/// );
+/// assert_eq!(output, "_a_\"b   c\"_d");
+/// ```
+///
 /// # Return
 ///
 /// The macro expands to the modified string literal.
@@ -66,12 +133,198 @@ pub fn merge_whitespace(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as MacroInput);
 
     let input_str = input.string.value();
-    let quote_char = input.quote_char;
-    let escape_char = input.escape_char;
+    expand(&input_str, &input)
+}
 
-    // Replace multiple whitespaces with a single space, skipping quoted blocks
+/// This is a procedural macro that reads the value of an environment variable at compile time
+/// (like [`env!`]) and removes multiple consecutive whitespaces from it, replacing them with a
+/// single space. It accepts the same `quote_char` and `escape_char` arguments as [`merge_whitespace!`].
+///
+/// ## Example
+///
+/// ```
+/// # use merge_whitespace::merge_whitespace_env;
+/// const NAME: &str = merge_whitespace_env!("CARGO_PKG_NAME", quote_char = '"');
+/// assert_eq!(NAME, "merge-whitespace");
+/// ```
+///
+/// A compile error is emitted if the variable is not set:
+///
+/// ```compile_fail
+/// # use merge_whitespace::merge_whitespace_env;
+/// const QUERY: &str = merge_whitespace_env!("MERGE_WHITESPACE_SURELY_UNSET_VARIABLE");
+/// ```
+///
+/// Note that unlike [`env!`], procedural macros cannot register the variable with the compiler,
+/// so changing its value does not by itself trigger a rebuild. Variables set by a build script
+/// through `cargo:rustc-env` are picked up, and `cargo:rerun-if-env-changed` can be used to
+/// track externally provided ones.
+///
+/// # Return
+///
+/// The macro expands to the modified string literal.
+#[proc_macro]
+pub fn merge_whitespace_env(input: TokenStream) -> TokenStream {
+    // Parse the input tokens into a syntax tree
+    let input = parse_macro_input!(input as MacroInput);
+
+    let variable = input.string.value();
+    match std::env::var(&variable) {
+        Ok(input_str) => expand(&input_str, &input),
+        Err(e) => syn::Error::new(
+            input.string.span(),
+            format!("Failed to read environment variable `{variable}`: {e}"),
+        )
+        .to_compile_error()
+        .into(),
+    }
+}
+
+/// This is a procedural macro that removes multiple consecutive whitespaces from a given string
+/// literal like [`merge_whitespace!`], but expands to a byte array of the UTF-8 encoded output
+/// instead of a string literal. It accepts the same arguments as [`merge_whitespace!`].
+///
+/// Since the array has the exact length of the output, it can be stored in a `static` or
+/// `const` of a fixed-size type, e.g. to place it in read-only memory on embedded targets.
+///
+/// ## Example
+///
+/// ```
+/// # use merge_whitespace::merge_whitespace_array;
+/// static GREETING: [u8; 19] =
+///     merge_whitespace_array!("  Hello   \"big   World\"  ", quote_char = '"');
+/// assert_eq!(&GREETING, b"Hello \"big   World\"");
+/// ```
+///
+/// # Return
+///
+/// The macro expands to a `[u8; N]` array expression, where `N` is the length of the merged
+/// output in bytes.
+#[proc_macro]
+pub fn merge_whitespace_array(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as MacroInput);
+
+    let input_str = input.string.value();
+    let output_str = match merge(&input_str, &input) {
+        Ok(output_str) => output_str,
+        Err(e) => return e.to_compile_error().into(),
+    };
+    let bytes = output_str.as_bytes();
+
+    let output = match bytes.len() {
+        0 => quote! { [0u8; 0] },
+        _ => quote! { [#(#bytes),*] },
+    };
+
+    output.into()
+}
+
+/// This is a procedural macro that turns a block of tokens into a string like [`stringify!`]
+/// and then removes multiple consecutive whitespaces from it. This allows embedding e.g. queries
+/// without having to quote them or to escape the quotes they contain.
+///
+/// String literals within the tokens are kept as-is, i.e. `"` acts as the quote character
+/// and `\` as the escape character.
+///
+/// Note that the spacing of the output is determined by the Rust tokenizer, not by the original
+/// source: token trees are separated by single spaces and, for example, punctuation may be spaced
+/// differently than written. Comments are removed, and the tokens need to be valid Rust tokens,
+/// e.g. with balanced delimiters.
+///
+/// ## Example
+///
+/// ```
+/// # use merge_whitespace::merge_whitespace_tokens;
+/// # use merge_whitespace_utils::merge_whitespace_with_quotes;
+/// const QUERY: &str = merge_whitespace_tokens! {
+///     query {
+///         users(name: "Froozle   Frobnik") {
+///             id
+///         }
+///     }
+/// };
+/// // The literal is kept verbatim, while no other spaces are doubled.
+/// assert!(QUERY.contains(r#""Froozle   Frobnik""#));
+/// assert!(!QUERY.replace(r#""Froozle   Frobnik""#, "").contains("  "));
+///
+/// // The output equals the merged output of `stringify!`.
+/// let tokens = stringify! {
+///     query {
+///         users(name: "Froozle   Frobnik") {
+///             id
+///         }
+///     }
+/// };
+/// assert_eq!(QUERY, merge_whitespace_with_quotes(tokens, Some('"'), Some('\\')));
+/// ```
+///
+/// # Return
+///
+/// The macro expands to the string literal of the merged tokens.
+#[proc_macro]
+pub fn merge_whitespace_tokens(input: TokenStream) -> TokenStream {
+    let input_str = input.to_string();
     let output_str =
-        merge_whitespace_utils::merge_whitespace_with_quotes(&input_str, quote_char, escape_char);
+        merge_whitespace_utils::merge_whitespace_with_quotes(&input_str, Some('"'), Some('\\'));
+
+    let output = quote! {
+        #output_str
+    };
+
+    output.into()
+}
+
+/// This is a procedural macro that asserts at compile time that [`merge_whitespace!`] produces
+/// the expected string literal for the given input. The first argument is the expected output,
+/// followed by the same arguments [`merge_whitespace!`] accepts.
+///
+/// This allows golden values to be verified during the build. The macro expands to an empty
+/// constant item, so it can be used both at item level and inside functions.
+///
+/// ## Example
+///
+/// ```
+/// # use merge_whitespace::assert_merged;
+/// assert_merged!("Hello World!", "Hello     World!");
+/// assert_merged!(r#"a "b   c" d"#, r#"a   "b   c"   d"#, quote_char = '"');
+/// ```
+///
+/// A mismatch fails the compilation:
+///
+/// ```compile_fail
+/// # use merge_whitespace::assert_merged;
+/// assert_merged!("Hello  World!", "Hello     World!");
+/// ```
+#[proc_macro]
+pub fn assert_merged(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as AssertMergedInput);
+
+    let input_str = input.input.string.value();
+    let output_str = match merge(&input_str, &input.input) {
+        Ok(output_str) => output_str,
+        Err(e) => return e.to_compile_error().into(),
+    };
+    let expected = input.expected.value();
+
+    let output = if output_str == expected {
+        quote! { const _: () = (); }
+    } else {
+        syn::Error::new(
+            input.expected.span(),
+            format!("merged output does not match the expected value\n  expected: {expected:?}\n    merged: {output_str:?}"),
+        )
+        .to_compile_error()
+    };
+
+    output.into()
+}
+
+/// Merges the whitespace in the provided string and generates the output literal.
+fn expand(input_str: &str, input: &MacroInput) -> TokenStream {
+    let output_str = match merge(input_str, input) {
+        Ok(output_str) => output_str,
+        Err(e) => return e.to_compile_error().into(),
+    };
 
     // Generate the output tokens
     let output = quote! {
@@ -80,3 +333,27 @@ pub fn merge_whitespace(input: TokenStream) -> TokenStream {
 
     output.into()
 }
+
+/// Merges the whitespace in the provided string according to the macro arguments.
+///
+/// The options struct is validated and its policies, such as `forbid_tabs`, are enforced,
+/// reporting violations as errors spanning the struct.
+fn merge(input_str: &str, input: &MacroInput) -> syn::Result<String> {
+    if let Some(options) = &input.options {
+        return merge_whitespace_utils::try_merge_whitespace_with_options(
+            input_str,
+            &options.options(),
+        )
+        .map(Cow::into_owned)
+        .map_err(|e| syn::Error::new_spanned(options, e));
+    }
+
+    let quote_char = input.quote_char;
+    let escape_char = input.escape_char;
+
+    // Replace multiple whitespaces with a single space, skipping quoted blocks
+    Ok(
+        merge_whitespace_utils::merge_whitespace_with_quotes(input_str, quote_char, escape_char)
+            .into_owned(),
+    )
+}
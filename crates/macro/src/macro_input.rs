@@ -1,6 +1,8 @@
 use syn::parse::{Parse, ParseStream, Result};
 use syn::{Expr, ExprLit, Ident, LitStr, Token};
 
+use crate::options_input::OptionsInput;
+
 /// Input for the whitespace merging macro.
 pub struct MacroInput {
     /// The input string to merge whitespaces in.
@@ -9,6 +11,8 @@ pub struct MacroInput {
     pub quote_char: Option<char>,
     /// The optional escape character to use.
     pub escape_char: Option<char>,
+    /// The optional options struct, which replaces the quote and escape characters.
+    pub options: Option<OptionsInput>,
 }
 
 impl Parse for MacroInput {
@@ -16,11 +20,27 @@ impl Parse for MacroInput {
         let string = input.parse()?;
         let mut quote_char = None;
         let mut escape_char = None;
+        let mut options = None;
+        let mut strict = false;
+        let mut positional_escape_char = None;
 
         while !input.is_empty() {
             input.parse::<Token![,]>()?;
+            if input.is_empty() {
+                // A single trailing comma is allowed.
+                break;
+            }
 
-            if input.peek(Ident) {
+            if options.is_some() {
+                return Err(input.error("Unexpected argument after the options struct"));
+            } else if OptionsInput::peek(input) {
+                if quote_char.is_some() || escape_char.is_some() {
+                    return Err(input.error(
+                        "The options struct cannot be combined with 'quote_char' or 'escape_char'",
+                    ));
+                }
+                options = Some(input.parse()?);
+            } else if input.peek(Ident) {
                 let ident: Ident = input.parse()?;
                 match &*ident.to_string() {
                     "quote_char" => {
@@ -29,8 +49,12 @@ impl Parse for MacroInput {
                     "escape_char" => {
                         escape_char = parse_named_char(&input, "escape_char")?;
                     }
+                    "strict" => {
+                        strict = true;
+                    }
                     _ => {
-                        return Err(input.error("Expected 'quote_char' or 'escape_char' identifier"))
+                        return Err(input
+                            .error("Expected 'quote_char', 'escape_char' or 'strict' identifier"))
                     }
                 }
             } else {
@@ -39,6 +63,7 @@ impl Parse for MacroInput {
                     if quote_char.is_none() {
                         quote_char = parse_char(&input, expr_lit, "quote_char")?;
                     } else if escape_char.is_none() {
+                        positional_escape_char = Some(expr_lit.clone());
                         escape_char = parse_char(&input, expr_lit, "escape_char")?;
                     } else {
                         return Err(input.error("Unexpected additional positional argument"));
@@ -49,14 +74,42 @@ impl Parse for MacroInput {
             }
         }
 
+        // Passing the escape character as a second positional argument is easily mistaken for
+        // the quote character, so it is rejected if the invocation opts into strict parsing.
+        if let (true, Some(expr_lit)) = (strict, positional_escape_char) {
+            return Err(syn::Error::new_spanned(
+                expr_lit,
+                "Ambiguous positional arguments: pass the characters as \
+                 'quote_char = ..., escape_char = ...' instead",
+            ));
+        }
+
         Ok(MacroInput {
             string,
             quote_char,
             escape_char,
+            options,
         })
     }
 }
 
+/// Input for the merge assertion macro.
+pub struct AssertMergedInput {
+    /// The expected output.
+    pub expected: LitStr,
+    /// The input to merge whitespaces in, and its options.
+    pub input: MacroInput,
+}
+
+impl Parse for AssertMergedInput {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let expected = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let input = input.parse()?;
+        Ok(AssertMergedInput { expected, input })
+    }
+}
+
 fn parse_char(
     input: &ParseStream,
     expr_lit: ExprLit,
@@ -234,4 +287,95 @@ mod tests {
         assert!(parse_str::<MacroInput>("42").is_err());
         assert!(parse_str::<MacroInput>(r#""foo" 42"#).is_err());
     }
+
+    #[test]
+    fn test_options_struct() {
+        let input: MacroInput = parse_str(
+            r#""Test string", MergeOptions { quote_char: Some('"'), ..Default::default() }"#,
+        )
+        .unwrap();
+        assert_eq!(input.string.value(), "Test string");
+        assert_eq!(input.quote_char, None);
+        assert_eq!(input.escape_char, None);
+        let options = input.options.unwrap();
+        assert_eq!(options.options().quote_char, Some('"'));
+
+        // The options struct cannot be combined with other arguments
+        assert!(parse_str::<MacroInput>(r#""Test string", '"', MergeOptions {}"#).is_err());
+        assert!(
+            parse_str::<MacroInput>(r#""Test string", escape_char = '\', MergeOptions {}"#)
+                .is_err()
+        );
+        assert!(parse_str::<MacroInput>(r#""Test string", MergeOptions {}, '"'"#).is_err());
+        assert!(
+            parse_str::<MacroInput>(r#""Test string", MergeOptions {}, MergeOptions {}"#).is_err()
+        );
+        assert!(parse_str::<MacroInput>(r#""Test string", Options {}"#).is_err());
+    }
+
+    #[test]
+    fn test_strict_positional_arguments() {
+        let error = parse_str::<MacroInput>(r#""Test string", strict, '"', '\\'"#)
+            .err()
+            .unwrap();
+        assert!(error
+            .to_string()
+            .contains("quote_char = ..., escape_char = ..."));
+        assert!(parse_str::<MacroInput>(r#""Test string", '"', '\\', strict"#).is_err());
+        assert!(parse_str::<MacroInput>(r#""Test string", '"', '\\', strict,"#).is_err());
+
+        // A single positional argument and named arguments are unambiguous.
+        for input in [
+            r#""Test string", strict"#,
+            r#""Test string", strict, '"'"#,
+            r#""Test string", '"', strict, escape_char = '\\'"#,
+            r#""Test string", strict, quote_char = '"', escape_char = '\\'"#,
+            r#""Test string", strict, MergeOptions {}"#,
+        ] {
+            assert!(parse_str::<MacroInput>(input).is_ok(), "{input}");
+        }
+
+        // Without the flag, two positional arguments remain supported.
+        let input: MacroInput = parse_str(r#""Test string", '"', '\\'"#).unwrap();
+        assert_eq!(input.escape_char, Some('\\'));
+    }
+
+    #[test]
+    fn test_trailing_comma() {
+        let input: MacroInput = parse_str(r#""Test string","#).unwrap();
+        assert_eq!(input.string.value(), "Test string");
+        assert_eq!(input.quote_char, None);
+
+        let input: MacroInput = parse_str(r#""Test string", '"', '\\',"#).unwrap();
+        assert_eq!(input.quote_char, Some('"'));
+        assert_eq!(input.escape_char, Some('\\'));
+
+        let input: MacroInput =
+            parse_str(r#""Test string", quote_char = '"', escape_char = '\\',"#).unwrap();
+        assert_eq!(input.quote_char, Some('"'));
+        assert_eq!(input.escape_char, Some('\\'));
+
+        let input: MacroInput = parse_str(r#""Test string", MergeOptions {},"#).unwrap();
+        assert!(input.options.is_some());
+
+        // Only a single trailing comma is allowed
+        assert!(parse_str::<MacroInput>(r#""Test string",,"#).is_err());
+        assert!(parse_str::<MacroInput>(r#""Test string", quote_char = '"',,"#).is_err());
+        assert!(parse_str::<MacroInput>(r#""Test string", , '"'"#).is_err());
+        assert!(parse_str::<AssertMergedInput>(r#""expected","#).is_err());
+    }
+
+    #[test]
+    fn test_assert_merged_input() {
+        let input: AssertMergedInput =
+            parse_str(r#""expected", "Test string", quote_char = '"'"#).unwrap();
+        assert_eq!(input.expected.value(), "expected");
+        assert_eq!(input.input.string.value(), "Test string");
+        assert_eq!(input.input.quote_char, Some('"'));
+        assert_eq!(input.input.escape_char, None);
+
+        assert!(parse_str::<AssertMergedInput>(r#""expected""#).is_err());
+        assert!(parse_str::<AssertMergedInput>(r#""expected" "Test string""#).is_err());
+        assert!(parse_str::<AssertMergedInput>(r#""expected", '"'"#).is_err());
+    }
 }
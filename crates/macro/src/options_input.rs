@@ -0,0 +1,525 @@
+use merge_whitespace_utils::{
+    CollapseTo, EdgeCollapse, LeadingPolicy, MergeOptions, SentenceSpacing, Trim,
+};
+use proc_macro2::TokenStream;
+use quote::ToTokens;
+use syn::parse::{Parse, ParseStream, Result};
+use syn::token::Brace;
+use syn::{braced, parenthesized, Ident, LitBool, LitChar, LitInt, LitStr, Path, Token};
+
+/// The fields of a `MergeOptions` struct expression supported by [`OptionsInput`].
+const FIELDS: &[&str] = &[
+    "quote_char",
+    "escape_char",
+    "table_separator",
+    "split_on",
+    "comment_char",
+    "trim_between",
+    "debug_sentinel",
+    "replacement",
+    "max_spaces",
+    "clamp_run",
+    "exact_run",
+    "tabstop",
+    "trim",
+    "collapse_to",
+    "sentence_spacing",
+    "edge_collapse",
+    "leading_newline",
+    "shell_mode",
+    "escape_protects_run",
+    "escape_only_specials",
+    "normalize_unicode_space",
+    "doubled_quote_escapes",
+    "split_on_type_change",
+    "keep_singletons",
+    "preserve_newlines",
+    "keep_trailing_newline",
+    "typographic_quotes",
+    "preserve_parens",
+    "drop_empty_quotes",
+    "protect_urls",
+    "heredoc",
+    "preserve_ansi",
+    "forbid_tabs",
+    "minimal_spacing",
+    "between_words_only",
+];
+
+/// Options given as a `MergeOptions { .. }` struct expression.
+///
+/// The expression is not evaluated but interpreted by the macro, so only a restricted grammar is
+/// supported: each field is assigned a literal (`'c'`, `"str"`, `true`, `42`), `Some(literal)`,
+/// `None` or an enum variant path such as `Trim::Start`, and the struct may end in
+/// `..Default::default()`, `..MergeOptions::new()` or `..MergeOptions::default()`. Each field
+/// may be assigned at most once.
+pub struct OptionsInput {
+    /// The options, except for the replacement.
+    pub options: MergeOptions<'static>,
+    /// The replacement, if specified.
+    pub replacement: Option<String>,
+    /// The path of the struct, used to span errors.
+    path: Path,
+    /// The braces of the struct, used to span errors.
+    brace: Brace,
+}
+
+impl OptionsInput {
+    /// Determines whether the input starts with an options struct expression.
+    pub fn peek(input: ParseStream) -> bool {
+        let fork = input.fork();
+        fork.parse::<Path>()
+            .is_ok_and(|path| is_named(&path, "MergeOptions") && fork.peek(syn::token::Brace))
+    }
+
+    /// Gets the options, borrowing the replacement.
+    pub fn options(&self) -> MergeOptions<'_> {
+        let mut options = self.options.clone();
+        if let Some(replacement) = &self.replacement {
+            options.replacement = replacement;
+        }
+        options
+    }
+}
+
+impl Parse for OptionsInput {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let path: Path = input.parse()?;
+        if !is_named(&path, "MergeOptions") {
+            return Err(syn::Error::new_spanned(
+                path,
+                "Expected a MergeOptions struct",
+            ));
+        }
+
+        let content;
+        let brace = braced!(content in input);
+        let mut options = MergeOptions::new();
+        let mut replacement = None;
+        let mut assigned = Vec::new();
+
+        while !content.is_empty() {
+            if content.peek(Token![..]) {
+                content.parse::<Token![..]>()?;
+                parse_default(&content)?;
+                if !content.is_empty() {
+                    return Err(content.error("Expected the end of the struct after the defaults"));
+                }
+                break;
+            }
+
+            let field: Ident = content.parse()?;
+            content.parse::<Token![:]>()?;
+            if assigned.contains(&field) {
+                return Err(syn::Error::new_spanned(
+                    &field,
+                    format!("Duplicate MergeOptions field `{field}`"),
+                ));
+            }
+            match &*field.to_string() {
+                "quote_char" => options.quote_char = parse_option(&content, parse_char)?,
+                "escape_char" => options.escape_char = parse_option(&content, parse_char)?,
+                "table_separator" => {
+                    options.table_separator = parse_option(&content, parse_char)?;
+                }
+                "split_on" => options.split_on = parse_option(&content, parse_char)?,
+                "comment_char" => options.comment_char = parse_option(&content, parse_char)?,
+                "trim_between" => options.trim_between = parse_option(&content, parse_char)?,
+                "debug_sentinel" => options.debug_sentinel = parse_option(&content, parse_char)?,
+                "replacement" => replacement = Some(content.parse::<LitStr>()?.value()),
+                "max_spaces" => options.max_spaces = parse_option(&content, parse_usize)?,
+                "clamp_run" => options.clamp_run = parse_option(&content, parse_usize)?,
+                "exact_run" => options.exact_run = parse_option(&content, parse_usize)?,
+                "tabstop" => options.tabstop = parse_option(&content, parse_usize)?,
+                "trim" => options.trim = parse_trim(&content)?,
+                "collapse_to" => options.collapse_to = parse_collapse_to(&content)?,
+                "sentence_spacing" => {
+                    options.sentence_spacing = parse_sentence_spacing(&content)?;
+                }
+                "edge_collapse" => options.edge_collapse = parse_edge_collapse(&content)?,
+                "leading_newline" => {
+                    options.leading_newline = parse_option(&content, parse_leading_policy)?;
+                }
+                "shell_mode" => options.shell_mode = parse_bool(&content)?,
+                "escape_protects_run" => options.escape_protects_run = parse_bool(&content)?,
+                "escape_only_specials" => options.escape_only_specials = parse_bool(&content)?,
+                "normalize_unicode_space" => {
+                    options.normalize_unicode_space = parse_bool(&content)?;
+                }
+                "doubled_quote_escapes" => options.doubled_quote_escapes = parse_bool(&content)?,
+                "split_on_type_change" => options.split_on_type_change = parse_bool(&content)?,
+                "keep_singletons" => options.keep_singletons = parse_bool(&content)?,
+                "preserve_newlines" => options.preserve_newlines = parse_bool(&content)?,
+                "keep_trailing_newline" => options.keep_trailing_newline = parse_bool(&content)?,
+                "typographic_quotes" => options.typographic_quotes = parse_bool(&content)?,
+                "preserve_parens" => options.preserve_parens = parse_bool(&content)?,
+                "drop_empty_quotes" => options.drop_empty_quotes = parse_bool(&content)?,
+                "protect_urls" => options.protect_urls = parse_bool(&content)?,
+                "heredoc" => options.heredoc = parse_bool(&content)?,
+                "preserve_ansi" => options.preserve_ansi = parse_bool(&content)?,
+                "forbid_tabs" => options.forbid_tabs = parse_bool(&content)?,
+                "minimal_spacing" => options.minimal_spacing = parse_bool(&content)?,
+                "between_words_only" => options.between_words_only = parse_bool(&content)?,
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        field,
+                        format!(
+                            "Unsupported MergeOptions field, expected one of: {}",
+                            FIELDS.join(", ")
+                        ),
+                    ))
+                }
+            }
+            assigned.push(field);
+
+            if !content.is_empty() {
+                content.parse::<Token![,]>()?;
+            }
+        }
+
+        Ok(OptionsInput {
+            options,
+            replacement,
+            path,
+            brace,
+        })
+    }
+}
+
+impl ToTokens for OptionsInput {
+    /// Emits the path and the braces of the struct, which is sufficient to span errors.
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        self.path.to_tokens(tokens);
+        self.brace.surround(tokens, |_| {});
+    }
+}
+
+/// Determines whether the last segment of the path is `name`.
+fn is_named(path: &Path, name: &str) -> bool {
+    path.segments
+        .last()
+        .is_some_and(|segment| segment.ident == name)
+}
+
+/// Parses `Default::default()`, `MergeOptions::new()` or `MergeOptions::default()`.
+fn parse_default(input: ParseStream) -> Result<()> {
+    let path: Path = input.parse()?;
+    let segments: Vec<_> = path.segments.iter().map(|s| s.ident.to_string()).collect();
+    let valid = match segments.as_slice() {
+        [.., ty, function] => {
+            (ty == "Default" && function == "default")
+                || (ty == "MergeOptions" && (function == "new" || function == "default"))
+        }
+        _ => false,
+    };
+    if !valid {
+        return Err(syn::Error::new_spanned(
+            path,
+            "Expected Default::default(), MergeOptions::new() or MergeOptions::default()",
+        ));
+    }
+    let arguments;
+    parenthesized!(arguments in input);
+    if !arguments.is_empty() {
+        return Err(arguments.error("Expected no arguments"));
+    }
+    Ok(())
+}
+
+/// Parses `None` or `Some(value)`.
+fn parse_option<T>(input: ParseStream, parse: fn(ParseStream) -> Result<T>) -> Result<Option<T>> {
+    let ident: Ident = input.parse()?;
+    match &*ident.to_string() {
+        "None" => Ok(None),
+        "Some" => {
+            let content;
+            parenthesized!(content in input);
+            let value = parse(&content)?;
+            if !content.is_empty() {
+                return Err(content.error("Expected a single value"));
+            }
+            Ok(Some(value))
+        }
+        _ => Err(syn::Error::new_spanned(ident, "Expected Some(..) or None")),
+    }
+}
+
+fn parse_char(input: ParseStream) -> Result<char> {
+    Ok(input.parse::<LitChar>()?.value())
+}
+
+fn parse_bool(input: ParseStream) -> Result<bool> {
+    Ok(input.parse::<LitBool>()?.value())
+}
+
+fn parse_usize(input: ParseStream) -> Result<usize> {
+    input.parse::<LitInt>()?.base10_parse()
+}
+
+/// Parses the path of an enum variant, e.g. `Trim::Start`, returning the variant name.
+fn parse_variant(input: ParseStream, enum_name: &str) -> Result<Ident> {
+    let path: Path = input.parse()?;
+    let mut segments = path.segments.iter().rev();
+    match (segments.next(), segments.next()) {
+        (Some(variant), Some(ty)) if ty.ident == enum_name => Ok(variant.ident.clone()),
+        _ => Err(syn::Error::new_spanned(
+            path,
+            format!("Expected a {enum_name} variant"),
+        )),
+    }
+}
+
+fn parse_trim(input: ParseStream) -> Result<Trim> {
+    let variant = parse_variant(input, "Trim")?;
+    match &*variant.to_string() {
+        "Both" => Ok(Trim::Both),
+        "Start" => Ok(Trim::Start),
+        "End" => Ok(Trim::End),
+        "None" => Ok(Trim::None),
+        _ => Err(syn::Error::new_spanned(variant, "Unknown Trim variant")),
+    }
+}
+
+fn parse_collapse_to(input: ParseStream) -> Result<CollapseTo> {
+    let variant = parse_variant(input, "CollapseTo")?;
+    match &*variant.to_string() {
+        "Replacement" => Ok(CollapseTo::Replacement),
+        "First" => Ok(CollapseTo::First),
+        "Last" => Ok(CollapseTo::Last),
+        _ => Err(syn::Error::new_spanned(
+            variant,
+            "Unknown CollapseTo variant",
+        )),
+    }
+}
+
+fn parse_sentence_spacing(input: ParseStream) -> Result<SentenceSpacing> {
+    let variant = parse_variant(input, "SentenceSpacing")?;
+    match &*variant.to_string() {
+        "Single" => Ok(SentenceSpacing::Single),
+        "Double" => Ok(SentenceSpacing::Double),
+        _ => Err(syn::Error::new_spanned(
+            variant,
+            "Unknown SentenceSpacing variant",
+        )),
+    }
+}
+
+fn parse_edge_collapse(input: ParseStream) -> Result<EdgeCollapse> {
+    let variant = parse_variant(input, "EdgeCollapse")?;
+    match &*variant.to_string() {
+        "Keep" => Ok(EdgeCollapse::Keep),
+        "DropAtQuotes" => Ok(EdgeCollapse::DropAtQuotes),
+        _ => Err(syn::Error::new_spanned(
+            variant,
+            "Unknown EdgeCollapse variant",
+        )),
+    }
+}
+
+fn parse_leading_policy(input: ParseStream) -> Result<LeadingPolicy> {
+    let variant = parse_variant(input, "LeadingPolicy")?;
+    match &*variant.to_string() {
+        "Strip" => Ok(LeadingPolicy::Strip),
+        "KeepOne" => Ok(LeadingPolicy::KeepOne),
+        _ => Err(syn::Error::new_spanned(
+            variant,
+            "Unknown LeadingPolicy variant",
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse_str;
+
+    #[test]
+    fn test_empty_struct() {
+        let input: OptionsInput = parse_str("MergeOptions {}").unwrap();
+        assert_eq!(input.options(), MergeOptions::new());
+
+        let input: OptionsInput = parse_str("MergeOptions { ..Default::default() }").unwrap();
+        assert_eq!(input.options(), MergeOptions::new());
+    }
+
+    #[test]
+    fn test_all_fields() {
+        let input: OptionsInput = parse_str(
+            r#"merge_whitespace_utils::MergeOptions {
+                quote_char: Some('"'),
+                escape_char: Some('\\'),
+                table_separator: None,
+                comment_char: Some('#'),
+                split_on: Some('|'),
+                trim_between: Some(','),
+                debug_sentinel: Some('·'),
+                replacement: "_",
+                max_spaces: Some(2),
+                clamp_run: Some(3),
+                exact_run: Some(1),
+                tabstop: Some(4),
+                trim: Trim::Start,
+                collapse_to: CollapseTo::Last,
+                sentence_spacing: SentenceSpacing::Double,
+                edge_collapse: EdgeCollapse::DropAtQuotes,
+                leading_newline: Some(LeadingPolicy::KeepOne),
+                shell_mode: true,
+                escape_protects_run: true,
+                escape_only_specials: true,
+                normalize_unicode_space: true,
+                doubled_quote_escapes: true,
+                split_on_type_change: true,
+                keep_singletons: true,
+                preserve_newlines: true,
+                keep_trailing_newline: true,
+                typographic_quotes: true,
+                preserve_parens: true,
+                drop_empty_quotes: true,
+                protect_urls: true,
+                heredoc: true,
+                preserve_ansi: true,
+                forbid_tabs: true,
+                minimal_spacing: true,
+                between_words_only: true,
+                ..MergeOptions::new()
+            }"#,
+        )
+        .unwrap();
+        let mut expected = MergeOptions::new()
+            .quote_char('"')
+            .escape_char('\\')
+            .comment_char('#')
+            .split_on('|')
+            .trim_between(',')
+            .debug_sentinel('·')
+            .replacement("_")
+            .max_spaces(2)
+            .clamp_run(3)
+            .exact_run(1)
+            .tabstop(4)
+            .trim(Trim::Start)
+            .collapse_to(CollapseTo::Last)
+            .sentence_spacing(SentenceSpacing::Double)
+            .edge_collapse(EdgeCollapse::DropAtQuotes)
+            .leading_newline(LeadingPolicy::KeepOne)
+            .shell_mode(true)
+            .escape_protects_run(true)
+            .escape_only_specials(true)
+            .normalize_unicode_space(true)
+            .doubled_quote_escapes(true)
+            .split_on_type_change(true)
+            .keep_singletons(true)
+            .preserve_newlines(true)
+            .keep_trailing_newline(true)
+            .typographic_quotes(true)
+            .preserve_parens(true)
+            .drop_empty_quotes(true)
+            .protect_urls(true)
+            .heredoc(true)
+            .preserve_ansi(true)
+            .forbid_tabs(true)
+            .minimal_spacing(true)
+            .between_words_only(true);
+        expected.table_separator = None;
+        assert_eq!(input.options(), expected);
+    }
+
+    #[test]
+    fn test_unsupported_field_error() {
+        let error = parse_str::<OptionsInput>("MergeOptions { word_char: None }")
+            .err()
+            .unwrap()
+            .to_string();
+        for field in FIELDS {
+            assert!(error.contains(field), "{field}");
+        }
+    }
+
+    #[test]
+    fn test_qualified_enum_paths() {
+        let input: OptionsInput =
+            parse_str("MergeOptions { trim: merge_whitespace_utils::Trim::None }").unwrap();
+        assert_eq!(input.options().trim, Trim::None);
+    }
+
+    #[test]
+    fn test_replacement_escapes() {
+        let input: OptionsInput = parse_str(r#"MergeOptions { replacement: "\n" }"#).unwrap();
+        assert_eq!(input.options().replacement, "\n");
+
+        let input: OptionsInput = parse_str(r#"MergeOptions { replacement: "\t\t" }"#).unwrap();
+        assert_eq!(input.options().replacement, "\t\t");
+    }
+
+    #[test]
+    fn test_peek() {
+        let is_options = |s: &str| {
+            let peek = |input: ParseStream| {
+                let is_options = OptionsInput::peek(input);
+                // Skip the remaining tokens.
+                input.step(|cursor| {
+                    let mut rest = *cursor;
+                    while let Some((_, next)) = rest.token_tree() {
+                        rest = next;
+                    }
+                    Ok(((), rest))
+                })?;
+                Ok(is_options)
+            };
+            syn::parse::Parser::parse_str(peek, s).unwrap()
+        };
+        assert!(is_options("MergeOptions { }"));
+        assert!(is_options("merge_whitespace_utils::MergeOptions { }"));
+        assert!(!is_options("MergeOptions::new()"));
+        assert!(!is_options("quote_char = '\"'"));
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        // Not a MergeOptions struct
+        assert!(parse_str::<OptionsInput>("Options { }").is_err());
+        assert!(parse_str::<OptionsInput>("MergeOptions").is_err());
+        assert!(parse_str::<OptionsInput>("MergeOptions::new()").is_err());
+
+        // Unsupported fields and values
+        assert!(parse_str::<OptionsInput>("MergeOptions { foo: true }").is_err());
+        assert!(parse_str::<OptionsInput>("MergeOptions { quote_char: '\"' }").is_err());
+        assert!(parse_str::<OptionsInput>("MergeOptions { quote_char: Some(\"x\") }").is_err());
+        assert!(parse_str::<OptionsInput>("MergeOptions { quote_char: Some('a', 'b') }").is_err());
+        assert!(parse_str::<OptionsInput>("MergeOptions { quote_char: QUOTE }").is_err());
+        assert!(parse_str::<OptionsInput>("MergeOptions { shell_mode: 1 }").is_err());
+        assert!(parse_str::<OptionsInput>("MergeOptions { max_spaces: Some(-1) }").is_err());
+        assert!(parse_str::<OptionsInput>("MergeOptions { replacement: ' ' }").is_err());
+        assert!(parse_str::<OptionsInput>("MergeOptions { trim: Start }").is_err());
+        assert!(parse_str::<OptionsInput>("MergeOptions { trim: Trim::Middle }").is_err());
+        assert!(parse_str::<OptionsInput>("MergeOptions { trim: CollapseTo::First }").is_err());
+
+        // Invalid defaults
+        assert!(parse_str::<OptionsInput>("MergeOptions { .. }").is_err());
+        assert!(parse_str::<OptionsInput>("MergeOptions { ..OPTIONS }").is_err());
+        assert!(parse_str::<OptionsInput>("MergeOptions { ..Trim::new() }").is_err());
+        assert!(parse_str::<OptionsInput>("MergeOptions { ..Default::default(1) }").is_err());
+        assert!(parse_str::<OptionsInput>(
+            "MergeOptions { ..Default::default(), shell_mode: true }"
+        )
+        .is_err());
+
+        // Duplicate fields
+        let error =
+            parse_str::<OptionsInput>(r#"MergeOptions { replacement: "a", replacement: "b" }"#)
+                .err()
+                .unwrap();
+        assert!(error.to_string().contains("`replacement`"));
+        assert!(parse_str::<OptionsInput>(
+            "MergeOptions { shell_mode: true, trim: Trim::End, shell_mode: false }"
+        )
+        .is_err());
+
+        // Missing comma
+        assert!(parse_str::<OptionsInput>(
+            "MergeOptions { shell_mode: true preserve_newlines: true }"
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,19 @@
+#[test]
+fn ui() {
+    // The test cases are compiled by a child process that inherits the environment,
+    // which provides the variables read by `merge_whitespace_env!`.
+    std::env::set_var(
+        "MERGE_WHITESPACE_TRYBUILD_QUERY",
+        "\n  query {\n    users (name: \"Froozle   Frobnik\") {\n      id\n    }\n  }\n",
+    );
+    std::env::remove_var("MERGE_WHITESPACE_TRYBUILD_UNSET");
+
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/assert_merged_pass.rs");
+    t.compile_fail("tests/ui/assert_merged_fail.rs");
+    t.pass("tests/ui/env_pass.rs");
+    t.compile_fail("tests/ui/env_unset_fail.rs");
+    t.pass("tests/ui/array_pass.rs");
+    t.pass("tests/ui/replacement_escapes_pass.rs");
+    t.compile_fail("tests/ui/options_fail.rs");
+}
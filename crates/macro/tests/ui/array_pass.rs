@@ -0,0 +1,37 @@
+use merge_whitespace::{merge_whitespace, merge_whitespace_array};
+
+const QUERY: [u8; 51] = merge_whitespace_array!(
+    r#"
+        query {
+          users (name: "Fröozle   Frobnik") {
+            id
+          }
+        }
+    "#,
+    quote_char = '"',
+);
+
+static EMPTY: [u8; 0] = merge_whitespace_array!("   ");
+
+fn main() {
+    let expected = merge_whitespace!(
+        r#"
+        query {
+          users (name: "Fröozle   Frobnik") {
+            id
+          }
+        }
+    "#,
+        quote_char = '"'
+    );
+    assert_eq!(QUERY.len(), expected.len());
+    assert_eq!(&QUERY, expected.as_bytes());
+    assert_eq!(
+        std::str::from_utf8(&QUERY),
+        Ok(r#"query { users (name: "Fröozle   Frobnik") { id } }"#)
+    );
+    assert!(EMPTY.is_empty());
+
+    let options = merge_whitespace_array!("  a  b  ", MergeOptions { replacement: "_", ..Default::default() });
+    assert_eq!(options, *b"a_b");
+}
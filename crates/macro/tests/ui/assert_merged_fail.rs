@@ -0,0 +1,5 @@
+use merge_whitespace::assert_merged;
+
+fn main() {
+    assert_merged!("Hello  World!", "Hello     World!");
+}
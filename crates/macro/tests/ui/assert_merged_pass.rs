@@ -0,0 +1,17 @@
+use merge_whitespace::assert_merged;
+
+assert_merged!("Hello World!", "  Hello     World!  ");
+
+fn main() {
+    assert_merged!(
+        r#"query { users (name: "Froozle   Frobnik") { id } }"#,
+        r#"
+            query {
+              users (name: "Froozle   Frobnik") {
+                id
+              }
+            }
+        "#,
+        quote_char = '"'
+    );
+}
@@ -0,0 +1,7 @@
+use merge_whitespace::merge_whitespace_env;
+
+const QUERY: &str = merge_whitespace_env!("MERGE_WHITESPACE_TRYBUILD_QUERY", quote_char = '"');
+
+fn main() {
+    assert_eq!(QUERY, r#"query { users (name: "Froozle   Frobnik") { id } }"#);
+}
@@ -0,0 +1,7 @@
+use merge_whitespace::merge_whitespace_env;
+
+const QUERY: &str = merge_whitespace_env!("MERGE_WHITESPACE_TRYBUILD_UNSET", quote_char = '"');
+
+fn main() {
+    println!("{QUERY}");
+}
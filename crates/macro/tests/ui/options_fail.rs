@@ -0,0 +1,21 @@
+use merge_whitespace::merge_whitespace;
+
+const TABS: &str = merge_whitespace!(
+    "a\tb",
+    MergeOptions {
+        forbid_tabs: true,
+        ..Default::default()
+    }
+);
+
+const INVALID: &str = merge_whitespace!(
+    "a   b",
+    MergeOptions {
+        quote_char: Some('"'),
+        escape_char: Some('"'),
+    }
+);
+
+fn main() {
+    println!("{TABS} {INVALID}");
+}
@@ -0,0 +1,11 @@
+use merge_whitespace::merge_whitespace;
+
+const LINES: &str = merge_whitespace!("  a   b  c ", MergeOptions { replacement: "\n", ..Default::default() });
+const TABS: &str = merge_whitespace!("a   b", MergeOptions { replacement: "\t\t", ..Default::default() });
+
+fn main() {
+    assert!(LINES.contains('\n'));
+    assert!(!LINES.contains('\\'));
+    assert_eq!(LINES, "a\nb\nc");
+    assert_eq!(TABS, "a\t\tb");
+}
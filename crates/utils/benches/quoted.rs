@@ -0,0 +1,26 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use merge_whitespace_utils::merge_whitespace_with_quotes;
+
+/// Builds a large input consisting mostly of quoted text.
+fn mostly_quoted_input() -> String {
+    let mut input = String::new();
+    for i in 0..1000 {
+        input.push_str(&format!(
+            "field_{i}:   \"a  long   quoted   value with \\\"escaped\\\" quotes   and   spaces\"\n"
+        ));
+    }
+    input
+}
+
+fn bench_quoted(c: &mut Criterion) {
+    let input = mostly_quoted_input();
+    let mut group = c.benchmark_group("quoted");
+    group.throughput(Throughput::Bytes(input.len() as u64));
+    group.bench_function("mostly_quoted", |b| {
+        b.iter(|| merge_whitespace_with_quotes(black_box(&input), Some('"'), Some('\\')))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_quoted);
+criterion_main!(benches);
@@ -0,0 +1,65 @@
+//! Reads a file and prints its contents with consecutive whitespace merged.
+//!
+//! ```text
+//! cargo run --example merge_file -- [--quote <CHAR>] [--escape <CHAR>] <PATH>
+//! ```
+
+use std::process::ExitCode;
+
+use merge_whitespace_utils::merge_whitespace_with_quotes;
+
+const USAGE: &str = "Usage: merge_file [--quote <CHAR>] [--escape <CHAR>] <PATH>";
+
+fn main() -> ExitCode {
+    let mut quote_char = None;
+    let mut escape_char = None;
+    let mut path = None;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--quote" => match parse_char(args.next()) {
+                Some(c) => quote_char = Some(c),
+                None => return usage_error("--quote expects a single character"),
+            },
+            "--escape" => match parse_char(args.next()) {
+                Some(c) => escape_char = Some(c),
+                None => return usage_error("--escape expects a single character"),
+            },
+            _ if path.is_none() => path = Some(arg),
+            _ => return usage_error("unexpected argument"),
+        }
+    }
+
+    let Some(path) = path else {
+        return usage_error("missing file path");
+    };
+
+    let input = match std::fs::read_to_string(&path) {
+        Ok(input) => input,
+        Err(e) => {
+            eprintln!("Failed to read {path}: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    println!(
+        "{}",
+        merge_whitespace_with_quotes(&input, quote_char, escape_char)
+    );
+    ExitCode::SUCCESS
+}
+
+fn parse_char(arg: Option<String>) -> Option<char> {
+    let arg = arg?;
+    let mut chars = arg.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Some(c),
+        _ => None,
+    }
+}
+
+fn usage_error(message: &str) -> ExitCode {
+    eprintln!("Error: {message}\n{USAGE}");
+    ExitCode::FAILURE
+}
@@ -0,0 +1,118 @@
+use std::alloc::Allocator;
+use std::ops::Range;
+
+use crate::engine::{merge_into, Sink};
+use crate::MergeOptions;
+
+/// Remove multiple consecutive whitespaces from a given string like
+/// [`merge_whitespace_with_quotes`](crate::merge_whitespace_with_quotes) does, allocating the
+/// output from `alloc`, e.g. an arena.
+///
+/// Since `String` does not support custom allocators, the output is returned as its UTF-8
+/// bytes. It is always allocated, even if it equals the input.
+///
+/// This requires the `allocator_api` feature and a nightly compiler.
+///
+/// ## Example
+///
+/// ```
+/// #![feature(allocator_api)]
+/// # use std::alloc::Global;
+/// # use merge_whitespace_utils::merge_whitespace_in;
+/// let output = merge_whitespace_in(r#"  a   "b   c"  "#, Some('"'), None, Global);
+/// assert_eq!(std::str::from_utf8(&output), Ok(r#"a "b   c""#));
+/// ```
+///
+/// # Return
+///
+/// The UTF-8 bytes of the modified string.
+pub fn merge_whitespace_in<A: Allocator>(
+    input: &str,
+    quote_char: Option<char>,
+    escape_char: Option<char>,
+    alloc: A,
+) -> Vec<u8, A> {
+    let options = MergeOptions {
+        quote_char,
+        escape_char,
+        ..MergeOptions::new()
+    };
+    // Merging with the default replacement never makes the output longer than the input.
+    let mut output = Vec::with_capacity_in(input.len(), alloc);
+    merge_into(input, &options, &mut AllocSink(&mut output));
+    output
+}
+
+/// Appends the UTF-8 bytes of the output to a vector using a custom allocator.
+struct AllocSink<'v, A: Allocator>(&'v mut Vec<u8, A>);
+
+impl<A: Allocator> Sink for AllocSink<'_, A> {
+    fn keep(&mut self, _offset: usize, text: &str) {
+        self.0.extend_from_slice(text.as_bytes());
+    }
+
+    fn replace(&mut self, _range: Range<usize>, with: &str) {
+        self.0.extend_from_slice(with.as_bytes());
+    }
+}
+
+#[cfg(test)]
+#[allow(unsafe_code)]
+mod tests {
+    use super::*;
+    use crate::merge_whitespace_with_quotes;
+    use std::alloc::{AllocError, Layout};
+    use std::cell::{Cell, UnsafeCell};
+    use std::ptr::NonNull;
+
+    /// A bump allocator handing out the memory of a fixed buffer, which is never freed.
+    struct Bump {
+        memory: UnsafeCell<[u8; 1024]>,
+        used: Cell<usize>,
+    }
+
+    impl Bump {
+        fn new() -> Self {
+            Self {
+                memory: UnsafeCell::new([0; 1024]),
+                used: Cell::new(0),
+            }
+        }
+
+        fn contains(&self, ptr: *const u8) -> bool {
+            let base = self.memory.get().cast::<u8>();
+            (base as usize..base as usize + 1024).contains(&(ptr as usize))
+        }
+    }
+
+    unsafe impl Allocator for &Bump {
+        fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+            let base = self.memory.get().cast::<u8>();
+            let address = base as usize + self.used.get();
+            let start = address.next_multiple_of(layout.align()) - base as usize;
+            let end = start.checked_add(layout.size()).ok_or(AllocError)?;
+            if end > 1024 {
+                return Err(AllocError);
+            }
+            self.used.set(end);
+            let ptr = NonNull::new(base.wrapping_add(start)).ok_or(AllocError)?;
+            Ok(NonNull::slice_from_raw_parts(ptr, layout.size()))
+        }
+
+        unsafe fn deallocate(&self, _ptr: NonNull<u8>, _layout: Layout) {}
+    }
+
+    #[test]
+    fn output_is_allocated_from_the_arena() {
+        let bump = Bump::new();
+        for input in ["  a   \"b   c\"  d  ", "a\\   b", "", "unchanged"] {
+            let output = merge_whitespace_in(input, Some('"'), Some('\\'), &bump);
+            let expected = merge_whitespace_with_quotes(input, Some('"'), Some('\\'));
+            assert_eq!(std::str::from_utf8(&output), Ok(&*expected));
+            if !input.is_empty() {
+                assert!(bump.contains(output.as_ptr()));
+            }
+        }
+        assert!(bump.used.get() > 0);
+    }
+}
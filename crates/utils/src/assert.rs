@@ -0,0 +1,67 @@
+/// Asserts that a string contains no two consecutive ASCII spaces outside of quoted text,
+/// and returns it unchanged.
+///
+/// This is intended as a safety net around already-merged constants: when used in a `const`
+/// context, a violation is reported at compile time. Text between a pair of `"` characters
+/// is considered quoted and is not checked; within it, `\` escapes the following character.
+///
+/// ## Example
+///
+/// ```
+/// # use merge_whitespace_utils::assert_no_double_space;
+/// const QUERY: &str = assert_no_double_space(r#"query { user(name: "a  b") { id } }"#);
+/// ```
+///
+/// A violation fails the compilation:
+///
+/// ```compile_fail
+/// # use merge_whitespace_utils::assert_no_double_space;
+/// const QUERY: &str = assert_no_double_space("query {  user { id } }");
+/// ```
+///
+/// # Panics
+///
+/// Panics if the string contains two consecutive spaces outside of quoted text.
+pub const fn assert_no_double_space(s: &str) -> &str {
+    let bytes = s.as_bytes();
+    let mut in_quotes = false;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' if in_quotes => i += 1,
+            b'"' => in_quotes = !in_quotes,
+            b' ' if !in_quotes && i + 1 < bytes.len() && bytes[i + 1] == b' ' => {
+                panic!("string contains two consecutive spaces outside of quotes")
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MERGED: &str = assert_no_double_space("query { user(name: \"a  \\\"  b\") { id } }");
+
+    #[test]
+    fn merged_string_passes() {
+        assert_eq!(MERGED, "query { user(name: \"a  \\\"  b\") { id } }");
+        assert_eq!(assert_no_double_space(""), "");
+        assert_eq!(assert_no_double_space(" a b "), " a b ");
+    }
+
+    #[test]
+    #[should_panic(expected = "two consecutive spaces")]
+    fn double_space_panics() {
+        assert_no_double_space("query {  user { id } }");
+    }
+
+    #[test]
+    #[should_panic(expected = "two consecutive spaces")]
+    fn double_space_after_quotes_panics() {
+        assert_no_double_space("a \"b  c\"  d");
+    }
+}
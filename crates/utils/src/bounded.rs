@@ -0,0 +1,181 @@
+use std::borrow::Cow;
+use std::ops::Range;
+
+use crate::engine::{merge_into, CowSink, Sink};
+use crate::{MergeOptions, Overflow};
+
+/// Remove multiple consecutive whitespaces from a given string like
+/// [`merge_whitespace_with_quotes`](crate::merge_whitespace_with_quotes) does, unless the output
+/// would be longer than `max_output` bytes, e.g. to process untrusted input in request handlers.
+///
+/// Merging stops as soon as the output exceeds the limit, so no more than `max_output` bytes
+/// are allocated, and the rest of the input is not processed.
+///
+/// ## Example
+///
+/// ```
+/// # use merge_whitespace_utils::{merge_whitespace_bounded, Overflow};
+/// let input = "  Hello     World  ";
+/// assert_eq!(merge_whitespace_bounded(input, None, None, 11).unwrap(), "Hello World");
+/// let error = merge_whitespace_bounded(input, None, None, 10).unwrap_err();
+/// assert_eq!(error.max_output, 10);
+/// ```
+///
+/// # Errors
+///
+/// Returns an [`Overflow`] if the output would exceed `max_output` bytes.
+pub fn merge_whitespace_bounded(
+    input: &str,
+    quote_char: Option<char>,
+    escape_char: Option<char>,
+    max_output: usize,
+) -> Result<Cow<'_, str>, Overflow> {
+    let options = MergeOptions {
+        quote_char,
+        escape_char,
+        ..MergeOptions::new()
+    };
+    let mut sink = BoundedSink {
+        inner: CowSink::new(input).limit_reserve(max_output),
+        len: 0,
+        max_output,
+    };
+    merge_into(input, &options, &mut sink);
+    if sink.len > max_output {
+        return Err(Overflow { max_output });
+    }
+    Ok(sink.inner.into_cow())
+}
+
+/// A [`CowSink`] that drops the output and is full once it exceeds `max_output` bytes.
+struct BoundedSink<'a> {
+    inner: CowSink<'a>,
+    /// The length of the output so far.
+    len: usize,
+    max_output: usize,
+}
+
+impl BoundedSink<'_> {
+    /// Counts `len` further output bytes, determining whether they are within the limit.
+    fn fits(&mut self, len: usize) -> bool {
+        self.len = self.len.saturating_add(len);
+        self.len <= self.max_output
+    }
+}
+
+impl Sink for BoundedSink<'_> {
+    fn keep(&mut self, offset: usize, text: &str) {
+        if self.fits(text.len()) {
+            self.inner.keep(offset, text);
+        }
+    }
+
+    fn replace(&mut self, range: Range<usize>, with: &str) {
+        if self.fits(with.len()) {
+            self.inner.replace(range, with);
+        }
+    }
+
+    fn is_full(&self) -> bool {
+        self.len > self.max_output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::merge_whitespace_with_quotes;
+
+    #[test]
+    fn output_at_the_limit_is_accepted() {
+        let input = r#"  a   "b   c"   d  "#;
+        let expected = merge_whitespace_with_quotes(input, Some('"'), None);
+        assert_eq!(expected.len(), 11);
+        for max_output in [11, 12, usize::MAX] {
+            assert_eq!(
+                merge_whitespace_bounded(input, Some('"'), None, max_output),
+                Ok(expected.clone())
+            );
+        }
+    }
+
+    #[test]
+    fn output_above_the_limit_is_rejected() {
+        let input = r#"  a   "b   c"   d  "#;
+        for max_output in [0, 1, 10] {
+            assert_eq!(
+                merge_whitespace_bounded(input, Some('"'), None, max_output),
+                Err(Overflow { max_output })
+            );
+        }
+    }
+
+    #[test]
+    fn borrowed_output_is_kept() {
+        assert!(matches!(
+            merge_whitespace_bounded("  a b  ", None, None, 3),
+            Ok(Cow::Borrowed("a b"))
+        ));
+        assert!(matches!(
+            merge_whitespace_bounded("   ", None, None, 0),
+            Ok(Cow::Borrowed(""))
+        ));
+    }
+
+    #[test]
+    fn allocation_is_bounded() {
+        let input = "a  ".repeat(100_000);
+        assert!(merge_whitespace_bounded(&input, None, None, 16).is_err());
+
+        let mut sink = BoundedSink {
+            inner: CowSink::new(&input).limit_reserve(16),
+            len: 0,
+            max_output: 16,
+        };
+        merge_into(&input, &MergeOptions::new(), &mut sink);
+        let Err(owned) = sink.inner.into_parts() else {
+            panic!("expected an owned output");
+        };
+        assert!(owned.capacity() < 64, "{}", owned.capacity());
+    }
+
+    #[test]
+    fn merging_stops_at_the_limit() {
+        /// Counts the output passed on to the bounded sink.
+        struct Counting<'a> {
+            inner: BoundedSink<'a>,
+            calls: usize,
+        }
+
+        impl Sink for Counting<'_> {
+            fn keep(&mut self, offset: usize, text: &str) {
+                self.calls += 1;
+                self.inner.keep(offset, text);
+            }
+
+            fn replace(&mut self, range: Range<usize>, with: &str) {
+                self.calls += 1;
+                self.inner.replace(range, with);
+            }
+
+            fn is_full(&self) -> bool {
+                self.inner.is_full()
+            }
+        }
+
+        let input = "a  \"b\"  ".repeat(1_000_000);
+        assert!(merge_whitespace_bounded(&input, Some('"'), None, 4).is_err());
+
+        let mut sink = Counting {
+            inner: BoundedSink {
+                inner: CowSink::new(&input).limit_reserve(4),
+                len: 0,
+                max_output: 4,
+            },
+            calls: 0,
+        };
+        merge_into(&input, &MergeOptions::new().quote_char('"'), &mut sink);
+        assert!(sink.inner.is_full());
+        assert!(sink.calls < 16, "{}", sink.calls);
+    }
+}
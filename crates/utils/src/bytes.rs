@@ -0,0 +1,363 @@
+use std::borrow::Cow;
+
+/// Remove multiple consecutive ASCII whitespaces from a byte slice and replace them with a
+/// single space, like [`merge_whitespace_with_quotes`](crate::merge_whitespace_with_quotes)
+/// does for strings. Leading and trailing ASCII whitespace is removed.
+///
+/// Only the [`COLLAPSIBLE_ASCII_WHITESPACE`](crate::COLLAPSIBLE_ASCII_WHITESPACE) bytes are
+/// merged, and all other bytes, including data that is not valid UTF-8,
+/// are kept untouched. Text between a pair of quote bytes is kept as-is, as is the byte following
+/// an escape byte.
+///
+/// ## Example
+///
+/// ```
+/// # use merge_whitespace_utils::merge_whitespace_bytes;
+/// let output = merge_whitespace_bytes(b"  a   \"b   c\"  \xFF ", Some(b'"'), None);
+/// assert_eq!(&*output, b"a \"b   c\" \xFF");
+/// ```
+///
+/// # Return
+///
+/// The modified bytes.
+pub fn merge_whitespace_bytes(
+    input: &[u8],
+    quote_char: Option<u8>,
+    escape_char: Option<u8>,
+) -> Cow<'_, [u8]> {
+    let start = leading_whitespace(input);
+    let mut verify = Verify {
+        input,
+        start,
+        modified: false,
+    };
+    let len = compact(&mut verify, input.len(), start, quote_char, escape_char);
+    if !verify.modified {
+        return Cow::Borrowed(&input[start..start + len]);
+    }
+
+    let mut output = input.to_vec();
+    merge_whitespace_bytes_in_place(&mut output, quote_char, escape_char);
+    Cow::Owned(output)
+}
+
+/// Remove multiple consecutive ASCII whitespaces from a byte buffer in place, like
+/// [`merge_whitespace_bytes`] does. The merged bytes are moved to the front of the buffer,
+/// which is then truncated, so no allocation takes place.
+///
+/// ## Example
+///
+/// ```
+/// # use merge_whitespace_utils::merge_whitespace_bytes_in_place;
+/// let mut buf = b"  a   \"b   c\"  d ".to_vec();
+/// merge_whitespace_bytes_in_place(&mut buf, Some(b'"'), None);
+/// assert_eq!(buf, b"a \"b   c\" d");
+/// ```
+pub fn merge_whitespace_bytes_in_place(
+    buf: &mut Vec<u8>,
+    quote_char: Option<u8>,
+    escape_char: Option<u8>,
+) {
+    let start = leading_whitespace(buf);
+    let len = buf.len();
+    let len = compact(&mut InPlace(buf), len, start, quote_char, escape_char);
+    buf.truncate(len);
+}
+
+/// A whitespace merger for bytes arriving in chunks, e.g. network frames, which produces the
+/// same output as [`merge_whitespace_bytes`] does for the concatenated chunks.
+///
+/// The state is carried across chunks, so quoted regions, escapes and runs of whitespace may
+/// straddle chunk boundaries. Since only ASCII whitespace is merged, multi-byte UTF-8 sequences
+/// split between chunks are kept intact.
+///
+/// ## Example
+///
+/// ```
+/// # use merge_whitespace_utils::ByteMerger;
+/// let mut merger = ByteMerger::new(Some(b'"'), None);
+/// let mut out = Vec::new();
+/// for chunk in [&b"  a   \"b "[..], b"  c\"  ", b"  d \xC3", b"\xBC  "] {
+///     merger.push(chunk, &mut out);
+/// }
+/// merger.finish();
+/// assert_eq!(out, "a \"b   c\" d ü".as_bytes());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ByteMerger {
+    quote_char: Option<u8>,
+    escape_char: Option<u8>,
+    state: State,
+    /// The whitespace output held back until it is known not to be trailing.
+    held: Vec<u8>,
+}
+
+impl ByteMerger {
+    /// Creates a new merger using the specified quote and escape bytes.
+    pub fn new(quote_char: Option<u8>, escape_char: Option<u8>) -> Self {
+        Self {
+            quote_char,
+            escape_char,
+            state: State::default(),
+            held: Vec::new(),
+        }
+    }
+
+    /// Merges the next chunk of the input, appending the output to `out`.
+    ///
+    /// Whitespace at the end of the chunk, even in a quoted region, is held back until the next
+    /// chunk shows whether it is followed by more text or trailing whitespace.
+    pub fn push(&mut self, chunk: &[u8], out: &mut Vec<u8>) {
+        for &byte in chunk {
+            // Like text following whitespace, an escape proves that it is not trailing.
+            let escaped = self.state.in_escape;
+            let held = &mut self.held;
+            self.state
+                .push(byte, self.quote_char, self.escape_char, |byte| {
+                    if byte.is_ascii_whitespace() && !escaped {
+                        held.push(byte);
+                    } else {
+                        out.append(held);
+                        out.push(byte);
+                    }
+                });
+        }
+    }
+
+    /// Ends the input, discarding any trailing whitespace, and resets the merger so that it
+    /// can be used for the next input.
+    pub fn finish(&mut self) {
+        self.state = State::default();
+        self.held.clear();
+    }
+}
+
+/// The state of merging, carried from one byte to the next.
+#[derive(Debug, Clone, Copy, Default)]
+struct State {
+    started: bool,
+    in_quote: bool,
+    in_escape: bool,
+    in_run: bool,
+}
+
+impl State {
+    /// Processes the next input byte, passing the resulting output bytes to `write`.
+    fn push(
+        &mut self,
+        byte: u8,
+        quote_char: Option<u8>,
+        escape_char: Option<u8>,
+        mut write: impl FnMut(u8),
+    ) {
+        if !self.in_escape && !self.in_quote && byte.is_ascii_whitespace() {
+            // Leading whitespace is removed entirely.
+            self.in_run = self.started;
+            return;
+        }
+        if self.in_run {
+            write(b' ');
+            self.in_run = false;
+        }
+        self.started = true;
+        if self.in_escape {
+            self.in_escape = false;
+        } else if Some(byte) == escape_char {
+            self.in_escape = true;
+        } else if Some(byte) == quote_char {
+            self.in_quote = !self.in_quote;
+        }
+        write(byte);
+    }
+}
+
+/// Determines the number of leading ASCII whitespace bytes.
+fn leading_whitespace(input: &[u8]) -> usize {
+    input
+        .iter()
+        .position(|b| !b.is_ascii_whitespace())
+        .unwrap_or(input.len())
+}
+
+/// The storage the merged bytes are written to. A byte is never written at a position after
+/// the one it is read from, so the input can be compacted in place.
+trait Output {
+    /// Reads the input byte at `index`.
+    fn read(&self, index: usize) -> u8;
+
+    /// Writes the output byte at `index`.
+    fn write(&mut self, index: usize, byte: u8);
+}
+
+/// Compacts the buffer in place.
+struct InPlace<'a>(&'a mut [u8]);
+
+impl Output for InPlace<'_> {
+    fn read(&self, index: usize) -> u8 {
+        self.0[index]
+    }
+
+    fn write(&mut self, index: usize, byte: u8) {
+        self.0[index] = byte;
+    }
+}
+
+/// Determines whether the output differs from the input following the leading whitespace.
+struct Verify<'a> {
+    input: &'a [u8],
+    start: usize,
+    modified: bool,
+}
+
+impl Output for Verify<'_> {
+    fn read(&self, index: usize) -> u8 {
+        self.input[index]
+    }
+
+    fn write(&mut self, index: usize, byte: u8) {
+        self.modified |= self.input[self.start + index] != byte;
+    }
+}
+
+/// Merges the `len` input bytes starting at `start`, writing the output from index zero on.
+///
+/// # Return
+///
+/// The length of the output.
+fn compact(
+    output: &mut impl Output,
+    len: usize,
+    start: usize,
+    quote_char: Option<u8>,
+    escape_char: Option<u8>,
+) -> usize {
+    let end = (start..len)
+        .rev()
+        .find(|&index| !output.read(index).is_ascii_whitespace())
+        .map_or(start, |index| index + 1);
+
+    let (mut written, mut read) = (0, start);
+    let mut state = State::default();
+    // An escaped whitespace byte at the end of the input is not trimmed.
+    while read < end || (state.in_escape && read < len) {
+        let byte = output.read(read);
+        read += 1;
+        state.push(byte, quote_char, escape_char, |byte| {
+            output.write(written, byte);
+            written += 1;
+        });
+    }
+    written
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::merge_whitespace_with_quotes;
+    use crate::test_support::random_inputs;
+
+    const QUOTE: Option<u8> = Some(b'"');
+    const ESCAPE: Option<u8> = Some(b'\\');
+
+    #[test]
+    fn unchanged_input_is_borrowed() {
+        assert!(matches!(
+            merge_whitespace_bytes(b"  a \"b  c\" d\n", QUOTE, ESCAPE),
+            Cow::Borrowed(b"a \"b  c\" d")
+        ));
+        assert!(matches!(
+            merge_whitespace_bytes(b" \t ", QUOTE, ESCAPE),
+            Cow::Borrowed(b"")
+        ));
+        assert!(matches!(
+            merge_whitespace_bytes(b"a  b", QUOTE, ESCAPE),
+            Cow::Owned(_)
+        ));
+    }
+
+    #[test]
+    fn non_utf8_bytes_are_kept() {
+        assert_eq!(
+            &*merge_whitespace_bytes(b"  \xFF  \xFE\"\xC3  \" \\  x ", QUOTE, ESCAPE),
+            b"\xFF \xFE\"\xC3  \" \\  x"
+        );
+    }
+
+    #[test]
+    fn in_place_matches_allocating_api() {
+        const ALPHABET: [char; 8] = [' ', ' ', '\t', 'a', '"', '\\', '\n', '\u{FF}'];
+
+        for input in random_inputs(&ALPHABET, 5000, 16) {
+            // Encoding the characters as Latin-1 turns `ÿ` into the invalid UTF-8 byte `0xFF`.
+            let input: Vec<u8> = input.chars().map(|c| c as u8).collect();
+            for (quote, escape) in [(None, None), (QUOTE, None), (None, ESCAPE), (QUOTE, ESCAPE)] {
+                let mut buf = input.clone();
+                let capacity = buf.capacity();
+                merge_whitespace_bytes_in_place(&mut buf, quote, escape);
+                assert_eq!(buf, &*merge_whitespace_bytes(&input, quote, escape));
+                assert_eq!(buf.capacity(), capacity);
+            }
+        }
+    }
+
+    #[test]
+    fn chunks_split_at_any_offset_match_whole_input() {
+        for input in [
+            "  foo   \"bar   baz\"  qux  ",
+            "  grüße   \"an   alle\"  \u{1F600}  ",
+            r#"a\   b   c\ "#,
+            "a   \"unclosed   quote  ",
+            "\r\n\t x \t\r\n y \n",
+        ] {
+            let input = input.as_bytes();
+            for (quote, escape) in [(None, None), (QUOTE, ESCAPE)] {
+                let expected = merge_whitespace_bytes(input, quote, escape);
+                let mut merger = ByteMerger::new(quote, escape);
+                for first in 0..=input.len() {
+                    for second in first..=input.len() {
+                        let mut out = Vec::new();
+                        merger.push(&input[..first], &mut out);
+                        merger.push(&input[first..second], &mut out);
+                        merger.push(&input[second..], &mut out);
+                        merger.finish();
+                        assert_eq!(out, &*expected, "{input:?} split at {first} and {second}");
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn finish_resets_the_state() {
+        let mut merger = ByteMerger::new(QUOTE, ESCAPE);
+        let mut out = Vec::new();
+        merger.push(b" \"a  ", &mut out);
+        merger.finish();
+        merger.push(b" b   c ", &mut out);
+        assert_eq!(out, b"\"ab c");
+    }
+
+    #[test]
+    fn ascii_input_matches_string_api() {
+        for input in [
+            "  foo   \"bar   baz\"  qux  ",
+            r#"what   \   if I quote\ spaces"#,
+            r#"foo   foobar   "  \"bar   \"   "   baz"#,
+            "a\\   b   c\\ ",
+            "trailing escape\\",
+            "a   \"unclosed   quote  ",
+            "\r\n\t x \t\r\n y \n",
+        ] {
+            for (quote, escape) in [(None, None), (QUOTE, None), (None, ESCAPE), (QUOTE, ESCAPE)] {
+                let expected = merge_whitespace_with_quotes(
+                    input,
+                    quote.map(char::from),
+                    escape.map(char::from),
+                );
+                let mut buf = input.as_bytes().to_vec();
+                merge_whitespace_bytes_in_place(&mut buf, quote, escape);
+                assert_eq!(buf, expected.as_bytes(), "{input:?}");
+            }
+        }
+    }
+}
@@ -0,0 +1,233 @@
+/// The ASCII characters that are merged as whitespace by default: space, `\t`, `\n`, vertical
+/// tab, form feed and `\r`, i.e. the ASCII characters [`char::is_whitespace`] accepts.
+/// Non-ASCII whitespace is merged as well, see [`is_default_whitespace`].
+///
+/// The byte-oriented functions merge the [`COLLAPSIBLE_ASCII_WHITESPACE`] instead.
+pub const ASCII_WHITESPACE: [char; 6] = [' ', '\t', '\n', VERTICAL_TAB, '\u{000C}', '\r'];
+
+/// The ASCII characters that are merged as whitespace by the byte-oriented functions such as
+/// [`merge_whitespace_bytes`](crate::merge_whitespace_bytes): the [`ASCII_WHITESPACE`] except
+/// for the vertical tab.
+///
+/// The vertical tab is excluded because the byte-oriented functions follow
+/// [`u8::is_ascii_whitespace`], which implements the WHATWG Infra Standard's definition of
+/// ASCII whitespace rather than that of [`char::is_whitespace`].
+pub const COLLAPSIBLE_ASCII_WHITESPACE: [char; 5] = without_vertical_tab(ASCII_WHITESPACE);
+
+/// The vertical tab, `U+000B`.
+const VERTICAL_TAB: char = '\u{000B}';
+
+/// Removes the [`VERTICAL_TAB`] from the characters, which must contain it exactly once.
+const fn without_vertical_tab(chars: [char; 6]) -> [char; 5] {
+    let mut output = [' '; 5];
+    let (mut read, mut written) = (0, 0);
+    while read < chars.len() {
+        if chars[read] != VERTICAL_TAB {
+            output[written] = chars[read];
+            written += 1;
+        }
+        read += 1;
+    }
+    output
+}
+
+/// Determines whether `c` is one of the [`COLLAPSIBLE_ASCII_WHITESPACE`] characters, e.g. to
+/// build a tokenizer consistent with [`merge_whitespace_bytes`](crate::merge_whitespace_bytes).
+///
+/// ## Example
+///
+/// ```
+/// # use merge_whitespace_utils::is_collapsible_ascii;
+/// const IS_TAB_COLLAPSIBLE: bool = is_collapsible_ascii('\t');
+/// assert!(IS_TAB_COLLAPSIBLE);
+/// assert!(!is_collapsible_ascii('\u{000B}'));
+/// assert!(!is_collapsible_ascii('\u{00A0}'));
+/// ```
+pub const fn is_collapsible_ascii(c: char) -> bool {
+    let mut index = 0;
+    while index < COLLAPSIBLE_ASCII_WHITESPACE.len() {
+        if COLLAPSIBLE_ASCII_WHITESPACE[index] == c {
+            return true;
+        }
+        index += 1;
+    }
+    false
+}
+
+/// Determines whether `c` is merged as whitespace by default, i.e. whether it is one of the
+/// [`ASCII_WHITESPACE`] characters or a Unicode whitespace character such as `U+00A0`
+/// (no-break space), e.g. to build custom predicates consistent with the default behavior.
+///
+/// ## Example
+///
+/// ```
+/// # use merge_whitespace_utils::is_default_whitespace;
+/// assert!(is_default_whitespace('\t'));
+/// assert!(is_default_whitespace('\u{2003}'));
+/// assert!(!is_default_whitespace('\u{200B}'));
+/// ```
+pub fn is_default_whitespace(c: char) -> bool {
+    c.is_whitespace()
+}
+
+/// The category of a whitespace character.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum WhitespaceCategory {
+    /// A space, such as `' '`, `U+00A0` (no-break space) or `U+2003` (em space).
+    Space,
+    /// A horizontal tab (`'\t'`).
+    Tab,
+    /// A line break, such as `'\n'`, `'\r'`, a vertical tab, a form feed or `U+2028`
+    /// (line separator).
+    LineBreak,
+}
+
+impl WhitespaceCategory {
+    /// Determines the category of a character, or `None` if it is not whitespace.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use merge_whitespace_utils::WhitespaceCategory;
+    /// assert_eq!(WhitespaceCategory::of('\u{00A0}'), Some(WhitespaceCategory::Space));
+    /// assert_eq!(WhitespaceCategory::of('\r'), Some(WhitespaceCategory::LineBreak));
+    /// assert_eq!(WhitespaceCategory::of('a'), None);
+    /// ```
+    pub fn of(c: char) -> Option<Self> {
+        match c {
+            '\t' => Some(Self::Tab),
+            '\n' | '\u{000B}' | '\u{000C}' | '\r' | '\u{0085}' | '\u{2028}' | '\u{2029}' => {
+                Some(Self::LineBreak)
+            }
+            c if c.is_whitespace() => Some(Self::Space),
+            _ => None,
+        }
+    }
+
+    /// The canonical separator of this category: `' '`, `'\t'` or `'\n'`.
+    pub const fn separator(self) -> char {
+        match self {
+            Self::Space => ' ',
+            Self::Tab => '\t',
+            Self::LineBreak => '\n',
+        }
+    }
+}
+
+/// The characters runs of whitespace are replaced with, depending on their
+/// [dominant category](crate::MergeOptions::category_replacement).
+///
+/// ## Example
+///
+/// ```
+/// # use merge_whitespace_utils::{CategoryReplacement, WhitespaceCategory};
+/// let replacement = CategoryReplacement::new().tab(' ');
+/// assert_eq!(replacement.get(WhitespaceCategory::Tab), ' ');
+/// assert_eq!(replacement.get(WhitespaceCategory::LineBreak), '\n');
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct CategoryReplacement {
+    /// The replacement of runs consisting mostly of spaces. Defaults to `' '`.
+    pub space: char,
+    /// The replacement of runs consisting mostly of tabs. Defaults to `'\t'`.
+    pub tab: char,
+    /// The replacement of runs consisting mostly of line breaks. Defaults to `'\n'`.
+    pub line_break: char,
+}
+
+impl CategoryReplacement {
+    /// Creates a mapping of each category to its [separator](WhitespaceCategory::separator).
+    pub const fn new() -> Self {
+        Self {
+            space: WhitespaceCategory::Space.separator(),
+            tab: WhitespaceCategory::Tab.separator(),
+            line_break: WhitespaceCategory::LineBreak.separator(),
+        }
+    }
+
+    /// Sets the replacement of runs consisting mostly of spaces.
+    #[must_use]
+    pub const fn space(mut self, space: char) -> Self {
+        self.space = space;
+        self
+    }
+
+    /// Sets the replacement of runs consisting mostly of tabs.
+    #[must_use]
+    pub const fn tab(mut self, tab: char) -> Self {
+        self.tab = tab;
+        self
+    }
+
+    /// Sets the replacement of runs consisting mostly of line breaks.
+    #[must_use]
+    pub const fn line_break(mut self, line_break: char) -> Self {
+        self.line_break = line_break;
+        self
+    }
+
+    /// Gets the replacement of the category.
+    pub const fn get(&self, category: WhitespaceCategory) -> char {
+        match category {
+            WhitespaceCategory::Space => self.space,
+            WhitespaceCategory::Tab => self.tab,
+            WhitespaceCategory::LineBreak => self.line_break,
+        }
+    }
+}
+
+impl Default for CategoryReplacement {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ascii_whitespace_matches_char_is_whitespace() {
+        for c in (0..=0x7F).filter_map(char::from_u32) {
+            assert_eq!(ASCII_WHITESPACE.contains(&c), c.is_whitespace(), "{c:?}");
+            assert_eq!(is_default_whitespace(c), c.is_whitespace(), "{c:?}");
+        }
+
+        // Unicode spaces are not ASCII, but merged by default.
+        for c in ['\u{0085}', '\u{00A0}', '\u{2003}', '\u{2028}', '\u{3000}'] {
+            assert!(!ASCII_WHITESPACE.contains(&c), "{c:?}");
+            assert!(is_default_whitespace(c), "{c:?}");
+        }
+    }
+
+    #[test]
+    fn collapsible_ascii_matches_byte_merging() {
+        for byte in 0..=0x7F_u8 {
+            let c = char::from(byte);
+            assert_eq!(
+                is_collapsible_ascii(c),
+                COLLAPSIBLE_ASCII_WHITESPACE.contains(&c)
+            );
+            assert_eq!(is_collapsible_ascii(c), byte.is_ascii_whitespace());
+
+            let input = [b'a', byte, byte, b'b'];
+            let merged = crate::merge_whitespace_bytes(&input, None, None);
+            match is_collapsible_ascii(c) {
+                true => assert_eq!(&*merged, b"a b", "{c:?}"),
+                false => assert_eq!(&*merged, input, "{c:?}"),
+            }
+        }
+        assert!(!is_collapsible_ascii('\u{00A0}'));
+    }
+
+    #[test]
+    fn all_whitespace_is_categorized() {
+        for c in (0..=0x10FFFF).filter_map(char::from_u32) {
+            assert_eq!(
+                WhitespaceCategory::of(c).is_some(),
+                c.is_whitespace(),
+                "{c:?}"
+            );
+        }
+    }
+}
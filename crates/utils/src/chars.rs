@@ -0,0 +1,140 @@
+use std::collections::VecDeque;
+use std::ops::Range;
+
+use crate::engine::{Engine, Sink};
+use crate::MergeOptions;
+
+/// Lazily yields the characters of the output of
+/// [`merge_whitespace_with_quotes`](crate::merge_whitespace_with_quotes), without building a
+/// `String`, e.g. to fill a fixed-size buffer and stop early.
+///
+/// ## Example
+///
+/// ```
+/// # use merge_whitespace_utils::chars_merged;
+/// let input = r#"  Hello   "big   wide"   World  "#;
+/// let prefix: String = chars_merged(input, Some('"'), None).take(15).collect();
+/// assert_eq!(prefix, r#"Hello "big   wi"#);
+/// ```
+///
+/// # Return
+///
+/// An iterator over the merged characters.
+pub fn chars_merged(
+    input: &str,
+    quote_char: Option<char>,
+    escape_char: Option<char>,
+) -> impl Iterator<Item = char> + '_ {
+    let options = MergeOptions {
+        quote_char,
+        escape_char,
+        ..MergeOptions::new()
+    };
+    let mut engine = Engine::new(&options);
+    let start = input.len() - input.trim_start().len();
+    let end = input.trim_end().len();
+    let mut chars = input
+        .char_indices()
+        .skip_while(move |&(offset, _)| offset < start);
+    let mut buffer = CharBuffer::default();
+    let mut finished = false;
+    std::iter::from_fn(move || loop {
+        if let Some(c) = buffer.0.pop_front() {
+            return Some(c);
+        }
+        if finished {
+            return None;
+        }
+        match chars.next() {
+            // An escaped whitespace character at the end of the input is not trimmed.
+            Some((offset, c)) if offset < end || engine.is_escaped() => {
+                engine.push(offset, c, &mut buffer);
+            }
+            _ => {
+                engine.finish(&mut buffer);
+                finished = true;
+            }
+        }
+    })
+}
+
+/// A [`Sink`] buffering the merged characters until the iterator yields them.
+#[derive(Default)]
+struct CharBuffer(VecDeque<char>);
+
+impl Sink for CharBuffer {
+    fn keep(&mut self, _offset: usize, text: &str) {
+        self.0.extend(text.chars());
+    }
+
+    fn replace(&mut self, _range: Range<usize>, with: &str) {
+        self.0.extend(with.chars());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::random_inputs;
+    use crate::{merge_whitespace, merge_whitespace_with_quotes};
+
+    const INPUTS: [&str; 8] = [
+        "",
+        " \t\n ",
+        "  foo   \"bar   baz\"  qux  ",
+        "  grüße \u{3000}  \"an   alle\"  \u{1F600}  ",
+        r#"what   \   if I quote\ spaces"#,
+        "a\\   b   c\\ ",
+        "trailing escape\\",
+        "a   \"unclosed   quote  ",
+    ];
+
+    #[test]
+    fn collected_chars_match_eager_merge() {
+        for input in INPUTS {
+            let merged: String = chars_merged(input, None, None).collect();
+            assert_eq!(merged, merge_whitespace(input), "{input:?}");
+            for (quote, escape) in [
+                (Some('"'), None),
+                (None, Some('\\')),
+                (Some('"'), Some('\\')),
+            ] {
+                let merged: String = chars_merged(input, quote, escape).collect();
+                let expected = merge_whitespace_with_quotes(input, quote, escape);
+                assert_eq!(merged, expected, "{input:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn generated_inputs_match_eager_merge() {
+        const ALPHABET: [char; 9] = [' ', ' ', '\t', '\u{a0}', '\n', 'a', 'ü', '"', '\\'];
+
+        for input in random_inputs(&ALPHABET, 5000, 14) {
+            for (quote, escape) in [
+                (None, None),
+                (Some('"'), None),
+                (None, Some('\\')),
+                (Some('"'), Some('\\')),
+            ] {
+                let merged: String = chars_merged(&input, quote, escape).collect();
+                let expected = merge_whitespace_with_quotes(&input, quote, escape);
+                assert_eq!(merged, expected, "{input:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn first_chars_are_a_prefix_of_the_output() {
+        for input in INPUTS {
+            let expected: Vec<char> = merge_whitespace_with_quotes(input, Some('"'), Some('\\'))
+                .chars()
+                .collect();
+            for n in 0..=expected.len() + 1 {
+                let merged: Vec<char> =
+                    chars_merged(input, Some('"'), Some('\\')).take(n).collect();
+                assert_eq!(merged, expected[..n.min(expected.len())], "{input:?}");
+            }
+        }
+    }
+}
@@ -0,0 +1,172 @@
+use std::borrow::Cow;
+
+use crate::engine::{merge_trimmed, CowSink, Engine, RunAction, RunHook};
+use crate::MergeOptions;
+
+/// Specifies how a run of whitespace is handled by [`merge_whitespace_decide`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum CollapseDecision {
+    /// Replace the run with a single space.
+    Collapse,
+    /// Keep the run as-is.
+    Preserve,
+    /// Remove the run entirely.
+    Remove,
+}
+
+/// Remove multiple consecutive whitespaces from a given string like
+/// [`merge_whitespace_with_quotes`](crate::merge_whitespace_with_quotes), while letting `decide`
+/// choose how each run of whitespace is handled.
+///
+/// The callback receives the text of each run between two non-whitespace characters in order.
+/// Leading and trailing whitespace is removed without consulting it.
+///
+/// ## Example
+///
+/// ```
+/// # use merge_whitespace_utils::{merge_whitespace_decide, CollapseDecision};
+/// let output = merge_whitespace_decide("a   b \n\n c   \"d   e\"", Some('"'), None, |run| {
+///     match run.contains('\n') {
+///         true => CollapseDecision::Preserve,
+///         false => CollapseDecision::Collapse,
+///     }
+/// });
+/// assert_eq!(output, "a b \n\n c \"d   e\"");
+/// ```
+///
+/// # Return
+///
+/// The modified string.
+pub fn merge_whitespace_decide(
+    input: &str,
+    quote_char: Option<char>,
+    escape_char: Option<char>,
+    mut decide: impl FnMut(&str) -> CollapseDecision,
+) -> Cow<'_, str> {
+    let options = MergeOptions {
+        quote_char,
+        escape_char,
+        ..MergeOptions::new()
+    };
+    let hook = RunHook {
+        input,
+        action: RunAction::Decide(&mut decide),
+    };
+    let mut engine = Engine::new(&options).with_run_hook(hook);
+    let mut sink = CowSink::new(input);
+    merge_trimmed(&mut engine, input, options.trim, &mut sink);
+    engine.finish(&mut sink);
+    sink.into_cow()
+}
+
+/// Remove multiple consecutive whitespaces from a given string like
+/// [`merge_whitespace_with_quotes`](crate::merge_whitespace_with_quotes), while letting `replace`
+/// compute the replacement of each run of whitespace.
+///
+/// The callback receives the text of each run between two non-whitespace characters in order.
+/// Leading and trailing whitespace is removed without consulting it.
+///
+/// ## Example
+///
+/// ```
+/// # use std::borrow::Cow;
+/// # use merge_whitespace_utils::merge_whitespace_with_fn;
+/// let output = merge_whitespace_with_fn("a   b \n\n c   \"d   e\"", Some('"'), None, |run| {
+///     match run.contains('\n') {
+///         true => Cow::Borrowed("\n"),
+///         false => Cow::Borrowed(" "),
+///     }
+/// });
+/// assert_eq!(output, "a b\nc \"d   e\"");
+/// ```
+///
+/// # Return
+///
+/// The modified string.
+pub fn merge_whitespace_with_fn<'a>(
+    input: &'a str,
+    quote_char: Option<char>,
+    escape_char: Option<char>,
+    replace: impl Fn(&str) -> Cow<'_, str>,
+) -> Cow<'a, str> {
+    let options = MergeOptions {
+        quote_char,
+        escape_char,
+        ..MergeOptions::new()
+    };
+    let hook = RunHook {
+        input,
+        action: RunAction::Replace(&replace),
+    };
+    let mut engine = Engine::new(&options).with_run_hook(hook);
+    let mut sink = CowSink::new(input);
+    merge_trimmed(&mut engine, input, options.trim, &mut sink);
+    engine.finish(&mut sink);
+    sink.into_cow()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn preserve_line_breaks(run: &str) -> CollapseDecision {
+        match run.contains('\n') {
+            true => CollapseDecision::Preserve,
+            false => CollapseDecision::Collapse,
+        }
+    }
+
+    #[test]
+    fn runs_with_line_breaks_are_preserved() {
+        let input = "  a    b\n\n  c\t\td  \n  \"e    \n f\"   g  \n";
+        assert_eq!(
+            merge_whitespace_decide(input, Some('"'), None, preserve_line_breaks),
+            "a b\n\n  c d  \n  \"e    \n f\" g"
+        );
+    }
+
+    #[test]
+    fn runs_are_reported_in_order() {
+        let mut runs = Vec::new();
+        let output = merge_whitespace_decide("  a  b\tc \n d  ", None, None, |run| {
+            runs.push(run.to_string());
+            CollapseDecision::Remove
+        });
+        assert_eq!(output, "abcd");
+        assert_eq!(runs, ["  ", "\t", " \n "]);
+    }
+
+    fn line_break_or_space(run: &str) -> Cow<'_, str> {
+        match run.contains(['\n', '\r']) {
+            true => Cow::Borrowed("\n"),
+            false => Cow::Borrowed(" "),
+        }
+    }
+
+    #[test]
+    fn runs_are_replaced_with_computed_text() {
+        let input = "  a    b\n\n  c\t\td  \r\n  \"e    \n f\"   g  \n";
+        assert_eq!(
+            merge_whitespace_with_fn(input, Some('"'), None, line_break_or_space),
+            "a b\nc d\n\"e    \n f\" g"
+        );
+        assert!(matches!(
+            merge_whitespace_with_fn(" a b\t", None, None, line_break_or_space),
+            Cow::Borrowed("a b")
+        ));
+    }
+
+    #[test]
+    fn replacement_may_depend_on_the_run() {
+        let output = merge_whitespace_with_fn("a b  c   d", None, None, |run| {
+            Cow::Owned(run.len().to_string())
+        });
+        assert_eq!(output, "a1b2c3d");
+    }
+
+    #[test]
+    fn unchanged_input_is_borrowed() {
+        let output = merge_whitespace_decide("a  b", None, None, |_| CollapseDecision::Preserve);
+        assert!(matches!(output, Cow::Borrowed("a  b")));
+    }
+}
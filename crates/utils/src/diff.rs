@@ -0,0 +1,135 @@
+use std::ops::Range;
+
+use crate::engine::{merge_into, Sink};
+use crate::MergeOptions;
+
+/// A replacement of the bytes in `range` of the input with the string `with`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Replacement {
+    /// The byte range of the input to replace.
+    pub range: Range<usize>,
+    /// The text to insert in place of the range.
+    pub with: String,
+}
+
+/// Determines the changes [`merge_whitespace_with_quotes`](crate::merge_whitespace_with_quotes)
+/// makes to the input without applying them, e.g. to present them as edits in an editor.
+///
+/// The replacements are ordered by their position, do not overlap and are not adjacent to each
+/// other. Applying all of them to the input produces the merged output.
+///
+/// ## Example
+///
+/// ```
+/// # use merge_whitespace_utils::{merge_whitespace_diff, Replacement};
+/// let diff = merge_whitespace_diff("  a   b \"c  d\"", Some('"'), None);
+/// assert_eq!(
+///     diff,
+///     [
+///         Replacement { range: 0..2, with: String::new() },
+///         Replacement { range: 3..6, with: String::from(" ") },
+///     ]
+/// );
+/// ```
+///
+/// # Return
+///
+/// The replacements, which are empty if the input is already merged.
+pub fn merge_whitespace_diff(
+    input: &str,
+    quote_char: Option<char>,
+    escape_char: Option<char>,
+) -> Vec<Replacement> {
+    let options = MergeOptions {
+        quote_char,
+        escape_char,
+        ..MergeOptions::new()
+    };
+    let mut sink = DiffSink {
+        input,
+        replacements: Vec::new(),
+    };
+    merge_into(input, &options, &mut sink);
+    sink.replacements
+}
+
+/// A [`Sink`] collecting the replacements that change the input.
+struct DiffSink<'a> {
+    input: &'a str,
+    replacements: Vec<Replacement>,
+}
+
+impl Sink for DiffSink<'_> {
+    fn keep(&mut self, _offset: usize, _text: &str) {}
+
+    fn replace(&mut self, range: Range<usize>, with: &str) {
+        if self.input[range.clone()] == *with {
+            return;
+        }
+        match self.replacements.last_mut() {
+            Some(last) if last.range.end == range.start => {
+                last.range.end = range.end;
+                last.with.push_str(with);
+            }
+            _ => self.replacements.push(Replacement {
+                range,
+                with: with.to_string(),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::merge_whitespace_with_quotes;
+
+    fn apply(input: &str, diff: &[Replacement]) -> String {
+        let mut output = input.to_string();
+        for replacement in diff.iter().rev() {
+            output.replace_range(replacement.range.clone(), &replacement.with);
+        }
+        output
+    }
+
+    #[test]
+    fn applying_the_diff_merges_the_input() {
+        for input in [
+            "",
+            "   ",
+            "a b",
+            "  Hello     World!\r\n      \"How        are\"         you?  ",
+            r#"foo   foobar   "  \"bar   \"   "   baz"#,
+            "a\\   b   c\\ ",
+            " \u{00A0}a\u{2003}\u{2003}b\t",
+        ] {
+            for (quote, escape) in [(None, None), (Some('"'), Some('\\'))] {
+                let diff = merge_whitespace_diff(input, quote, escape);
+                assert_eq!(
+                    apply(input, &diff),
+                    merge_whitespace_with_quotes(input, quote, escape),
+                    "{input:?}"
+                );
+                assert!(diff
+                    .windows(2)
+                    .all(|pair| pair[0].range.end < pair[1].range.start));
+            }
+        }
+    }
+
+    #[test]
+    fn merged_input_has_no_diff() {
+        assert!(merge_whitespace_diff("a \"b  c\" d", Some('"'), None).is_empty());
+    }
+
+    #[test]
+    fn single_spaces_are_not_replaced() {
+        assert_eq!(
+            merge_whitespace_diff("a b\tc", None, None),
+            [Replacement {
+                range: 3..4,
+                with: String::from(" ")
+            }]
+        );
+    }
+}
@@ -0,0 +1,99 @@
+use std::fmt;
+use std::ops::Range;
+
+use crate::engine::{merge_into, Sink};
+use crate::MergeOptions;
+
+/// Lazily merges the whitespace of the input when formatted, e.g. in log messages.
+///
+/// Unlike [`merge_whitespace_with_quotes`](crate::merge_whitespace_with_quotes), no output
+/// is allocated: the merged text is written to the formatter piece by piece. Since the input
+/// is merged again each time, prefer the eager functions when formatting the value repeatedly.
+///
+/// ## Example
+///
+/// ```
+/// # use merge_whitespace_utils::merged_display;
+/// let raw = r#"  request   "GET   /"  took  12ms "#;
+/// let message = format!("<{}>", merged_display(raw, Some('"'), None));
+/// assert_eq!(message, r#"<request "GET   /" took 12ms>"#);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct MergedDisplay<'a> {
+    input: &'a str,
+    quote_char: Option<char>,
+    escape_char: Option<char>,
+}
+
+/// Wraps the `input` so that its whitespace is merged when formatted, like
+/// [`merge_whitespace_with_quotes`](crate::merge_whitespace_with_quotes) does.
+pub fn merged_display(
+    input: &str,
+    quote_char: Option<char>,
+    escape_char: Option<char>,
+) -> MergedDisplay<'_> {
+    MergedDisplay {
+        input,
+        quote_char,
+        escape_char,
+    }
+}
+
+impl fmt::Display for MergedDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let options = MergeOptions {
+            quote_char: self.quote_char,
+            escape_char: self.escape_char,
+            ..MergeOptions::new()
+        };
+        let mut sink = FormatterSink { f, result: Ok(()) };
+        merge_into(self.input, &options, &mut sink);
+        sink.result
+    }
+}
+
+/// A [`Sink`] writing the output to a formatter, remembering the first error.
+struct FormatterSink<'f, 'b> {
+    f: &'f mut fmt::Formatter<'b>,
+    result: fmt::Result,
+}
+
+impl Sink for FormatterSink<'_, '_> {
+    fn keep(&mut self, _offset: usize, text: &str) {
+        if self.result.is_ok() {
+            self.result = self.f.write_str(text);
+        }
+    }
+
+    fn replace(&mut self, _range: Range<usize>, with: &str) {
+        if self.result.is_ok() {
+            self.result = self.f.write_str(with);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::merge_whitespace_with_quotes;
+
+    #[test]
+    fn formatting_matches_eager_merge() {
+        for input in [
+            "",
+            "   ",
+            "  foo   \"bar   baz\"  qux  ",
+            r#"what   \   if I quote\ spaces"#,
+            "a   \"unclosed   quote  ",
+            "\r\n\t grüße \t\r\n \u{1F600} \n",
+        ] {
+            for (quote, escape) in [(None, None), (Some('"'), Some('\\'))] {
+                assert_eq!(
+                    format!("{}", merged_display(input, quote, escape)),
+                    merge_whitespace_with_quotes(input, quote, escape),
+                    "{input:?}"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,172 @@
+use std::ops::Range;
+
+use crate::engine::{merge_trimmed, Discard, Engine, QuoteState};
+use crate::{MergeOptions, Trim};
+
+/// A change of a text: the bytes in `range` are replaced with `replacement`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Edit<'a> {
+    /// The byte range of the text to replace.
+    pub range: Range<usize>,
+    /// The text to insert in place of the range.
+    pub replacement: &'a str,
+}
+
+/// Applies an [`Edit`] to an already merged string and merges the result again, like
+/// [`merge_whitespace_with_quotes`](crate::merge_whitespace_with_quotes) would do for the
+/// edited text.
+///
+/// Only the edited region and the whitespace surrounding it are processed. If quote or escape
+/// characters are configured, the text preceding the edit is scanned for its quoting state, and
+/// if the edit changes the quoting state of the text following it (e.g. by inserting a quote
+/// character), that text is processed as well.
+///
+/// ## Example
+///
+/// ```
+/// # use merge_whitespace_utils::{merge_whitespace_after_edit, Edit};
+/// let output = merge_whitespace_after_edit(
+///     "Hello World!",
+///     Edit { range: 5..6, replacement: "  big  " },
+///     None,
+///     None,
+/// );
+/// assert_eq!(output, "Hello big World!");
+/// ```
+///
+/// # Panics
+///
+/// Panics if the range of the edit is out of bounds or does not lie on `char` boundaries.
+///
+/// # Return
+///
+/// The merged edited string.
+pub fn merge_whitespace_after_edit(
+    prev_output: &str,
+    edit: Edit,
+    quote_char: Option<char>,
+    escape_char: Option<char>,
+) -> String {
+    let Edit { range, replacement } = edit;
+    let (prefix, suffix) = (&prev_output[..range.start], &prev_output[range.end..]);
+    let options = MergeOptions {
+        quote_char,
+        escape_char,
+        ..MergeOptions::new()
+    };
+
+    // Include the whitespace runs adjacent to the edit.
+    let start = prefix.trim_end().len();
+    let context = suffix.len() - suffix.trim_start().len();
+
+    let mut state = QuoteState::default();
+    if quote_char.is_some() || escape_char.is_some() {
+        state = scan(&prefix[..start], &options, state);
+    }
+
+    let mut window = [&prefix[start..], replacement, &suffix[..context]].concat();
+    let mut rest = &suffix[context..];
+
+    // The remaining text is only unaffected if its quoting state did not change.
+    let old_state = scan(&prev_output[start..range.end + context], &options, state);
+    if scan(&window, &options, state) != old_state {
+        window.push_str(rest);
+        rest = "";
+    }
+
+    let trim = match (start == 0, rest.is_empty()) {
+        (true, true) => Trim::Both,
+        (true, false) => Trim::Start,
+        (false, true) => Trim::End,
+        (false, false) => Trim::None,
+    };
+
+    let mut output = String::with_capacity(prev_output.len() + replacement.len());
+    output.push_str(&prefix[..start]);
+    let mut engine = Engine::with_state(&options, state);
+    merge_trimmed(&mut engine, &window, trim, &mut output);
+    engine.finish(&mut output);
+    output.push_str(rest);
+    output
+}
+
+/// Determines the quoting state after processing `text`, starting from `state`.
+fn scan(text: &str, options: &MergeOptions, state: QuoteState) -> QuoteState {
+    let mut engine = Engine::with_state(options, state);
+    for (offset, c) in text.char_indices() {
+        engine.push(offset, c, &mut Discard);
+    }
+    engine.quote_state()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::merge_whitespace_with_quotes;
+
+    const QUOTE: Option<char> = Some('"');
+    const ESCAPE: Option<char> = Some('\\');
+
+    /// Merges the input, applies the edit and compares the result with a full merge.
+    fn assert_matches_full_merge(
+        input: &str,
+        edit: Edit,
+        quote: Option<char>,
+        escape: Option<char>,
+    ) {
+        let prev_output = merge_whitespace_with_quotes(input, quote, escape);
+        if edit.range.end > prev_output.len() {
+            return;
+        }
+        let mut edited = String::from(&*prev_output);
+        edited.replace_range(edit.range.clone(), edit.replacement);
+        let expected = merge_whitespace_with_quotes(&edited, quote, escape);
+        assert_eq!(
+            merge_whitespace_after_edit(&prev_output, edit.clone(), quote, escape),
+            expected,
+            "output: {prev_output:?}, edit: {edit:?}"
+        );
+    }
+
+    #[test]
+    fn edits_match_full_merge() {
+        let input = r#"query { users (name: "Froozle   \"78\"   Frobnik") { id name } }"#;
+        let edits = [
+            (0..0, "   "),
+            (0..5, ""),
+            (5..6, "\n\n  "),
+            (6..7, ""),
+            (8..8, "  x  "),
+            (14..14, "\""),
+            (22..22, "  a  "),
+            (30..31, ""),
+            (29..29, "\\"),
+            (48..49, ""),
+            (55..57, " \t "),
+            (57..57, "   "),
+            (0..57, " a  b "),
+        ];
+        for (range, replacement) in edits {
+            let edit = Edit { range, replacement };
+            assert_matches_full_merge(input, edit.clone(), QUOTE, ESCAPE);
+            assert_matches_full_merge(input, edit, None, None);
+        }
+    }
+
+    #[test]
+    fn all_small_edits_match_full_merge() {
+        let prev_output = r#"a "b  c" d\  e"#;
+        for start in 0..=prev_output.len() {
+            for end in start..=prev_output.len() {
+                for replacement in ["", " ", "  x  ", "\"", "\\", "\" \""] {
+                    let edit = Edit {
+                        range: start..end,
+                        replacement,
+                    };
+                    assert_matches_full_merge(prev_output, edit.clone(), QUOTE, ESCAPE);
+                    assert_matches_full_merge(prev_output, edit, None, ESCAPE);
+                }
+            }
+        }
+    }
+}
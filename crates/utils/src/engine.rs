@@ -0,0 +1,1401 @@
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::ops::Range;
+
+use crate::{
+    CollapseDecision, CollapseTo, EdgeCollapse, LeadingPolicy, MergeOptions, MergeStats,
+    SentenceSpacing, Trim, WhitespaceCategory,
+};
+
+/// Receives the output of the merging [`Engine`].
+///
+/// Every byte of the input is reported exactly once and in order, either as text that is kept
+/// verbatim or as a range that is replaced with a (possibly empty) string.
+pub(crate) trait Sink {
+    /// Called for input text at `offset` that is kept as-is.
+    fn keep(&mut self, offset: usize, text: &str);
+
+    /// Called for input text at `range` that is replaced with `with`.
+    fn replace(&mut self, range: Range<usize>, with: &str);
+
+    /// Determines whether the sink rejects any further output, in which case merging stops
+    /// without processing the rest of the input.
+    fn is_full(&self) -> bool {
+        false
+    }
+}
+
+/// A run of whitespace.
+struct Run {
+    /// The byte range of the run.
+    range: Range<usize>,
+    /// The first character of the run.
+    first: char,
+    /// The last character of the run.
+    last: char,
+    /// The number of characters of each [`WhitespaceCategory`] in the run.
+    counts: [usize; 3],
+    /// The output column following the run if its tabs were expanded, as tracked for
+    /// [`MergeOptions::tabstop`].
+    end_column: usize,
+    /// The last output character preceding the run, as tracked for
+    /// [`MergeOptions::minimal_spacing`] and [`MergeOptions::between_words_only`].
+    before: Option<char>,
+    /// Whether the run directly follows the end of a quoted region.
+    after_quote: bool,
+    /// The characters of the run, as kept by [`MergeOptions::between_words_only`].
+    text: String,
+}
+
+impl Run {
+    /// Determines the category most characters of the run belong to. Ties are resolved in favor
+    /// of line breaks, then tabs.
+    fn dominant_category(&self) -> WhitespaceCategory {
+        let categories = [
+            WhitespaceCategory::Space,
+            WhitespaceCategory::Tab,
+            WhitespaceCategory::LineBreak,
+        ];
+        categories
+            .into_iter()
+            .zip(self.counts)
+            .max_by_key(|&(_, count)| count)
+            .map_or(WhitespaceCategory::Space, |(category, _)| category)
+    }
+}
+
+/// The quoting state of the [`Engine`] between two characters.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub(crate) struct QuoteState {
+    open_quote: Option<char>,
+    quote_depth: usize,
+    quote_pending_close: bool,
+    in_escape: bool,
+}
+
+/// Custom matching of quote characters, replacing the quote settings of the [`MergeOptions`].
+#[derive(Copy, Clone)]
+pub(crate) struct QuoteMatcher<'m> {
+    /// Determines whether a character opens a quoted region.
+    pub is_open: &'m dyn Fn(char) -> bool,
+    /// Determines whether a character closes the region opened by the given quote character.
+    pub close_matches: &'m dyn Fn(char, char) -> bool,
+}
+
+/// A callback deciding how each run of whitespace is handled.
+pub(crate) struct RunHook<'o> {
+    /// The input the run ranges refer to.
+    pub input: &'o str,
+    /// The callback consulted for each run.
+    pub action: RunAction<'o>,
+}
+
+/// The callback of a [`RunHook`].
+pub(crate) enum RunAction<'o> {
+    /// Decides how the run with the given text is handled.
+    Decide(&'o mut dyn FnMut(&str) -> CollapseDecision),
+    /// Computes the replacement of the run with the given text.
+    Replace(&'o dyn Fn(&str) -> Cow<'_, str>),
+}
+
+/// The whitespace merging state machine.
+pub(crate) struct Engine<'o, 'a> {
+    options: MergeOptions<'a>,
+    /// The quote character of the currently open quoted region, if any.
+    open_quote: Option<char>,
+    /// The number of nested opening quotes within the quoted region, for distinct quote pairs.
+    quote_depth: usize,
+    /// The maximum number of simultaneously open quoted regions so far.
+    max_quote_depth: usize,
+    /// Whether the last character closed the quoted region, unless it is doubled.
+    quote_pending_close: bool,
+    in_escape: bool,
+    /// The pending whitespace run, if any.
+    run: Option<Run>,
+    /// The leading characters of the pending run that are preserved by [`MergeOptions::max_spaces`].
+    run_prefix: String,
+    /// Whether only whitespace followed the last preserved line break.
+    at_line_start: bool,
+    /// Whether the last character was escaped whitespace or followed it, as protected by
+    /// [`MergeOptions::escape_protects_run`].
+    in_protected_run: bool,
+    /// Whether a line comment started by [`MergeOptions::comment_char`] is being removed.
+    in_comment: bool,
+    /// The characters following the last [`MergeOptions::table_separator`] of the current line.
+    /// They are kept as-is if another separator follows on the same line.
+    cell: Option<Vec<(usize, char)>>,
+    /// The output column within the current line, as tracked for [`MergeOptions::tabstop`].
+    column: Cell<usize>,
+    /// The last output character, as tracked for [`MergeOptions::minimal_spacing`],
+    /// [`MergeOptions::between_words_only`], [`MergeOptions::tight_after`],
+    /// [`MergeOptions::trim_between`] and [`MergeOptions::sentence_spacing`].
+    last_char: Cell<Option<char>>,
+    /// The nesting depth of the regions delimited by [`MergeOptions::only_inside`].
+    scope_depth: usize,
+    /// Whether the last character ended a quoted region.
+    closed_quote: bool,
+    /// The character being processed, which follows the pending run.
+    next_char: Option<char>,
+    /// The custom quote matching, if any.
+    matcher: Option<QuoteMatcher<'o>>,
+    /// The callback deciding how each run is handled, if any.
+    run_hook: Option<RunHook<'o>>,
+}
+
+impl<'o, 'a> Engine<'o, 'a> {
+    pub fn new(options: &MergeOptions<'a>) -> Self {
+        Self::with_state(options, QuoteState::default())
+    }
+
+    /// Creates an engine resuming from a quoting state obtained by [`Engine::quote_state`].
+    pub fn with_state(options: &MergeOptions<'a>, state: QuoteState) -> Self {
+        Self {
+            options: options.clone(),
+            open_quote: state.open_quote,
+            quote_depth: state.quote_depth,
+            max_quote_depth: usize::from(state.open_quote.is_some()) + state.quote_depth,
+            quote_pending_close: state.quote_pending_close,
+            in_escape: state.in_escape,
+            run: None,
+            run_prefix: String::new(),
+            at_line_start: false,
+            in_protected_run: false,
+            in_comment: false,
+            cell: None,
+            column: Cell::new(0),
+            last_char: Cell::new(None),
+            scope_depth: 0,
+            closed_quote: false,
+            next_char: None,
+            matcher: None,
+            run_hook: None,
+        }
+    }
+
+    /// Uses the `matcher` to detect quoted regions instead of the quote settings of the options.
+    pub fn with_matcher(mut self, matcher: QuoteMatcher<'o>) -> Self {
+        self.matcher = Some(matcher);
+        self
+    }
+
+    /// Uses the `hook` to decide how each run is handled before applying the options.
+    pub fn with_run_hook(mut self, hook: RunHook<'o>) -> Self {
+        self.run_hook = Some(hook);
+        self
+    }
+
+    /// Gets the current quoting state.
+    pub fn quote_state(&self) -> QuoteState {
+        QuoteState {
+            open_quote: self.open_quote,
+            quote_depth: self.quote_depth,
+            quote_pending_close: self.quote_pending_close,
+            in_escape: self.in_escape,
+        }
+    }
+
+    /// Determines whether the last character was part of a quoted region.
+    pub fn is_quoted(&self) -> bool {
+        self.open_quote.is_some()
+    }
+
+    /// Determines whether the next character is escaped.
+    pub fn is_escaped(&self) -> bool {
+        self.in_escape
+    }
+
+    /// Processes the character `c` found at byte `offset` of the input.
+    pub fn push<S: Sink>(&mut self, offset: usize, c: char, sink: &mut S) {
+        let end = offset + c.len_utf8();
+        let mut buf = [0; 4];
+        self.next_char = Some(c);
+        let mut after_quote = std::mem::take(&mut self.closed_quote);
+
+        if let Some((open, close)) = self.options.only_inside {
+            if self.scope_depth == 0 && c != open {
+                // Text outside of the regions is kept verbatim.
+                self.keep(sink, offset, c.encode_utf8(&mut buf));
+                return;
+            }
+            if self.open_quote.is_none() && !self.in_escape {
+                if c == open {
+                    self.scope_depth += 1;
+                } else if c == close {
+                    self.scope_depth -= 1;
+                }
+            }
+        }
+
+        if self.cell.is_some() {
+            if WhitespaceCategory::of(c) != Some(WhitespaceCategory::LineBreak) {
+                let is_text = self.track_quote(c);
+                let cell = self.cell.as_mut().expect("the cell is open");
+                if is_text && self.options.table_separator == Some(c) {
+                    // The cell is complete, so its content is kept as-is.
+                    let cell = std::mem::take(cell);
+                    for (offset, c) in cell {
+                        self.emit(offset, c, sink);
+                    }
+                    self.emit(offset, c, sink);
+                } else {
+                    cell.push((offset, c));
+                }
+                return;
+            }
+            // The line ends without another separator.
+            self.replay_cell(sink);
+        }
+
+        if self.in_escape && self.options.escape_only_specials && !self.is_escapable(c) {
+            // The escape character was plain text.
+            self.in_escape = false;
+        }
+
+        if self.quote_pending_close {
+            self.quote_pending_close = false;
+            if self
+                .open_quote
+                .is_some_and(|open_quote| self.closes(open_quote, c))
+            {
+                // A doubled quote character is an escaped quote; the region stays open.
+                self.emit(offset, c, sink);
+                return;
+            }
+            self.open_quote = None;
+            after_quote = true;
+        }
+
+        if self.is_escape(c) && !self.in_escape {
+            self.flush_run(sink);
+            self.at_line_start = false;
+            self.in_escape = true;
+            self.keep(sink, offset, c.encode_utf8(&mut buf));
+            return;
+        }
+        if self.in_comment && WhitespaceCategory::of(c) == Some(WhitespaceCategory::LineBreak) {
+            self.in_comment = false;
+        }
+        if self.in_comment
+            || (self.options.comment_char == Some(c)
+                && self.open_quote.is_none()
+                && !self.in_escape)
+        {
+            // Comments inside a run become part of it so that the surrounding whitespace is
+            // merged as a whole.
+            self.in_comment = true;
+            match &mut self.run {
+                Some(run) => run.range.end = end,
+                None => self.replace(sink, offset..end, ""),
+            }
+            return;
+        }
+        if self.in_protected_run && c.is_whitespace() && !self.in_escape {
+            self.emit(offset, c, sink);
+            return;
+        }
+        #[cfg(feature = "unicode")]
+        if self.options.strip_format_chars
+            && self.open_quote.is_none()
+            && !self.in_escape
+            && crate::unicode::is_format_or_control(c)
+        {
+            // Stripped characters inside a run become part of it so that the run is merged as a whole.
+            match &mut self.run {
+                Some(run) => run.range.end = end,
+                None => self.replace(sink, offset..end, ""),
+            }
+            return;
+        }
+        if self.options.split_on == Some(c) && self.open_quote.is_none() && !self.in_escape {
+            // Separators are kept, while the whitespace surrounding them is removed.
+            self.at_line_start = true;
+            self.flush_run(sink);
+            self.emit(offset, c, sink);
+            self.in_protected_run = false;
+            return;
+        }
+        if c.is_whitespace() && self.open_quote.is_none() && !self.in_escape {
+            if self.options.preserve_newlines
+                && WhitespaceCategory::of(c) == Some(WhitespaceCategory::LineBreak)
+            {
+                // Line breaks are kept, while the whitespace surrounding them is removed.
+                self.at_line_start = true;
+                self.flush_run(sink);
+                self.emit(offset, c, sink);
+                return;
+            }
+            if self.options.split_on_type_change
+                && self.run.as_ref().is_some_and(|run| {
+                    WhitespaceCategory::of(run.first) != WhitespaceCategory::of(c)
+                })
+            {
+                self.flush_run(sink);
+            }
+            let run = self.run.get_or_insert(Run {
+                range: offset..offset,
+                first: c,
+                last: c,
+                counts: [0; 3],
+                end_column: self.column.get(),
+                before: self.last_char.get(),
+                after_quote,
+                text: String::new(),
+            });
+            run.range.end = end;
+            run.last = c;
+            if let Some(category) = WhitespaceCategory::of(c) {
+                run.counts[category as usize] += 1;
+            }
+            if let Some(tabstop) = self.options.tabstop {
+                let tabstop = tabstop.max(1);
+                run.end_column = match c {
+                    '\t' => (run.end_column / tabstop + 1) * tabstop,
+                    _ => run.end_column + 1,
+                };
+            }
+            if self.options.between_words_only {
+                run.text.push(c);
+            }
+            if let Some(max_spaces) = self.options.max_spaces {
+                if self.run_prefix.chars().count() < max_spaces {
+                    self.run_prefix.push(self.normalize(c));
+                }
+            }
+            return;
+        }
+        let was_quoted = self.open_quote.is_some();
+        if !self.in_escape {
+            self.update_quote(c);
+        }
+        self.flush_run(sink);
+        self.at_line_start = false;
+        self.emit(offset, c, sink);
+        self.closed_quote = was_quoted && self.open_quote.is_none();
+        if !self.in_escape && self.open_quote.is_none() && self.options.table_separator == Some(c) {
+            self.cell = Some(Vec::new());
+        }
+        self.in_protected_run =
+            self.in_escape && self.options.escape_protects_run && c.is_whitespace();
+        self.in_escape = false;
+    }
+
+    /// Keeps the leading part of `text`, found at byte `offset` of the input, that lies within the
+    /// current quoted region and contains no escape or quote characters. This is equivalent to
+    /// pushing these characters one by one, but copies them in bulk.
+    ///
+    /// # Return
+    ///
+    /// The number of bytes kept, which is zero if the current state requires processing the
+    /// characters individually.
+    pub fn push_quoted<S: Sink>(&mut self, offset: usize, text: &str, sink: &mut S) -> usize {
+        let Some(open_quote) = self.open_quote else {
+            return 0;
+        };
+        #[cfg(feature = "unicode")]
+        if self.options.case_quoted.is_some() {
+            return 0;
+        }
+        if self.in_escape
+            || self.quote_pending_close
+            || self.cell.is_some()
+            || self.matcher.is_some()
+            || self.options.normalize_unicode_space
+        {
+            return 0;
+        }
+
+        let close_quote = self.closing_quote(open_quote);
+        let escape_char = self.options.effective_escape_char();
+        let len = text
+            .find(|c| c == open_quote || c == close_quote || Some(c) == escape_char)
+            .unwrap_or(text.len());
+        if len > 0 {
+            self.keep(sink, offset, &text[..len]);
+            self.at_line_start = false;
+            self.in_protected_run = false;
+        }
+        len
+    }
+
+    /// Keeps `text`, found at byte `offset` of the input, verbatim as if it was quoted, e.g. to
+    /// protect a URL from being modified.
+    ///
+    /// # Return
+    ///
+    /// The number of bytes kept, which is zero if the current state requires processing the
+    /// characters individually, e.g. in quoted text.
+    pub fn push_verbatim<S: Sink>(&mut self, offset: usize, text: &str, sink: &mut S) -> usize {
+        if self.open_quote.is_some()
+            || self.in_escape
+            || self.in_comment
+            || self.quote_pending_close
+            || self.cell.is_some()
+            || (self.options.only_inside.is_some() && self.scope_depth == 0)
+        {
+            return 0;
+        }
+        self.next_char = text.chars().next();
+        self.flush_run(sink);
+        self.keep(sink, offset, text);
+        self.at_line_start = false;
+        self.in_protected_run = false;
+        text.len()
+    }
+
+    /// Keeps `text`, found at byte `offset` of the input, verbatim like
+    /// [`push_verbatim`](Self::push_verbatim) does, but also within quoted regions. The text must
+    /// not contain quote or escape characters.
+    ///
+    /// # Return
+    ///
+    /// The number of bytes kept, which is zero if the current state requires processing the
+    /// characters individually.
+    pub fn push_opaque<S: Sink>(&mut self, offset: usize, text: &str, sink: &mut S) -> usize {
+        if self.open_quote.is_none() {
+            return self.push_verbatim(offset, text, sink);
+        }
+        if self.in_escape || self.quote_pending_close || self.cell.is_some() {
+            return 0;
+        }
+        self.keep(sink, offset, text);
+        text.len()
+    }
+
+    /// Drops the empty quoted region `text`, found at byte `offset` of the input, as enabled by
+    /// [`MergeOptions::drop_empty_quotes`]. The whitespace on both sides of it is merged as a
+    /// single run.
+    ///
+    /// # Return
+    ///
+    /// The number of bytes dropped, which is zero if the current state requires processing the
+    /// characters individually, e.g. in quoted text.
+    pub fn push_dropped<S: Sink>(&mut self, offset: usize, text: &str, sink: &mut S) -> usize {
+        if self.open_quote.is_some()
+            || self.in_escape
+            || self.in_comment
+            || self.quote_pending_close
+            || self.cell.is_some()
+            || (self.options.only_inside.is_some() && self.scope_depth == 0)
+        {
+            return 0;
+        }
+        let range = offset..offset + text.len();
+        match &mut self.run {
+            Some(run) => run.range.end = range.end,
+            None => self.replace(sink, range, ""),
+        }
+        text.len()
+    }
+
+    /// Determines the length of the quoted region at the start of `text` if it contains nothing
+    /// but whitespace, as dropped by [`MergeOptions::drop_empty_quotes`].
+    pub fn empty_quote_len(&self, text: &str) -> Option<usize> {
+        let mut chars = text.chars();
+        let open_quote = chars
+            .next()
+            .filter(|&c| self.is_quote(c) && !(self.options.preserve_parens && c == '('))?;
+        let interior = chars.as_str();
+        let interior_len = interior.len() - interior.trim_start().len();
+        let close_quote = interior[interior_len..]
+            .chars()
+            .next()
+            .filter(|&c| self.closes(open_quote, c))?;
+        let len = open_quote.len_utf8() + interior_len + close_quote.len_utf8();
+        // A doubled closing quote is an escaped quote character within the region.
+        if self.options.doubled_quote_escapes && text[len..].starts_with(close_quote) {
+            return None;
+        }
+        Some(len)
+    }
+
+    /// Opens or closes a quoted region if the unescaped character `c` is a quote character.
+    fn update_quote(&mut self, c: char) {
+        match self.open_quote {
+            Some(open_quote) if self.closes(open_quote, c) => {
+                if self.quote_depth > 0 {
+                    self.quote_depth -= 1;
+                } else if self.options.doubled_quote_escapes {
+                    self.quote_pending_close = true;
+                } else {
+                    self.open_quote = None;
+                }
+            }
+            // Distinct quote pairs can be nested.
+            Some(open_quote) if open_quote == c => {
+                self.quote_depth += 1;
+                self.max_quote_depth = self.max_quote_depth.max(self.quote_depth + 1);
+            }
+            None if self.is_quote(c) => {
+                self.open_quote = Some(c);
+                self.max_quote_depth = self.max_quote_depth.max(1);
+            }
+            _ => {}
+        }
+    }
+
+    /// Updates the quoting state for the character `c` of a table cell, which is buffered rather
+    /// than processed.
+    ///
+    /// # Return
+    ///
+    /// Whether `c` is neither quoted nor escaped, nor a quote or escape character itself.
+    fn track_quote(&mut self, c: char) -> bool {
+        let escaped = std::mem::take(&mut self.in_escape)
+            && (!self.options.escape_only_specials || self.is_escapable(c));
+        if escaped {
+            return false;
+        }
+        if self.quote_pending_close {
+            self.quote_pending_close = false;
+            if self
+                .open_quote
+                .is_some_and(|open_quote| self.closes(open_quote, c))
+            {
+                // A doubled quote character is an escaped quote; the region stays open.
+                return false;
+            }
+            self.open_quote = None;
+        }
+        if self.is_escape(c) {
+            self.in_escape = true;
+            return false;
+        }
+        let was_quoted = self.open_quote.is_some();
+        self.update_quote(c);
+        !was_quoted && self.open_quote.is_none()
+    }
+
+    /// Processes the characters of an incomplete table cell as regular text.
+    fn replay_cell<S: Sink>(&mut self, sink: &mut S) {
+        if let Some(cell) = self.cell.take() {
+            // Cells start outside of quoted text, so the quoting state is reset to its start.
+            self.open_quote = None;
+            self.quote_depth = 0;
+            self.quote_pending_close = false;
+            self.in_escape = false;
+            for (offset, c) in cell {
+                self.push(offset, c, sink);
+            }
+        }
+    }
+
+    /// Completes the processing, flushing any pending whitespace run.
+    pub fn finish<S: Sink>(&mut self, sink: &mut S) -> MergeStats {
+        self.replay_cell(sink);
+        self.next_char = None;
+        self.flush_run(sink);
+        if self.quote_pending_close {
+            self.quote_pending_close = false;
+            self.open_quote = None;
+        }
+        MergeStats {
+            quotes_balanced: self.open_quote.is_none(),
+            max_quote_depth: self.max_quote_depth,
+        }
+    }
+
+    /// Emits the character `c` found at byte `offset` of the input.
+    fn emit<S: Sink>(&self, offset: usize, c: char, sink: &mut S) {
+        let mut buf = [0; 4];
+        let normalized = self.normalize(c);
+        #[cfg(feature = "unicode")]
+        {
+            let case = match self.open_quote {
+                Some(_) => self.options.case_quoted,
+                None => self.options.case_unquoted,
+            };
+            if let Some(case) = case {
+                let mut buf = [0; 12];
+                let converted = crate::unicode::convert_case(normalized, case, &mut buf);
+                if converted != c.encode_utf8(&mut [0; 4]) {
+                    self.replace(sink, offset..offset + c.len_utf8(), converted);
+                    return;
+                }
+            }
+        }
+        if normalized == c {
+            self.keep(sink, offset, c.encode_utf8(&mut buf));
+        } else {
+            self.replace(
+                sink,
+                offset..offset + c.len_utf8(),
+                normalized.encode_utf8(&mut buf),
+            );
+        }
+    }
+
+    /// Applies [`MergeOptions::normalize_unicode_space`] to a character that is kept.
+    fn normalize(&self, c: char) -> char {
+        if self.options.normalize_unicode_space && is_unicode_space(c) {
+            ' '
+        } else {
+            c
+        }
+    }
+
+    /// Determines whether `c` opens a quoted region.
+    fn is_quote(&self, c: char) -> bool {
+        if let Some(matcher) = self.matcher {
+            return (matcher.is_open)(c);
+        }
+        self.options.quote_char == Some(c)
+            || (self.options.shell_mode && matches!(c, '\'' | '"'))
+            || (self.options.typographic_quotes && matches!(c, '«' | '“'))
+            || (self.options.preserve_parens && c == '(')
+    }
+
+    /// Determines whether `c` has a special meaning that an escape character can remove.
+    fn is_escapable(&self, c: char) -> bool {
+        c.is_whitespace()
+            || self.is_quote(c)
+            || self
+                .open_quote
+                .is_some_and(|open_quote| self.closes(open_quote, c))
+            || self.options.effective_escape_char() == Some(c)
+    }
+
+    /// Determines whether `c` closes the quoted region opened by `open_quote`.
+    fn closes(&self, open_quote: char, c: char) -> bool {
+        match self.matcher {
+            Some(matcher) => (matcher.close_matches)(open_quote, c),
+            None => self.closing_quote(open_quote) == c,
+        }
+    }
+
+    /// Determines the character closing the quoted region opened by `open_quote`.
+    fn closing_quote(&self, open_quote: char) -> char {
+        match open_quote {
+            '«' if self.options.typographic_quotes => '»',
+            '“' if self.options.typographic_quotes => '”',
+            '(' if self.options.preserve_parens => ')',
+            _ => open_quote,
+        }
+    }
+
+    /// Determines whether `c` is an escape character in the current context.
+    fn is_escape(&self, c: char) -> bool {
+        // Single-quoted shell strings do not support escapes at all.
+        self.options.effective_escape_char() == Some(c)
+            && !(self.options.shell_mode && self.open_quote == Some('\''))
+    }
+
+    /// Reports input text at `offset` that is kept as-is to the `sink`.
+    fn keep<S: Sink>(&self, sink: &mut S, offset: usize, text: &str) {
+        self.advance_column(text);
+        self.track_last_char(text);
+        sink.keep(offset, text);
+    }
+
+    /// Reports input text at `range` that is replaced with `with` to the `sink`.
+    fn replace<S: Sink>(&self, sink: &mut S, range: Range<usize>, with: &str) {
+        self.advance_column(with);
+        self.track_last_char(with);
+        sink.replace(range, with);
+    }
+
+    /// Tracks the output column if [`MergeOptions::tabstop`] is set. Each character counts as
+    /// one column.
+    fn advance_column(&self, output: &str) {
+        if self.options.tabstop.is_none() {
+            return;
+        }
+        let is_line_break = |c| WhitespaceCategory::of(c) == Some(WhitespaceCategory::LineBreak);
+        let column = match output.rfind(is_line_break) {
+            Some(index) => output[index..].chars().count() - 1,
+            None => self.column.get() + output.chars().count(),
+        };
+        self.column.set(column);
+    }
+
+    /// Tracks the last output character if [`MergeOptions::minimal_spacing`],
+    /// [`MergeOptions::between_words_only`], [`MergeOptions::tight_after`],
+    /// [`MergeOptions::trim_between`] or [`MergeOptions::sentence_spacing`] requires it.
+    fn track_last_char(&self, output: &str) {
+        if self.options.minimal_spacing
+            || self.options.between_words_only
+            || !self.options.tight_after.is_empty()
+            || self.options.trim_between.is_some()
+            || self.options.sentence_spacing != SentenceSpacing::Single
+        {
+            if let Some(c) = output.chars().next_back() {
+                self.last_char.set(Some(c));
+            }
+        }
+    }
+
+    /// Removes the pending whitespace run, if any.
+    fn discard_run<S: Sink>(&mut self, sink: &mut S) {
+        if let Some(run) = self.run.take() {
+            self.replace(sink, run.range, "");
+            self.run_prefix.clear();
+        }
+    }
+
+    pub fn flush_run<S: Sink>(&mut self, sink: &mut S) {
+        let Some(run) = self.run.take() else {
+            return;
+        };
+        let mut buf = [0; 4];
+        let options = &self.options;
+        if self.at_line_start {
+            self.replace(sink, run.range, "");
+            self.run_prefix.clear();
+            return;
+        }
+        if let Some(hook) = &mut self.run_hook {
+            let input = hook.input;
+            let decide = match &mut hook.action {
+                RunAction::Decide(decide) => decide,
+                RunAction::Replace(replace) => {
+                    let replacement = replace(&input[run.range.clone()]);
+                    self.replace(sink, run.range, &replacement);
+                    self.run_prefix.clear();
+                    return;
+                }
+            };
+            match decide(&input[run.range.clone()]) {
+                CollapseDecision::Collapse => {}
+                CollapseDecision::Preserve => {
+                    self.keep(sink, run.range.start, &input[run.range]);
+                    self.run_prefix.clear();
+                    return;
+                }
+                CollapseDecision::Remove => {
+                    self.replace(sink, run.range, "");
+                    self.run_prefix.clear();
+                    return;
+                }
+            }
+        }
+        if run.before.is_some_and(|c| options.tight_after.contains(&c))
+            || self
+                .next_char
+                .is_some_and(|c| options.tight_before.contains(&c))
+            || (options.trim_between.is_some()
+                && run.before == options.trim_between
+                && self.next_char == options.trim_between)
+        {
+            self.replace(sink, run.range, "");
+            self.run_prefix.clear();
+            return;
+        }
+        // A run can only be pending in a quoted region if the character ending it opened that region.
+        if options.edge_collapse == EdgeCollapse::DropAtQuotes
+            && (run.after_quote || self.open_quote.is_some())
+        {
+            self.replace(sink, run.range, "");
+            self.run_prefix.clear();
+            return;
+        }
+        let is_word_char = |c: Option<char>| c.is_some_and(options.word_char);
+        let between_words = is_word_char(run.before) && is_word_char(self.next_char);
+        if options.minimal_spacing && !between_words {
+            self.replace(sink, run.range, "");
+            self.run_prefix.clear();
+            return;
+        }
+        if options.between_words_only && !between_words {
+            // The run may span dropped text, which is not reported as kept.
+            self.replace(sink, run.range, &run.text);
+            self.run_prefix.clear();
+            return;
+        }
+        if options.keep_singletons && run.range.len() == run.first.len_utf8() {
+            let c = self.normalize(run.first);
+            self.replace(sink, run.range, c.encode_utf8(&mut buf));
+            self.run_prefix.clear();
+            return;
+        }
+        if let Some(sentinel) = options.debug_sentinel {
+            let is_replacement = run.range.len() == run.first.len_utf8()
+                && options.replacement.chars().eq([run.first]);
+            if !is_replacement {
+                self.replace(sink, run.range, sentinel.encode_utf8(&mut buf));
+                self.run_prefix.clear();
+                return;
+            }
+        }
+        if options.preserve_newlines
+            && options.tabstop.is_some()
+            && run.counts[WhitespaceCategory::Tab as usize] > 0
+        {
+            let width = run.end_column - self.column.get();
+            self.replace(sink, run.range, &" ".repeat(width));
+            self.run_prefix.clear();
+            return;
+        }
+        if options.max_spaces.is_some() {
+            self.replace(sink, run.range, &self.run_prefix);
+            self.run_prefix.clear();
+            return;
+        }
+        if let Some(exact_run) = options.exact_run {
+            self.replace(sink, run.range, &options.replacement.repeat(exact_run));
+            return;
+        }
+        if let Some(clamp_run) = options.clamp_run {
+            let len = run.counts.iter().sum::<usize>().min(clamp_run);
+            self.replace(sink, run.range, &options.replacement.repeat(len));
+            return;
+        }
+        match options.collapse_to {
+            CollapseTo::Replacement if options.category_replacement.is_some() => {
+                let replacement = options.category_replacement.unwrap_or_default();
+                let c = replacement.get(run.dominant_category());
+                self.replace(sink, run.range, c.encode_utf8(&mut buf));
+            }
+            CollapseTo::First => {
+                let c = self.normalize(run.first);
+                self.replace(sink, run.range, c.encode_utf8(&mut buf));
+            }
+            CollapseTo::Last => {
+                let c = self.normalize(run.last);
+                self.replace(sink, run.range, c.encode_utf8(&mut buf));
+            }
+            CollapseTo::Replacement if options.split_on_type_change => {
+                let category =
+                    WhitespaceCategory::of(run.first).unwrap_or(WhitespaceCategory::Space);
+                self.replace(sink, run.range, category.separator().encode_utf8(&mut buf));
+            }
+            CollapseTo::Replacement
+                if options.sentence_spacing == SentenceSpacing::Double
+                    && matches!(run.before, Some('.' | '!' | '?')) =>
+            {
+                self.replace(sink, run.range, &options.replacement.repeat(2));
+            }
+            CollapseTo::Replacement => self.replace(sink, run.range, options.replacement),
+        }
+    }
+}
+
+/// Determines whether `c` is a non-ASCII space separator (Unicode category `Zs`).
+fn is_unicode_space(c: char) -> bool {
+    matches!(
+        c,
+        '\u{00A0}' | '\u{1680}' | '\u{2000}'..='\u{200A}' | '\u{202F}' | '\u{205F}' | '\u{3000}'
+    )
+}
+
+/// Merges the whitespace of the whole `input`, reporting the result to the `sink`.
+pub(crate) fn merge_into<S: Sink>(input: &str, options: &MergeOptions, sink: &mut S) -> MergeStats {
+    merge_with(&mut Engine::new(options), input, sink)
+}
+
+/// Merges the whitespace of the whole `input` using the `engine`, reporting the result to the
+/// `sink`.
+///
+/// The output is cut off at the [`MergeOptions::truncate_to`] limit, in which case the rest of
+/// the input is not processed.
+pub(crate) fn merge_with<S: Sink>(engine: &mut Engine, input: &str, sink: &mut S) -> MergeStats {
+    let trim = engine.options.trim;
+    match engine.options.truncate_to {
+        Some(max_len) => {
+            let mut sink = TruncatingSink {
+                inner: sink,
+                len: 0,
+                max_len,
+                ellipsis: engine.options.ellipsis,
+                full: false,
+            };
+            merge_trimmed(engine, input, trim, &mut sink);
+            engine.finish(&mut sink)
+        }
+        None => {
+            merge_trimmed(engine, input, trim, sink);
+            engine.finish(sink)
+        }
+    }
+}
+
+/// Feeds the `input` to the `engine`, removing the whitespace at the trimmed ends.
+///
+/// An escaped whitespace character at the end of the input, and the run it starts if
+/// [`MergeOptions::escape_protects_run`] is enabled, is protected from trimming.
+/// The engine is not finished, so pending whitespace is not yet flushed.
+pub(crate) fn merge_trimmed<S: Sink>(engine: &mut Engine, input: &str, trim: Trim, sink: &mut S) {
+    let trailing_newline = engine.options.keep_trailing_newline
+        && input
+            .chars()
+            .next_back()
+            .and_then(WhitespaceCategory::of)
+            .is_some_and(|category| category == WhitespaceCategory::LineBreak);
+    let trim_chars = engine.options.trim_chars;
+    // Text outside of the regions delimited by `only_inside` is not trimmed.
+    let trim = match engine.options.only_inside {
+        Some(_) => Trim::None,
+        None => trim,
+    };
+    let mut bounds = trim.bounds(input, trim_chars);
+    if trailing_newline {
+        // The trailing whitespace is always removed in favor of the single line break.
+        let end = input.trim_end().len();
+        bounds = bounds.start.min(end)..bounds.end.min(end);
+    }
+    if matches!(trim, Trim::Both | Trim::Start) && trim_chars.is_none() {
+        // Whitespace following removed text at the very start is trimmed as well.
+        engine.at_line_start = true;
+    }
+    let mut leading = "";
+    if let Some(policy) = engine.options.leading_newline {
+        bounds.start = (input.len() - input.trim_start().len()).min(bounds.end);
+        let has_line_break = input[..bounds.start]
+            .chars()
+            .any(|c| WhitespaceCategory::of(c) == Some(WhitespaceCategory::LineBreak));
+        if policy == LeadingPolicy::KeepOne && has_line_break {
+            leading = "\n";
+        }
+    }
+    if bounds.start > 0 {
+        sink.replace(0..bounds.start, leading);
+    }
+
+    let mut offset = bounds.start;
+    while let Some(c) = input[offset..bounds.end].chars().next() {
+        if sink.is_full() {
+            return;
+        }
+        let mut len = engine.push_quoted(offset, &input[offset..bounds.end], sink);
+        if len == 0 && engine.options.drop_empty_quotes {
+            if let Some(quote_len) = engine.empty_quote_len(&input[offset..bounds.end]) {
+                len = engine.push_dropped(offset, &input[offset..offset + quote_len], sink);
+            }
+        }
+        if len == 0 {
+            if let Some(span_len) = verbatim_span_len(&engine.options, &input[offset..bounds.end]) {
+                len = engine.push_verbatim(offset, &input[offset..offset + span_len], sink);
+            }
+        }
+        if len == 0 {
+            if let Some(heredoc_len) = heredoc_len(&engine.options, &input[offset..bounds.end]) {
+                len = engine.push_verbatim(offset, &input[offset..offset + heredoc_len], sink);
+            }
+        }
+        if len == 0 && engine.options.preserve_ansi {
+            if let Some(ansi_len) = ansi_len(&input[offset..bounds.end]) {
+                len = engine.push_opaque(offset, &input[offset..offset + ansi_len], sink);
+            }
+        }
+        if len == 0 && engine.options.protect_urls {
+            if let Some(url_len) = url_len(&input[..bounds.end], offset) {
+                len = engine.push_verbatim(offset, &input[offset..offset + url_len], sink);
+            }
+        }
+        if len > 0 {
+            offset += len;
+            continue;
+        }
+        engine.push(offset, c, sink);
+        offset += c.len_utf8();
+    }
+    if (matches!(trim, Trim::Both | Trim::End) && trim_chars.is_none()) || trailing_newline {
+        // Whitespace preceding removed text at the very end is trimmed as well.
+        engine.discard_run(sink);
+    }
+    while engine.in_escape || engine.in_protected_run {
+        let Some(c) = input[bounds.end..].chars().next() else {
+            break;
+        };
+        engine.push(bounds.end, c, sink);
+        bounds.end += c.len_utf8();
+    }
+
+    if bounds.end < input.len() {
+        // The trailing whitespace is reported after the pending run it follows.
+        engine.flush_run(sink);
+        let with = if trailing_newline { "\n" } else { "" };
+        sink.replace(bounds.end..input.len(), with);
+    }
+}
+
+/// Determines the length of the [verbatim span](MergeOptions::verbatim_span) at the start of
+/// `text`, which extends to the end of the closing sentinel or of the text.
+///
+/// Of the [`MergeOptions::verbatim_spans`] with a matching opening sentinel, the longest one,
+/// or the first of equally long ones, is used.
+fn verbatim_span_len(options: &MergeOptions, text: &str) -> Option<usize> {
+    let (open, close) = options
+        .verbatim_span
+        .iter()
+        .chain(options.verbatim_spans)
+        .filter(|(open, _)| !open.is_empty() && text.starts_with(open))
+        .reduce(|longest, span| match span.0.len() > longest.0.len() {
+            true => span,
+            false => longest,
+        })?;
+    let len = text[open.len()..]
+        .find(close)
+        .map_or(text.len(), |index| open.len() + index + close.len());
+    Some(len)
+}
+
+/// Determines the length of the heredoc-style region at the start of the `text`, if any,
+/// as configured by [`MergeOptions::heredoc`].
+///
+/// The region extends up to and including the line break after the closing line, so that the
+/// following line starts as in the input.
+fn heredoc_len(options: &MergeOptions, text: &str) -> Option<usize> {
+    if !options.heredoc || options.heredoc_marker.is_empty() {
+        return None;
+    }
+    let rest = text.strip_prefix(options.heredoc_marker)?;
+    let ident_len = rest
+        .find(|c: char| !c.is_alphanumeric() && c != '_')
+        .unwrap_or(rest.len());
+    if ident_len == 0 {
+        return None;
+    }
+    let ident = &rest[..ident_len];
+    // Like the `<<-` and `<<~` forms of shells, such markers allow the closing line to be
+    // indented.
+    let indented = options.heredoc_marker.ends_with(['-', '~']);
+
+    // The body starts on the line following the marker.
+    let mut offset = text.len() - rest.len() + ident_len;
+    offset += text[offset..].find('\n')? + 1;
+    while offset < text.len() {
+        let line_len = text[offset..].find('\n').unwrap_or(text.len() - offset);
+        let line = &text[offset..offset + line_len];
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        let line = if indented { line.trim_start() } else { line };
+        if line == ident {
+            return Some((offset + line_len + 1).min(text.len()));
+        }
+        offset += line_len + 1;
+    }
+    Some(text.len())
+}
+
+/// Determines the length of the ANSI CSI escape sequence at the start of the `text`, if any,
+/// as kept by [`MergeOptions::preserve_ansi`].
+///
+/// A sequence consists of `ESC [`, any parameter bytes (`0x30`–`0x3F`), any intermediate bytes
+/// (`0x20`–`0x2F`) and a final byte (`0x40`–`0x7E`).
+fn ansi_len(text: &str) -> Option<usize> {
+    let rest = text.strip_prefix("\x1b[")?.as_bytes();
+    let params = rest
+        .iter()
+        .take_while(|b| (0x30..=0x3F).contains(*b))
+        .count();
+    let intermediates = rest[params..]
+        .iter()
+        .take_while(|b| (0x20..=0x2F).contains(*b))
+        .count();
+    let end = params + intermediates;
+    rest.get(end)
+        .is_some_and(|b| (0x40..=0x7E).contains(b))
+        .then_some(2 + end + 1)
+}
+
+/// Determines the length of the `http://` or `https://` URL starting at byte `offset` of the
+/// input, which extends to the next whitespace character.
+///
+/// URLs are only detected at the start of a word, i.e. not following an alphanumeric character.
+fn url_len(input: &str, offset: usize) -> Option<usize> {
+    let text = &input[offset..];
+    let is_url = ["http://", "https://"].iter().any(|scheme| {
+        text.get(..scheme.len())
+            .is_some_and(|prefix| prefix.eq_ignore_ascii_case(scheme))
+    });
+    let at_word_start = !input[..offset]
+        .chars()
+        .next_back()
+        .is_some_and(char::is_alphanumeric);
+    (is_url && at_word_start).then(|| text.find(char::is_whitespace).unwrap_or(text.len()))
+}
+
+/// A [`Sink`] that cuts the output off at the last character boundary within `max_len` bytes,
+/// appending the `ellipsis`, and is full once it has done so.
+struct TruncatingSink<'s, 'e, S> {
+    inner: &'s mut S,
+    /// The length of the output so far.
+    len: usize,
+    max_len: usize,
+    ellipsis: &'e str,
+    full: bool,
+}
+
+impl<S: Sink> TruncatingSink<'_, '_, S> {
+    /// Determines the length of the leading part of `text` that fits within the limit. If the
+    /// text does not fit completely, the sink becomes full.
+    fn fit(&mut self, text: &str) -> usize {
+        let remaining = self.max_len - self.len;
+        if text.len() <= remaining {
+            self.len += text.len();
+            return text.len();
+        }
+        self.full = true;
+        (0..=remaining)
+            .rev()
+            .find(|&index| text.is_char_boundary(index))
+            .unwrap_or_default()
+    }
+
+    /// Appends the ellipsis to the output that was cut off before byte `offset` of the input.
+    fn cut(&mut self, offset: usize) {
+        if !self.ellipsis.is_empty() {
+            self.inner.replace(offset..offset, self.ellipsis);
+        }
+    }
+}
+
+impl<S: Sink> Sink for TruncatingSink<'_, '_, S> {
+    fn keep(&mut self, offset: usize, text: &str) {
+        if self.full {
+            return;
+        }
+        let len = self.fit(text);
+        if len > 0 {
+            self.inner.keep(offset, &text[..len]);
+        }
+        if self.full {
+            self.cut(offset + len);
+        }
+    }
+
+    fn replace(&mut self, range: Range<usize>, with: &str) {
+        if self.full {
+            return;
+        }
+        let len = self.fit(with);
+        if !self.full {
+            self.inner.replace(range, with);
+            return;
+        }
+        if len > 0 {
+            self.inner.replace(range.clone(), &with[..len]);
+        }
+        self.cut(range.end);
+    }
+
+    fn is_full(&self) -> bool {
+        self.full || self.inner.is_full()
+    }
+}
+
+/// A [`Sink`] discarding the output.
+pub(crate) struct Discard;
+
+impl Sink for Discard {
+    fn keep(&mut self, _offset: usize, _text: &str) {}
+
+    fn replace(&mut self, _range: Range<usize>, _with: &str) {}
+}
+
+/// A [`Sink`] determining whether the output differs from the input, which is full as soon as
+/// it does.
+pub(crate) struct ChangeSink<'a> {
+    pub input: &'a str,
+    pub changed: bool,
+}
+
+impl Sink for ChangeSink<'_> {
+    fn keep(&mut self, _offset: usize, _text: &str) {}
+
+    fn replace(&mut self, range: Range<usize>, with: &str) {
+        self.changed |= self.input[range] != *with;
+    }
+
+    fn is_full(&self) -> bool {
+        self.changed
+    }
+}
+
+/// A [`Sink`] counting the bytes of the output.
+#[derive(Default)]
+pub(crate) struct LenSink {
+    pub len: usize,
+}
+
+impl Sink for LenSink {
+    fn keep(&mut self, _offset: usize, text: &str) {
+        self.len += text.len();
+    }
+
+    fn replace(&mut self, _range: Range<usize>, with: &str) {
+        self.len += with.len();
+    }
+}
+
+/// Appends the output to the string.
+impl Sink for String {
+    fn keep(&mut self, _offset: usize, text: &str) {
+        self.push_str(text);
+    }
+
+    fn replace(&mut self, _range: Range<usize>, with: &str) {
+        self.push_str(with);
+    }
+}
+
+/// Appends the UTF-8 bytes of the output to the vector.
+impl Sink for Vec<u8> {
+    fn keep(&mut self, _offset: usize, text: &str) {
+        self.extend_from_slice(text.as_bytes());
+    }
+
+    fn replace(&mut self, _range: Range<usize>, with: &str) {
+        self.extend_from_slice(with.as_bytes());
+    }
+}
+
+/// A [`Sink`] producing a [`Cow`] that borrows from the input for as long as the output
+/// is a contiguous, unmodified slice of it.
+pub(crate) struct CowSink<'a> {
+    input: &'a str,
+    /// The borrowed slice of the input, as long as no owned copy was required.
+    borrowed: Range<usize>,
+    owned: Option<String>,
+    /// The buffer an owned copy is created in.
+    spare: String,
+    /// The capacity reserved for an owned copy.
+    reserve: usize,
+}
+
+impl<'a> CowSink<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Self::with_buffer(input, String::new())
+    }
+
+    /// Creates a sink that creates an owned copy in the provided buffer, if required.
+    pub fn with_buffer(input: &'a str, mut buffer: String) -> Self {
+        buffer.clear();
+        Self {
+            input,
+            borrowed: 0..0,
+            owned: None,
+            spare: buffer,
+            reserve: input.len(),
+        }
+    }
+
+    /// Limits the capacity reserved for an owned copy to `max` bytes.
+    pub fn limit_reserve(mut self, max: usize) -> Self {
+        self.reserve = self.reserve.min(max);
+        self
+    }
+
+    pub fn into_cow(self) -> Cow<'a, str> {
+        match self.into_parts() {
+            Ok((borrowed, _)) => Cow::Borrowed(borrowed),
+            Err(owned) => Cow::Owned(owned),
+        }
+    }
+
+    /// Gets the borrowed slice of the input along with the unused buffer, or the owned copy
+    /// created in the buffer if one was required.
+    pub fn into_parts(self) -> Result<(&'a str, String), String> {
+        match self.owned {
+            Some(resulting_string) => Err(resulting_string),
+            None => Ok((&self.input[self.borrowed], self.spare)),
+        }
+    }
+
+    fn owned(&mut self) -> &mut String {
+        let input = self.input;
+        let borrowed = &self.borrowed;
+        let spare = &mut self.spare;
+        let reserve = self.reserve;
+        self.owned.get_or_insert_with(|| {
+            let mut string = std::mem::take(spare);
+            string.reserve(reserve);
+            string.push_str(&input[borrowed.clone()]);
+            string
+        })
+    }
+}
+
+impl Sink for CowSink<'_> {
+    fn keep(&mut self, offset: usize, text: &str) {
+        if self.owned.is_none() {
+            if self.borrowed.is_empty() {
+                self.borrowed = offset..offset;
+            }
+            if self.borrowed.end == offset {
+                self.borrowed.end += text.len();
+                return;
+            }
+        }
+        self.owned().push_str(text);
+    }
+
+    fn replace(&mut self, range: Range<usize>, with: &str) {
+        if self.input[range.clone()] == *with {
+            self.keep(range.start, with);
+        } else if !with.is_empty() {
+            self.owned().push_str(with);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::random_inputs;
+
+    /// Merges the input by pushing each character individually.
+    fn merge_scalar(input: &str, options: &MergeOptions) -> String {
+        let mut output = String::new();
+        let mut engine = Engine::new(options);
+        for (offset, c) in input.char_indices() {
+            engine.push(offset, c, &mut output);
+        }
+        engine.finish(&mut output);
+        output
+    }
+
+    /// Merges the input, copying quoted text in bulk.
+    fn merge_bulk(input: &str, options: &MergeOptions) -> String {
+        let mut output = String::new();
+        merge_into(input, options, &mut output);
+        output
+    }
+
+    #[test]
+    fn owned_copy_is_created_only_if_required() {
+        const ALPHABET: [char; 6] = [' ', ' ', '\t', 'a', 'b', '"'];
+        let options = MergeOptions::new().quote_char('"');
+
+        for input in random_inputs(&ALPHABET, 5000, 12) {
+            let mut sink = CowSink::new(&input);
+            merge_into(&input, &options, &mut sink);
+            let expected = merge_bulk(&input, &options);
+            // Trimming alone never requires a copy, while any interior change does.
+            match sink.into_parts() {
+                Ok((borrowed, _)) => assert_eq!(borrowed, input.trim(), "{input:?}"),
+                Err(owned) => {
+                    assert_eq!(owned, expected, "{input:?}");
+                    assert_ne!(owned, input.trim(), "{input:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn quoted_fast_path_matches_scalar_loop() {
+        const ALPHABET: [char; 10] = [' ', ' ', 'a', '"', '\\', '\'', '«', '»', '\n', 'ü'];
+        let base = MergeOptions::new()
+            .quote_char('"')
+            .escape_char('\\')
+            .trim(Trim::None);
+        let options = [
+            base.clone(),
+            base.clone().doubled_quote_escapes(true),
+            base.clone().typographic_quotes(true),
+            base.clone().shell_mode(true),
+            base.clone().escape_protects_run(true),
+            base.clone().normalize_unicode_space(true),
+            base.clone().table_separator('a'),
+            base.clone().quote_char('«').escape_char('»'),
+        ];
+
+        for input in random_inputs(&ALPHABET, 2000, 24) {
+            for options in &options {
+                assert_eq!(
+                    merge_bulk(&input, options),
+                    merge_scalar(&input, options),
+                    "{input:?}, {options:?}"
+                );
+            }
+        }
+    }
+}
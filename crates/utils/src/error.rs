@@ -0,0 +1,126 @@
+use std::fmt;
+
+/// An error of [`try_merge_whitespace_with_options`](crate::try_merge_whitespace_with_options).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MergeError {
+    /// A tab character was found outside of quoted regions while
+    /// [`MergeOptions::forbid_tabs`](crate::MergeOptions::forbid_tabs) is enabled.
+    TabFound {
+        /// The byte offset of the tab character in the input.
+        at: usize,
+    },
+    /// The options are invalid, as determined by [`MergeOptions::validate`](crate::MergeOptions::validate).
+    InvalidOptions(OptionError),
+}
+
+impl fmt::Display for MergeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TabFound { at } => write!(f, "Found a tab character at byte {at}"),
+            Self::InvalidOptions(error) => write!(f, "Invalid options: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for MergeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::TabFound { .. } => None,
+            Self::InvalidOptions(error) => Some(error),
+        }
+    }
+}
+
+impl From<OptionError> for MergeError {
+    fn from(error: OptionError) -> Self {
+        Self::InvalidOptions(error)
+    }
+}
+
+/// A contradictory combination of options, as reported by
+/// [`MergeOptions::validate`](crate::MergeOptions::validate).
+///
+/// The options are referred to by their field names in [`MergeOptions`](crate::MergeOptions).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum OptionError {
+    /// An option has no effect because another option takes precedence.
+    Conflicting {
+        /// The option without effect.
+        option: &'static str,
+        /// The option taking precedence.
+        with: &'static str,
+    },
+    /// An option has no effect unless another option is enabled.
+    Requires {
+        /// The option without effect.
+        option: &'static str,
+        /// The option it depends on.
+        requires: &'static str,
+    },
+    /// Two options are set to the same character, which makes their meaning ambiguous.
+    SameChar {
+        /// The first option.
+        option: &'static str,
+        /// The second option.
+        with: &'static str,
+    },
+    /// An option is set to an empty string, which never matches.
+    Empty {
+        /// The empty option.
+        option: &'static str,
+    },
+    /// An option delimits regions with the same opening and closing character, so that the
+    /// regions never close.
+    SameDelimiters {
+        /// The option delimiting the regions.
+        option: &'static str,
+    },
+}
+
+impl fmt::Display for OptionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Conflicting { option, with } => {
+                write!(f, "`{option}` has no effect in combination with `{with}`")
+            }
+            Self::Requires { option, requires } => {
+                write!(f, "`{option}` has no effect unless `{requires}` is enabled")
+            }
+            Self::SameChar { option, with } => {
+                write!(f, "`{option}` and `{with}` are the same character")
+            }
+            Self::Empty { option } => write!(f, "`{option}` must not be empty"),
+            Self::SameDelimiters { option } => {
+                write!(
+                    f,
+                    "`{option}` must open and close with different characters"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for OptionError {}
+
+/// The error of [`merge_whitespace_bounded`](crate::merge_whitespace_bounded) if the output
+/// would exceed the limit.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Overflow {
+    /// The maximum length of the output in bytes.
+    pub max_output: usize,
+}
+
+impl fmt::Display for Overflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "The output exceeds the limit of {} bytes",
+            self.max_output
+        )
+    }
+}
+
+impl std::error::Error for Overflow {}
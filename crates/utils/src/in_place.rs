@@ -0,0 +1,125 @@
+use crate::engine::{merge_into, CowSink};
+use crate::MergeOptions;
+
+/// Remove multiple consecutive whitespaces from a string in place, like
+/// [`merge_whitespace_with_options`](crate::merge_whitespace_with_options) does.
+///
+/// The string keeps its allocation if the merged output fits into its capacity.
+///
+/// ## Example
+///
+/// ```
+/// # use merge_whitespace_utils::{merge_whitespace_in_place, MergeOptions};
+/// let mut line = String::from("  Hello     World!  ");
+/// merge_whitespace_in_place(&mut line, &MergeOptions::new());
+/// assert_eq!(line, "Hello World!");
+/// ```
+pub fn merge_whitespace_in_place(string: &mut String, options: &MergeOptions) {
+    merge_in_place(string, options, String::new());
+}
+
+/// Remove multiple consecutive whitespaces from each of the strings in place, like
+/// [`merge_whitespace_in_place`] does.
+///
+/// The strings keep their allocations if their merged output fits into their capacity, and a
+/// single intermediate buffer is shared by all of them.
+///
+/// ## Example
+///
+/// ```
+/// # use merge_whitespace_utils::{merge_whitespace_each_in_place, MergeOptions};
+/// let mut lines = vec![String::from("a   b"), String::from("  c  ")];
+/// merge_whitespace_each_in_place(&mut lines, &MergeOptions::new());
+/// assert_eq!(lines, ["a b", "c"]);
+/// ```
+pub fn merge_whitespace_each_in_place(strings: &mut [String], options: &MergeOptions) {
+    let mut buffer = String::new();
+    for string in strings {
+        buffer = merge_in_place(string, options, buffer);
+    }
+}
+
+/// Merges the string in place, using the buffer for the intermediate output if required.
+///
+/// # Return
+///
+/// The buffer, for reuse.
+fn merge_in_place(string: &mut String, options: &MergeOptions, buffer: String) -> String {
+    let mut sink = CowSink::with_buffer(string, buffer);
+    merge_into(string, options, &mut sink);
+    match sink.into_parts() {
+        Ok((borrowed, buffer)) => {
+            let start = borrowed.as_ptr() as usize - string.as_ptr() as usize;
+            let end = start + borrowed.len();
+            string.truncate(end);
+            string.drain(..start);
+            buffer
+        }
+        Err(owned) => {
+            string.clear();
+            string.push_str(&owned);
+            owned
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::merge_whitespace_with_options;
+
+    #[test]
+    fn each_line_is_merged() {
+        let options = MergeOptions::new().quote_char('"');
+        let input = ["  a   b  ", "c", "", "   ", "d  \"e   f\"  g", "ß\t\tü"];
+        let mut lines = input.map(String::from);
+        merge_whitespace_each_in_place(&mut lines, &options);
+        for (line, input) in lines.iter().zip(input) {
+            assert_eq!(line, &merge_whitespace_with_options(input, &options));
+        }
+    }
+
+    #[test]
+    fn capacities_are_reused() {
+        let mut lines: Vec<String> = ["  a   b  ", "  unchanged  ", "c  d"]
+            .iter()
+            .map(|line| {
+                let mut string = String::with_capacity(64);
+                string.push_str(line);
+                string
+            })
+            .collect();
+        let vec_ptr = lines.as_ptr();
+        let pointers: Vec<_> = lines.iter().map(|line| line.as_ptr()).collect();
+
+        merge_whitespace_each_in_place(&mut lines, &MergeOptions::new());
+
+        assert_eq!(lines, ["a b", "unchanged", "c d"]);
+        assert_eq!(lines.as_ptr(), vec_ptr);
+        for (line, pointer) in lines.iter().zip(pointers) {
+            assert_eq!(line.as_ptr(), pointer);
+            assert_eq!(line.capacity(), 64);
+        }
+    }
+
+    #[test]
+    fn buffer_is_kept_for_unchanged_strings() {
+        let options = MergeOptions::new();
+        let buffer = merge_in_place(&mut String::from("a  b"), &options, String::new());
+        let pointer = buffer.as_ptr();
+
+        let mut line = String::from("  unchanged  ");
+        let buffer = merge_in_place(&mut line, &options, buffer);
+        assert_eq!(line, "unchanged");
+        assert_eq!(buffer.as_ptr(), pointer);
+        assert!(buffer.capacity() > 0);
+    }
+
+    #[test]
+    fn longer_output_grows_the_string() {
+        let mut line = String::from("a b  c");
+        let options = MergeOptions::new().replacement("<space>");
+        merge_whitespace_in_place(&mut line, &options);
+        assert_eq!(line, "a<space>b<space>c");
+    }
+}
@@ -0,0 +1,107 @@
+use std::ops::Range;
+
+use crate::engine::{merge_into, Sink};
+use crate::MergeOptions;
+
+/// Remove multiple consecutive whitespaces from a given string like
+/// [`merge_whitespace_with_quotes`](crate::merge_whitespace_with_quotes) and escape the result
+/// for use in a JSON string.
+///
+/// The characters `"` and `\`, as well as all control characters, are escaped. The output is
+/// not surrounded by quotes. Merging and escaping happen in a single pass.
+///
+/// ## Example
+///
+/// ```
+/// # use merge_whitespace_utils::merge_whitespace_json_escaped;
+/// let query = merge_whitespace_json_escaped("query {  user(name: \"a\tb\")  }", Some('"'), None);
+/// assert_eq!(query, r#"query { user(name: \"a\tb\") }"#);
+/// ```
+///
+/// # Return
+///
+/// The merged and JSON-escaped string.
+pub fn merge_whitespace_json_escaped(
+    input: &str,
+    quote_char: Option<char>,
+    escape_char: Option<char>,
+) -> String {
+    let options = MergeOptions {
+        quote_char,
+        escape_char,
+        ..MergeOptions::new()
+    };
+    let mut sink = JsonSink(String::with_capacity(input.len()));
+    merge_into(input, &options, &mut sink);
+    sink.0
+}
+
+/// A [`Sink`] appending the JSON-escaped output to a string.
+struct JsonSink(String);
+
+impl JsonSink {
+    fn push_escaped(&mut self, text: &str) {
+        for c in text.chars() {
+            match c {
+                '"' => self.0.push_str("\\\""),
+                '\\' => self.0.push_str("\\\\"),
+                '\n' => self.0.push_str("\\n"),
+                '\r' => self.0.push_str("\\r"),
+                '\t' => self.0.push_str("\\t"),
+                '\u{0008}' => self.0.push_str("\\b"),
+                '\u{000C}' => self.0.push_str("\\f"),
+                c if c.is_control() => self.0.push_str(&format!("\\u{:04x}", c as u32)),
+                c => self.0.push(c),
+            }
+        }
+    }
+}
+
+impl Sink for JsonSink {
+    fn keep(&mut self, _offset: usize, text: &str) {
+        self.push_escaped(text);
+    }
+
+    fn replace(&mut self, _range: Range<usize>, with: &str) {
+        self.push_escaped(with);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quotes_and_backslashes_are_escaped() {
+        let input = r#"  {  "name":   "Froozle   \"78\"   Frobnik"  }  "#;
+        assert_eq!(
+            merge_whitespace_json_escaped(input, Some('"'), Some('\\')),
+            r#"{ \"name\": \"Froozle   \\\"78\\\"   Frobnik\" }"#
+        );
+    }
+
+    #[test]
+    fn newlines_are_merged_or_escaped() {
+        assert_eq!(
+            merge_whitespace_json_escaped("query {\n  users {\n    id\n  }\n}\n", None, None),
+            "query { users { id } }"
+        );
+        assert_eq!(
+            merge_whitespace_json_escaped("a\n\nb \"c\r\n\td\"", Some('"'), None),
+            r#"a b \"c\r\n\td\""#
+        );
+    }
+
+    #[test]
+    fn control_characters_are_escaped() {
+        assert_eq!(
+            merge_whitespace_json_escaped(
+                "a\u{0007}b \"\u{0008}\u{000C}\u{001F}\u{007F}\"",
+                Some('"'),
+                None
+            ),
+            r#"a\u0007b \"\b\f\u001f\u007f\""#
+        );
+        assert_eq!(merge_whitespace_json_escaped("Grüße", None, None), "Grüße");
+    }
+}
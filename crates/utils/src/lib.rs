@@ -27,10 +27,91 @@
 //!                                             named \"spaces  in  space \"") { id name todos(order_by: {created_at: desc}, limit: 5) { id title } } }"#);
 //! ```
 
-#![forbid(unsafe_code)]
+#![cfg_attr(
+    not(any(feature = "wasm", all(feature = "allocator_api", test))),
+    forbid(unsafe_code)
+)]
+// The bindings generated by `wasm-bindgen` and the allocator used to test the `allocator_api`
+// feature contain unsafe code, which is allowed in their modules.
+#![cfg_attr(
+    any(feature = "wasm", all(feature = "allocator_api", test)),
+    deny(unsafe_code)
+)]
+#![cfg_attr(feature = "allocator_api", feature(allocator_api))]
 
 use std::borrow::Cow;
 
+#[cfg(feature = "allocator_api")]
+mod allocator;
+mod assert;
+mod bounded;
+mod bytes;
+mod category;
+mod chars;
+mod decide;
+mod diff;
+mod display;
+mod edit;
+mod engine;
+mod error;
+mod in_place;
+mod json;
+mod map;
+mod matcher;
+mod merged;
+mod merger;
+mod options;
+mod os_str;
+mod presets;
+mod regions;
+mod segments;
+mod stats;
+#[cfg(test)]
+mod test_support;
+mod tokens;
+#[cfg(feature = "unicode")]
+mod unicode;
+#[cfg(feature = "wasm")]
+#[allow(unsafe_code)]
+mod wasm;
+mod wrap;
+
+use crate::engine::{merge_into, ChangeSink, CowSink, Discard, Engine, LenSink};
+
+#[cfg(feature = "allocator_api")]
+pub use allocator::merge_whitespace_in;
+pub use assert::assert_no_double_space;
+pub use bounded::merge_whitespace_bounded;
+pub use bytes::{merge_whitespace_bytes, merge_whitespace_bytes_in_place, ByteMerger};
+pub use category::{
+    is_collapsible_ascii, is_default_whitespace, CategoryReplacement, WhitespaceCategory,
+    ASCII_WHITESPACE, COLLAPSIBLE_ASCII_WHITESPACE,
+};
+pub use chars::chars_merged;
+pub use decide::{merge_whitespace_decide, merge_whitespace_with_fn, CollapseDecision};
+pub use diff::{merge_whitespace_diff, Replacement};
+pub use display::{merged_display, MergedDisplay};
+pub use edit::{merge_whitespace_after_edit, Edit};
+pub use error::{MergeError, OptionError, Overflow};
+pub use in_place::{merge_whitespace_each_in_place, merge_whitespace_in_place};
+pub use json::merge_whitespace_json_escaped;
+pub use map::merge_whitespace_with_map;
+pub use matcher::merge_whitespace_with_matcher;
+pub use merged::MergedStr;
+pub use merger::{CompiledMerger, Merger};
+#[cfg(feature = "unicode")]
+pub use options::Case;
+pub use options::{CollapseTo, EdgeCollapse, LeadingPolicy, MergeOptions, SentenceSpacing, Trim};
+pub use os_str::merge_whitespace_os;
+pub use presets::{minify_graphql, minify_json_whitespace};
+pub use regions::{quoted_regions, quotes_balanced};
+pub use segments::merge_whitespace_segments;
+pub use stats::{merge_whitespace_with_stats, MergeStats};
+pub use tokens::{tokens, whitespace_tokens};
+#[cfg(feature = "wasm")]
+pub use wasm::merge_whitespace_wasm;
+pub use wrap::merge_and_wrap;
+
 /// Remove multiple consecutive whitespaces from a given string and replace them with a single space.
 /// If special handling of quoted text is required, see [`merge_whitespace_with_quotes`] instead.
 ///
@@ -45,12 +126,16 @@ use std::borrow::Cow;
 /// # Return
 ///
 /// The modified string.
-pub fn merge_whitespace(input: &str) -> Cow<str> {
+pub fn merge_whitespace(input: &str) -> Cow<'_, str> {
     merge_whitespace_with_quotes(input, None, None)
 }
 
 /// Remove multiple consecutive whitespaces from a given string literal and replace them with a
-/// single space. Quoted text will be ignored and kept as-is.
+/// single space. Quoted text will be ignored and kept as-is. Empty and whitespace-only input
+/// results in an empty string.
+///
+/// If the input consists of a single quoted region, apart from leading and trailing whitespace,
+/// it is returned as a [`Cow::Borrowed`] slice of the input without further processing.
 ///
 /// ## Example
 ///
@@ -60,6 +145,25 @@ pub fn merge_whitespace(input: &str) -> Cow<str> {
 /// assert_eq!(output, "Hello World! \"How        are\" you?");
 /// ```
 ///
+/// ## Escaping
+///
+/// The character following an escape character is kept as-is, even if it is whitespace or a
+/// quote character. This works independently of quoting: without a quote character, escapes
+/// can still be used to protect individual whitespace characters. The escape character itself is
+/// kept in the output, and an escape character at the very end of the input is kept as-is.
+/// An escaped whitespace character at the end of the input is not trimmed. An escaped escape
+/// character is kept as well, so a doubled escape character results in both characters, and
+/// neither of them escapes the character that follows.
+///
+/// ```
+/// # use merge_whitespace_utils::merge_whitespace_with_quotes;
+/// let output = merge_whitespace_with_quotes("a\\   b   c\\ ", None, Some('\\'));
+/// assert_eq!(output, "a\\  b c\\ ");
+///
+/// let output = merge_whitespace_with_quotes("a\\\\   b\\\\ ", None, Some('\\'));
+/// assert_eq!(output, "a\\\\ b\\\\");
+/// ```
+///
 /// # Return
 ///
 /// The modified string.
@@ -67,55 +171,298 @@ pub fn merge_whitespace_with_quotes(
     input: &str,
     quote_char: Option<char>,
     escape_char: Option<char>,
-) -> Cow<str> {
-    let trimmed_input = input.trim();
-    let mut result = None; // Use this to lazily initialize a String if needed
-    let mut in_quotes = false;
-    let mut prev_char_was_space = false;
-    let mut in_escape = false;
+) -> Cow<'_, str> {
+    let options = MergeOptions {
+        quote_char,
+        escape_char,
+        ..MergeOptions::new()
+    };
+    merge_whitespace_with_options(input, &options)
+}
 
-    for c in trimmed_input.chars() {
-        if escape_char == Some(c) && !in_escape {
-            if prev_char_was_space {
-                result
-                    .get_or_insert_with(|| String::with_capacity(trimmed_input.len()))
-                    .push(' ');
-            }
-            prev_char_was_space = false;
-            in_escape = true;
-            result
-                .get_or_insert_with(|| String::with_capacity(trimmed_input.len()))
-                .push(c);
-            continue;
-        }
-        if c.is_whitespace() && !in_quotes && !in_escape {
-            prev_char_was_space = true;
-            continue;
-        }
-        if quote_char == Some(c) && !in_escape {
-            in_quotes = !in_quotes;
+/// Remove multiple consecutive whitespaces from a given string literal and replace them with a
+/// single space, like [`merge_whitespace_with_quotes`], while converting the text outside of
+/// quoted regions to the `case`. Quoted text keeps its original case; passing [`Case::None`]
+/// leaves the case unchanged everywhere.
+///
+/// This is a shorthand for [`MergeOptions::case_unquoted`].
+///
+/// ## Example
+///
+/// ```
+/// # use merge_whitespace_utils::{merge_whitespace_map_case, Case};
+/// let input = "Select  *  from t  where  x = 'Value'";
+/// let output = merge_whitespace_map_case(input, Case::Upper, Some('\''), None);
+/// assert_eq!(output, "SELECT * FROM T WHERE X = 'Value'");
+/// ```
+///
+/// # Return
+///
+/// The modified string.
+#[cfg(feature = "unicode")]
+pub fn merge_whitespace_map_case(
+    input: &str,
+    case: Case,
+    quote_char: Option<char>,
+    escape_char: Option<char>,
+) -> Cow<'_, str> {
+    let options = MergeOptions {
+        quote_char,
+        escape_char,
+        case_unquoted: (case != Case::None).then_some(case),
+        ..MergeOptions::new()
+    };
+    merge_whitespace_with_options(input, &options)
+}
+
+/// Remove multiple consecutive whitespaces from a given string literal and replace them with a
+/// single space, like [`merge_whitespace_with_quotes`], while also reporting whether all quoted
+/// regions were properly closed.
+///
+/// Unbalanced quotes do not cause an error; the text following an unmatched quote character is
+/// kept as-is, exactly as [`merge_whitespace_with_quotes`] would do.
+///
+/// ## Example
+///
+/// ```
+/// # use merge_whitespace_utils::merge_whitespace_validated;
+/// let (output, balanced) = merge_whitespace_validated("a   \"b   c\"   d", Some('"'), None);
+/// assert_eq!(output, "a \"b   c\" d");
+/// assert!(balanced);
+///
+/// let (output, balanced) = merge_whitespace_validated("a   \"b   c   d", Some('"'), None);
+/// assert_eq!(output, "a \"b   c   d");
+/// assert!(!balanced);
+/// ```
+///
+/// # Return
+///
+/// The modified string and whether all quoted regions were closed.
+pub fn merge_whitespace_validated(
+    input: &str,
+    quote_char: Option<char>,
+    escape_char: Option<char>,
+) -> (Cow<'_, str>, bool) {
+    let options = MergeOptions {
+        quote_char,
+        escape_char,
+        ..MergeOptions::new()
+    };
+    let mut sink = CowSink::new(input);
+    let summary = merge_into(input, &options, &mut sink);
+    (sink.into_cow(), summary.quotes_balanced)
+}
+
+/// Remove multiple consecutive whitespaces from a given string like
+/// [`merge_whitespace_with_quotes`] and append the UTF-8 bytes of the result to `out`.
+///
+/// This avoids an intermediate `String` when assembling byte buffers.
+///
+/// ## Example
+///
+/// ```
+/// # use merge_whitespace_utils::merge_whitespace_to_vec;
+/// let mut out = b"query=".to_vec();
+/// merge_whitespace_to_vec("  a   \"b   c\"  ", &mut out, Some('"'), None);
+/// assert_eq!(out, b"query=a \"b   c\"");
+/// ```
+pub fn merge_whitespace_to_vec(
+    input: &str,
+    out: &mut Vec<u8>,
+    quote_char: Option<char>,
+    escape_char: Option<char>,
+) {
+    let options = MergeOptions {
+        quote_char,
+        escape_char,
+        ..MergeOptions::new()
+    };
+    merge_into(input, &options, out);
+}
+
+/// Remove multiple consecutive whitespaces from a given string like
+/// [`merge_whitespace_with_quotes`], always returning an owned `String`.
+///
+/// An unchanged input is cloned, which saves the `.into_owned()` call when a `String` is needed
+/// regardless.
+///
+/// ## Example
+///
+/// ```
+/// # use merge_whitespace_utils::merge_whitespace_to_string;
+/// let output: String = merge_whitespace_to_string("  a   \"b   c\"  ", Some('"'), None);
+/// assert_eq!(output, "a \"b   c\"");
+/// ```
+///
+/// # Return
+///
+/// The modified string.
+pub fn merge_whitespace_to_string(
+    input: &str,
+    quote_char: Option<char>,
+    escape_char: Option<char>,
+) -> String {
+    merge_whitespace_with_quotes(input, quote_char, escape_char).into_owned()
+}
+
+/// Determines the byte length of the output [`merge_whitespace_with_quotes`] would produce for
+/// the same arguments, without building it, e.g. to pre-size buffers or to validate size limits.
+///
+/// ## Example
+///
+/// ```
+/// # use merge_whitespace_utils::merged_len;
+/// assert_eq!(merged_len("  a   \"b   c\"  ", Some('"'), None), 9);
+/// ```
+///
+/// # Return
+///
+/// The length of the modified string, in bytes.
+pub fn merged_len(input: &str, quote_char: Option<char>, escape_char: Option<char>) -> usize {
+    let options = MergeOptions {
+        quote_char,
+        escape_char,
+        ..MergeOptions::new()
+    };
+    let mut sink = LenSink::default();
+    merge_into(input, &options, &mut sink);
+    sink.len
+}
+
+/// Determines whether [`merge_whitespace_with_quotes`] would produce a string different from
+/// the input for the same arguments, e.g. to avoid needlessly writing back unchanged files.
+///
+/// The input is merged without allocating, stopping at the first difference.
+///
+/// ## Example
+///
+/// ```
+/// # use merge_whitespace_utils::would_change;
+/// assert!(!would_change("a \"b   c\" d", Some('"'), None));
+/// assert!(would_change("a  \"b   c\" d", Some('"'), None));
+/// assert!(would_change("a \"b   c\" d\n", Some('"'), None));
+/// ```
+pub fn would_change(input: &str, quote_char: Option<char>, escape_char: Option<char>) -> bool {
+    let options = MergeOptions {
+        quote_char,
+        escape_char,
+        ..MergeOptions::new()
+    };
+    let mut sink = ChangeSink {
+        input,
+        changed: false,
+    };
+    merge_into(input, &options, &mut sink);
+    sink.changed
+}
+
+/// Remove multiple consecutive whitespaces from a given string and replace them with the
+/// configured replacement. See [`MergeOptions`] for the available settings.
+///
+/// Whitespace at a trimmed end of the input is removed entirely and does not produce a
+/// replacement, regardless of what the replacement is.
+///
+/// ## Example
+///
+/// ```
+/// # use merge_whitespace_utils::{merge_whitespace_with_options, MergeOptions, Trim};
+/// let options = MergeOptions::new().replacement("_");
+/// assert_eq!(merge_whitespace_with_options("  a  b  ", &options), "a_b");
+///
+/// let options = options.trim(Trim::None);
+/// assert_eq!(merge_whitespace_with_options("  a  b  ", &options), "_a_b_");
+/// ```
+///
+/// # Return
+///
+/// The modified string.
+pub fn merge_whitespace_with_options<'a>(input: &'a str, options: &MergeOptions) -> Cow<'a, str> {
+    match single_quoted_region(input, options) {
+        Some(quoted) => Cow::Borrowed(quoted),
+        None => {
+            let mut sink = CowSink::new(input);
+            merge_into(input, options, &mut sink);
+            sink.into_cow()
         }
-        if prev_char_was_space {
-            result
-                .get_or_insert_with(|| String::with_capacity(trimmed_input.len()))
-                .push(' ');
+    }
+}
+
+/// Remove multiple consecutive whitespaces from a given string like
+/// [`merge_whitespace_with_options`], while enforcing the policies of the `options` that
+/// are checked before merging.
+///
+/// ## Example
+///
+/// ```
+/// # use merge_whitespace_utils::{try_merge_whitespace_with_options, MergeError, MergeOptions};
+/// let options = MergeOptions::new().quote_char('"').forbid_tabs(true);
+/// assert_eq!(
+///     try_merge_whitespace_with_options("a   \"b\tc\"", &options).as_deref(),
+///     Ok("a \"b\tc\"")
+/// );
+/// assert_eq!(
+///     try_merge_whitespace_with_options("a\t\"b\"", &options),
+///     Err(MergeError::TabFound { at: 1 })
+/// );
+/// ```
+///
+/// # Errors
+///
+/// Returns [`MergeError::InvalidOptions`] if the options fail [`MergeOptions::validate`],
+/// or [`MergeError::TabFound`] if [`MergeOptions::forbid_tabs`] is enabled and the input
+/// contains a tab character outside of quoted regions.
+pub fn try_merge_whitespace_with_options<'a>(
+    input: &'a str,
+    options: &MergeOptions,
+) -> Result<Cow<'a, str>, MergeError> {
+    options.validate()?;
+    if options.forbid_tabs && input.contains('\t') {
+        let mut engine = Engine::new(options);
+        for (offset, c) in input.char_indices() {
+            if c == '\t' && !engine.is_quoted() {
+                return Err(MergeError::TabFound { at: offset });
+            }
+            engine.push(offset, c, &mut Discard);
         }
-        result
-            .get_or_insert_with(|| String::with_capacity(trimmed_input.len()))
-            .push(c);
-        prev_char_was_space = false;
-        in_escape = false;
+    }
+    Ok(merge_whitespace_with_options(input, options))
+}
+
+/// Determines whether the trimmed input consists of a single quoted region, which is kept as-is.
+///
+/// This is a fast path for quoted payloads that only applies to plain quote and escape settings,
+/// i.e. if all other options have their default values.
+fn single_quoted_region<'a>(input: &'a str, options: &MergeOptions) -> Option<&'a str> {
+    let quote_char = options.quote_char?;
+    let plain = MergeOptions {
+        quote_char: options.quote_char,
+        escape_char: options.escape_char,
+        ..MergeOptions::new()
+    };
+    if options != &plain {
+        return None;
     }
 
-    match result {
-        Some(resulting_string) => Cow::Owned(resulting_string),
-        None => Cow::Borrowed(trimmed_input),
+    let trimmed = input.trim();
+    let inner = trimmed.strip_prefix(quote_char)?.strip_suffix(quote_char)?;
+    let mut in_escape = false;
+    for c in inner.chars() {
+        if in_escape {
+            in_escape = false;
+        } else if options.escape_char == Some(c) {
+            in_escape = true;
+        } else if c == quote_char {
+            return None;
+        }
     }
+    // An escaped closing quote does not close the region.
+    (!in_escape).then_some(trimmed)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_support::random_inputs;
 
     const QUOTE: Option<char> = Some('"');
     const ESCAPE: Option<char> = Some('\\');
@@ -148,6 +495,14 @@ mod tests {
         );
     }
 
+    #[test]
+    fn unmodified_text_is_borrowed() {
+        assert!(matches!(
+            merge_whitespace_with_quotes("  foo \"bar\" baz\n", QUOTE, None),
+            Cow::Borrowed("foo \"bar\" baz")
+        ));
+    }
+
     #[test]
     fn multiple_whitespace_in_text_is_merged() {
         assert_eq!(
@@ -172,6 +527,32 @@ mod tests {
         );
     }
 
+    #[test]
+    fn escapes_in_quoted_regions() {
+        // Each input is a single quoted region, followed by a run merged outside of it.
+        for region in [r#""a\ b""#, r#""a\"b""#, r#""a\\b""#, r#""a\\ \"  b""#] {
+            let input = format!("  {region}   c  ");
+            assert_eq!(
+                merge_whitespace_with_quotes(&input, QUOTE, ESCAPE),
+                format!("{region} c")
+            );
+            let regions = quoted_regions(&input, '"', ESCAPE, true);
+            assert_eq!(regions.len(), 1);
+            assert_eq!(&input[regions[0].clone()], region);
+        }
+
+        // An escaped escape character does not escape the closing quote.
+        assert_eq!(
+            merge_whitespace_with_quotes(r#""a\\"   "b   c""#, QUOTE, ESCAPE),
+            r#""a\\" "b   c""#
+        );
+        // An escaped quote character does not close the region.
+        assert_eq!(
+            merge_whitespace_with_quotes(r#""a\"   b   c"#, QUOTE, ESCAPE),
+            r#""a\"   b   c"#
+        );
+    }
+
     #[test]
     fn quoted_whitespace_with_escaped_quotes() {
         assert_eq!(
@@ -225,4 +606,2030 @@ mod tests {
         );
         assert_eq!(result, "query { users (limit: 1, name: \"Froozle   Frobnik\") { id name todos(order_by: {created_at: desc}, limit: 5) { id title } } }");
     }
+
+    #[test]
+    fn multi_char_replacement_is_not_emitted_at_trimmed_ends() {
+        let options = MergeOptions::new().replacement("_");
+        assert_eq!(merge_whitespace_with_options("  a  b  ", &options), "a_b");
+
+        let options = MergeOptions::new().replacement("<->");
+        assert_eq!(merge_whitespace_with_options("  a  b  ", &options), "a<->b");
+        assert_eq!(merge_whitespace_with_options(" \t\n ", &options), "");
+    }
+
+    #[test]
+    fn multi_char_replacement_respects_trim_setting() {
+        let options = MergeOptions::new().replacement("_");
+        assert_eq!(
+            merge_whitespace_with_options("  a  b  ", &options.clone().trim(Trim::Start)),
+            "a_b_"
+        );
+        assert_eq!(
+            merge_whitespace_with_options("  a  b  ", &options.clone().trim(Trim::End)),
+            "_a_b"
+        );
+        assert_eq!(
+            merge_whitespace_with_options("  a  b  ", &options.trim(Trim::None)),
+            "_a_b_"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "unicode")]
+    fn format_chars_are_stripped_when_enabled() {
+        let options = MergeOptions::new().strip_format_chars(true);
+        assert_eq!(
+            merge_whitespace_with_options("soft\u{00AD}hyphen  zero\u{200B}width", &options),
+            "softhyphen zerowidth"
+        );
+        assert_eq!(
+            merge_whitespace_with_options("a \u{00AD}  b\u{0007}", &options),
+            "a b"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "unicode")]
+    fn format_chars_are_kept_in_quotes_when_enabled() {
+        let options = MergeOptions::new().quote_char('"').strip_format_chars(true);
+        assert_eq!(
+            merge_whitespace_with_options("a\u{00AD}b \"a\u{00AD}b\"", &options),
+            "ab \"a\u{00AD}b\""
+        );
+    }
+
+    #[test]
+    fn format_chars_are_kept_by_default() {
+        assert_eq!(
+            merge_whitespace("soft\u{00AD}hyphen  zero\u{200B}width"),
+            "soft\u{00AD}hyphen zero\u{200B}width"
+        );
+    }
+
+    #[test]
+    fn shell_mode_single_quotes_ignore_escapes() {
+        let options = MergeOptions::new().shell_mode(true);
+        assert_eq!(
+            merge_whitespace_with_options(r"cmd   'a\  b'   c", &options),
+            r"cmd 'a\  b' c"
+        );
+        // The backslash does not escape the closing single quote.
+        assert_eq!(
+            merge_whitespace_with_options(r"cmd   'a\'   b", &options),
+            r"cmd 'a\' b"
+        );
+    }
+
+    #[test]
+    fn shell_mode_double_quotes_honor_escapes() {
+        let options = MergeOptions::new().shell_mode(true);
+        assert_eq!(
+            merge_whitespace_with_options(r#"cmd   "a\  b"   c"#, &options),
+            r#"cmd "a\  b" c"#
+        );
+        // The backslash escapes the quote, so the region stays open.
+        assert_eq!(
+            merge_whitespace_with_options(r#"cmd   "a\"   b"   c"#, &options),
+            r#"cmd "a\"   b" c"#
+        );
+    }
+
+    #[test]
+    fn shell_mode_escapes_outside_of_quotes() {
+        let options = MergeOptions::new().shell_mode(true);
+        assert_eq!(
+            merge_whitespace_with_options(r"a\  b   c\'  d", &options),
+            r"a\  b c\' d"
+        );
+        assert_eq!(
+            merge_whitespace_with_options(r#"'a  "b'  "c  'd""#, &options),
+            r#"'a  "b' "c  'd""#
+        );
+    }
+
+    #[test]
+    fn escape_without_quote_char() {
+        assert_eq!(
+            merge_whitespace_with_quotes("a\\  b", None, ESCAPE),
+            "a\\  b"
+        );
+        assert_eq!(
+            merge_whitespace_with_quotes("a   \\  b", None, ESCAPE),
+            "a \\  b"
+        );
+        assert_eq!(
+            merge_whitespace_with_quotes("a \\\"  b  \"", None, ESCAPE),
+            "a \\\" b \""
+        );
+        assert_eq!(
+            merge_whitespace_with_quotes("a\\\\   b", None, ESCAPE),
+            "a\\\\ b"
+        );
+    }
+
+    #[test]
+    fn trailing_escape_without_quote_char() {
+        assert_eq!(merge_whitespace_with_quotes("a\\", None, ESCAPE), "a\\");
+        assert_eq!(merge_whitespace_with_quotes("a  \\", None, ESCAPE), "a \\");
+        assert_eq!(merge_whitespace_with_quotes("a\\ ", None, ESCAPE), "a\\ ");
+        assert_eq!(
+            merge_whitespace_with_quotes("a\\\n  ", None, ESCAPE),
+            "a\\\n"
+        );
+        assert_eq!(
+            merge_whitespace_with_quotes("a\\\\  ", None, ESCAPE),
+            "a\\\\"
+        );
+        assert_eq!(
+            merge_whitespace_with_quotes("a\\\\\\ ", None, ESCAPE),
+            "a\\\\\\ "
+        );
+    }
+
+    #[test]
+    fn would_change_detects_differences() {
+        for input in [
+            "",
+            "a",
+            "a b",
+            "a \"b   c\" d",
+            r#"a\  b"#,
+            "a\\ ",
+            "a \"unclosed   quote",
+            "tab in \"quoted\ttext\"",
+        ] {
+            assert!(!would_change(input, QUOTE, ESCAPE), "{input:?}");
+        }
+        for input in [
+            " ",
+            " a",
+            "a ",
+            "a\n",
+            "a  b",
+            "a\tb",
+            "a \u{3000}b",
+            "a \"b   c\"  d",
+            "a   \"unclosed   quote  ",
+        ] {
+            assert!(would_change(input, QUOTE, ESCAPE), "{input:?}");
+        }
+        assert!(would_change("a \"b   c\"", None, None));
+        assert!(!would_change("a \"b   c\"", QUOTE, None));
+    }
+
+    #[test]
+    fn would_change_matches_merging() {
+        let alphabet = [' ', ' ', '\t', 'a', '"', '\\', '\n', 'é'];
+        for input in random_inputs(&alphabet, 5000, 12) {
+            for (quote, escape) in [(None, None), (QUOTE, ESCAPE)] {
+                let merged = merge_whitespace_with_quotes(&input, quote, escape);
+                assert_eq!(
+                    would_change(&input, quote, escape),
+                    merged != input,
+                    "{input:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn multi_byte_quote_and_escape_chars() {
+        const QUOTE: Option<char> = Some('§');
+        const ESCAPE: Option<char> = Some('¶');
+        assert_eq!(
+            merge_whitespace_with_quotes("  a   §  b ¶§  c  §   ¶   d ¶", QUOTE, ESCAPE),
+            "a §  b ¶§  c  § ¶  d ¶"
+        );
+
+        // The output matches that of single-byte delimiters, with the delimiters swapped.
+        let to_ascii = |s: &str| s.replace('§', "\"").replace('¶', "\\");
+        for input in [
+            "  a   §  b ¶§  c  §   ¶   d ¶",
+            "§§   §  §§ ¶¶  §¶  ¶ ",
+            "  grüße   §  an   alle  §  ¶§  ¶   \u{1F600}§",
+            "a   §  unclosed  ¶§   ",
+        ] {
+            let merged = merge_whitespace_with_quotes(input, QUOTE, ESCAPE);
+            let ascii = to_ascii(input);
+            let expected = merge_whitespace_with_quotes(&ascii, Some('"'), Some('\\'));
+            assert_eq!(to_ascii(&merged), expected, "{input:?}");
+            assert_eq!(
+                chars_merged(input, QUOTE, ESCAPE).collect::<String>(),
+                merged,
+                "{input:?}"
+            );
+            assert_eq!(
+                merged_display(input, QUOTE, ESCAPE).to_string(),
+                merged,
+                "{input:?}"
+            );
+            let (mapped, _) = merge_whitespace_with_map(input, QUOTE, ESCAPE);
+            assert_eq!(mapped, merged, "{input:?}");
+            let tokens: Vec<_> = whitespace_tokens(input, QUOTE, ESCAPE)
+                .map(to_ascii)
+                .collect();
+            let expected: Vec<_> = whitespace_tokens(&ascii, Some('"'), Some('\\')).collect();
+            assert_eq!(tokens, expected, "{input:?}");
+        }
+    }
+
+    #[test]
+    fn escaped_escape_at_end_of_input() {
+        // An escaped escape character keeps both characters and escapes nothing else.
+        for (input, expected) in [
+            ("\\\\", "\\\\"),
+            ("a\\\\", "a\\\\"),
+            ("a\\\\ ", "a\\\\"),
+            ("  a\\\\ \t\n", "a\\\\"),
+            ("a\\\\  b", "a\\\\ b"),
+            ("a\\\\\\ ", "a\\\\\\ "),
+        ] {
+            assert_eq!(merge_whitespace_with_quotes(input, None, ESCAPE), expected);
+            assert_eq!(merge_whitespace_with_quotes(input, QUOTE, ESCAPE), expected);
+            assert_eq!(
+                &*merge_whitespace_bytes(input.as_bytes(), Some(b'"'), Some(b'\\')),
+                expected.as_bytes()
+            );
+        }
+
+        // In quoted regions, an escaped escape character does not escape the closing quote.
+        assert_eq!(
+            merge_whitespace_with_quotes("\"a\\\\\"   b", QUOTE, ESCAPE),
+            "\"a\\\\\" b"
+        );
+        assert_eq!(
+            merge_whitespace_with_quotes("\"a\\\\ \"  ", QUOTE, ESCAPE),
+            "\"a\\\\ \""
+        );
+    }
+
+    #[test]
+    fn max_spaces_caps_run_length() {
+        let options = MergeOptions::new().max_spaces(2);
+        assert_eq!(merge_whitespace_with_options("a b", &options), "a b");
+        assert_eq!(merge_whitespace_with_options("a  b", &options), "a  b");
+        assert_eq!(merge_whitespace_with_options("a     b", &options), "a  b");
+        assert_eq!(
+            merge_whitespace_with_options("  a     b c  ", &options),
+            "a  b c"
+        );
+    }
+
+    #[test]
+    fn clamp_run_limits_the_number_of_replacements() {
+        let input = "  a     b c\t\n d  ";
+        for (clamp_run, expected) in [
+            (0, "abcd"),
+            (1, "a b c d"),
+            (2, "a  b c  d"),
+            (3, "a   b c   d"),
+            (10, "a     b c   d"),
+        ] {
+            let options = MergeOptions::new().clamp_run(clamp_run);
+            assert_eq!(merge_whitespace_with_options(input, &options), expected);
+        }
+
+        let options = MergeOptions::new()
+            .clamp_run(2)
+            .replacement("_")
+            .quote_char('"');
+        assert_eq!(
+            merge_whitespace_with_options("a     \"b     c\"   d", &options),
+            "a__\"b     c\"__d"
+        );
+    }
+
+    #[test]
+    fn exact_run_sets_the_number_of_replacements() {
+        for (exact_run, expected) in [(0, "ab"), (1, "a b"), (3, "a   b")] {
+            let options = MergeOptions::new().exact_run(exact_run);
+            assert_eq!(merge_whitespace_with_options("a   b", &options), expected);
+        }
+        for (exact_run, expected) in [(0, "abcd"), (1, "a b c d"), (3, "a   b   c   d")] {
+            let options = MergeOptions::new().exact_run(exact_run);
+            let output = merge_whitespace_with_options("  a     b c\t\n d  ", &options);
+            assert_eq!(output, expected);
+        }
+
+        let options = MergeOptions::new()
+            .exact_run(3)
+            .replacement("_")
+            .quote_char('"');
+        assert_eq!(
+            merge_whitespace_with_options("a \"b     c\"     d", &options),
+            "a___\"b     c\"___d"
+        );
+        assert_eq!(
+            options.clamp_run(1).validate(),
+            Err(OptionError::Conflicting {
+                option: "clamp_run",
+                with: "exact_run"
+            })
+        );
+    }
+
+    #[test]
+    fn max_spaces_keeps_original_characters() {
+        let options = MergeOptions::new().max_spaces(2).replacement("_");
+        assert_eq!(
+            merge_whitespace_with_options("a\t\t\tb", &options),
+            "a\t\tb"
+        );
+        assert_eq!(merge_whitespace_with_options("a \n\n b", &options), "a \nb");
+
+        let options = MergeOptions::new().max_spaces(0);
+        assert_eq!(merge_whitespace_with_options("a     b", &options), "ab");
+    }
+
+    #[test]
+    fn validated_reports_balanced_quotes() {
+        let (output, balanced) = merge_whitespace_validated("a  \"b  c\"  d", QUOTE, ESCAPE);
+        assert_eq!(output, "a \"b  c\" d");
+        assert!(balanced);
+
+        let (output, balanced) = merge_whitespace_validated("a  \"b \\\"  c\"", QUOTE, ESCAPE);
+        assert_eq!(output, "a \"b \\\"  c\"");
+        assert!(balanced);
+
+        let (output, balanced) = merge_whitespace_validated("a  b", None, None);
+        assert_eq!(output, "a b");
+        assert!(balanced);
+    }
+
+    #[test]
+    fn validated_reports_unbalanced_quotes() {
+        let (output, balanced) = merge_whitespace_validated("a  \"b  c", QUOTE, ESCAPE);
+        assert_eq!(output, "a \"b  c");
+        assert!(!balanced);
+
+        let (output, balanced) = merge_whitespace_validated("a  \"b \\\"  c", QUOTE, ESCAPE);
+        assert_eq!(output, "a \"b \\\"  c");
+        assert!(!balanced);
+    }
+
+    #[test]
+    fn unicode_spaces_are_normalized_when_enabled() {
+        let options = MergeOptions::new()
+            .quote_char('"')
+            .escape_char('\\')
+            .normalize_unicode_space(true);
+        assert_eq!(
+            merge_whitespace_with_options("a\u{00A0}b\u{2003} \u{2003}c", &options),
+            "a b c"
+        );
+        assert_eq!(
+            merge_whitespace_with_options("\"a\u{00A0}\u{00A0}b\" \\\u{2003}c", &options),
+            "\"a  b\" \\ c"
+        );
+
+        let options = options.max_spaces(2);
+        assert_eq!(
+            merge_whitespace_with_options("a\u{2003}\u{00A0}\u{2003}b", &options),
+            "a  b"
+        );
+    }
+
+    #[test]
+    fn unicode_spaces_are_kept_in_quotes_by_default() {
+        let options = MergeOptions::new().quote_char('"');
+        assert_eq!(
+            merge_whitespace_with_options("a\u{00A0}b \"a\u{00A0}\u{2003}b\"", &options),
+            "a b \"a\u{00A0}\u{2003}b\""
+        );
+    }
+
+    #[test]
+    fn doubled_and_backslash_escaped_quotes_in_one_region() {
+        let options = MergeOptions::new()
+            .quote_char('"')
+            .escape_char('\\')
+            .doubled_quote_escapes(true);
+        assert_eq!(
+            merge_whitespace_with_options(r#"say   "a \"b\"   and   ""c""   d"   e"#, &options),
+            r#"say "a \"b\"   and   ""c""   d" e"#
+        );
+        assert_eq!(
+            merge_whitespace_with_options(r#"a   ""   b   """"   c"#, &options),
+            r#"a "" b """" c"#
+        );
+    }
+
+    #[test]
+    fn doubled_quotes_close_and_reopen_by_default() {
+        let options = MergeOptions::new().quote_char('"').escape_char('\\');
+        assert_eq!(
+            merge_whitespace_with_options(r#"say   "a   ""   b"   c"#, &options),
+            r#"say "a   ""   b" c"#
+        );
+    }
+
+    #[test]
+    fn doubled_quote_at_end_of_input() {
+        let options = MergeOptions::new()
+            .quote_char('"')
+            .doubled_quote_escapes(true);
+        assert_eq!(
+            merge_whitespace_with_options("  \"a  b\"  ", &options),
+            "\"a  b\""
+        );
+        assert_eq!(
+            merge_whitespace_with_options("  \"a  b\"\"  ", &options),
+            "\"a  b\"\""
+        );
+    }
+
+    #[test]
+    fn split_on_type_change_separates_runs() {
+        let options = MergeOptions::new().split_on_type_change(true);
+        assert_eq!(merge_whitespace_with_options("a   b", &options), "a b");
+        assert_eq!(merge_whitespace_with_options("a \t b", &options), "a \t b");
+        assert_eq!(
+            merge_whitespace_with_options("a  \t\t  b", &options),
+            "a \t b"
+        );
+        assert_eq!(
+            merge_whitespace_with_options("a \r\n\n\tb\u{00A0} c", &options),
+            "a \n\tb c"
+        );
+    }
+
+    #[test]
+    fn mixed_runs_are_merged_by_default() {
+        assert_eq!(merge_whitespace("a \t b"), "a b");
+        assert_eq!(merge_whitespace("a  \t\t  b"), "a b");
+    }
+
+    #[test]
+    fn collapse_to_last_whitespace() {
+        let options = MergeOptions::new().collapse_to(CollapseTo::Last);
+        assert_eq!(
+            merge_whitespace_with_options("a  \n  \nb", &options),
+            "a\nb"
+        );
+        assert_eq!(merge_whitespace_with_options("a \n \tb", &options), "a\tb");
+        assert_eq!(merge_whitespace_with_options("a b", &options), "a b");
+    }
+
+    #[test]
+    fn collapse_to_first_whitespace() {
+        let options = MergeOptions::new().collapse_to(CollapseTo::First);
+        assert_eq!(merge_whitespace_with_options("a\n   \nb", &options), "a\nb");
+        assert_eq!(merge_whitespace_with_options("a\t \n b", &options), "a\tb");
+        assert_eq!(merge_whitespace_with_options("a  \n\tb", &options), "a b");
+    }
+
+    #[test]
+    fn tabstop_expands_tabs_to_the_next_stop() {
+        let options = MergeOptions::new().preserve_newlines(true).tabstop(4);
+        for (input, expected) in [
+            ("a\tb", "a   b"),
+            ("abcd\tb", "abcd    b"),
+            ("a \t b", "a    b"),
+            ("a\t\tb", "a       b"),
+            ("a  b\tc", "a b c"),
+            ("\ta\tb\n\tcd\te \n", "a   b\ncd  e"),
+            // Multibyte characters count as a single column.
+            ("äöü\tß\t€", "äöü ß   €"),
+        ] {
+            assert_eq!(merge_whitespace_with_options(input, &options), expected);
+        }
+
+        // Quoted tabs are kept and advance the column by one.
+        let options = options.quote_char('"');
+        assert_eq!(
+            merge_whitespace_with_options("\"a\tb\"\tc", &options),
+            "\"a\tb\"   c"
+        );
+
+        // Tab stops only apply if line breaks are preserved.
+        let options = MergeOptions::new().tabstop(4);
+        assert_eq!(merge_whitespace_with_options("a\tb", &options), "a b");
+    }
+
+    #[test]
+    fn preserve_newlines_keeps_line_breaks() {
+        let options = MergeOptions::new().preserve_newlines(true);
+        assert_eq!(
+            merge_whitespace_with_options("  a   b  \n  c\n\n\n d  ", &options),
+            "a b\nc\n\n\nd"
+        );
+        assert_eq!(
+            merge_whitespace_with_options("a \r\n \t b", &options),
+            "a\r\nb"
+        );
+
+        let options = options.quote_char('"');
+        assert_eq!(
+            merge_whitespace_with_options("a  \"b  \n  c\"  \n  d", &options),
+            "a \"b  \n  c\"\nd"
+        );
+    }
+
+    #[test]
+    fn keep_trailing_newline_with_trailing_line_break() {
+        let options = MergeOptions::new().keep_trailing_newline(true);
+        assert_eq!(merge_whitespace_with_options("a  b\n", &options), "a b\n");
+        assert_eq!(
+            merge_whitespace_with_options("a  b \n\n", &options),
+            "a b\n"
+        );
+        assert_eq!(merge_whitespace_with_options("a  b\r\n", &options), "a b\n");
+        assert_eq!(merge_whitespace_with_options("  \n", &options), "\n");
+
+        let options = options.trim(Trim::None);
+        assert_eq!(
+            merge_whitespace_with_options(" a  b \n", &options),
+            " a b\n"
+        );
+
+        let options = options.trim(Trim::Both).preserve_newlines(true);
+        assert_eq!(
+            merge_whitespace_with_options("a  \n  b  \n\n", &options),
+            "a\nb\n"
+        );
+    }
+
+    #[test]
+    fn keep_trailing_newline_without_trailing_line_break() {
+        let options = MergeOptions::new().keep_trailing_newline(true);
+        assert_eq!(merge_whitespace_with_options("a  b", &options), "a b");
+        assert_eq!(
+            merge_whitespace_with_options("a \n b \n  ", &options),
+            "a b"
+        );
+        assert_eq!(merge_whitespace_with_options("", &options), "");
+
+        let options = options.preserve_newlines(true);
+        assert_eq!(merge_whitespace_with_options("a \n b", &options), "a\nb");
+    }
+
+    #[test]
+    fn keep_trailing_newline_borrows_unchanged_input() {
+        let options = MergeOptions::new().keep_trailing_newline(true);
+        assert!(matches!(
+            merge_whitespace_with_options("a b\n", &options),
+            Cow::Borrowed("a b\n")
+        ));
+    }
+
+    #[test]
+    fn table_separator_keeps_column_padding() {
+        let table = "| name   | value |\n|  a     |   1   |\n  text   after  ";
+        let options = MergeOptions::new().table_separator('|');
+        assert_eq!(
+            merge_whitespace_with_options(table, &options),
+            "| name   | value | |  a     |   1   | text after"
+        );
+
+        let options = options.preserve_newlines(true);
+        assert_eq!(
+            merge_whitespace_with_options(table, &options),
+            "| name   | value |\n|  a     |   1   |\ntext after"
+        );
+    }
+
+    #[test]
+    fn table_separator_is_line_scoped() {
+        let options = MergeOptions::new().table_separator('|');
+        assert_eq!(
+            merge_whitespace_with_options("a   | b   c |   d   e", &options),
+            "a | b   c | d e"
+        );
+        assert_eq!(
+            merge_whitespace_with_options("a   | b   c\n  d   | e   f |", &options),
+            "a | b c d | e   f |"
+        );
+    }
+
+    #[test]
+    fn table_separator_in_quotes_and_escaped() {
+        let options = MergeOptions::new()
+            .table_separator('|')
+            .quote_char('"')
+            .escape_char('\\');
+        assert_eq!(
+            merge_whitespace_with_options("\"a | b\"   c   | d", &options),
+            "\"a | b\" c | d"
+        );
+        assert_eq!(
+            merge_whitespace_with_options(r"a   \|   b   |   c   \|   d   |", &options),
+            r"a \| b |   c   \|   d   |"
+        );
+        assert_eq!(
+            merge_whitespace_with_options(r"a   \|   b   | c \| d   e", &options),
+            r"a \| b | c \| d e"
+        );
+
+        assert_eq!(
+            merge_whitespace_with_options(r#"x |  "a  |  b"   c"#, &options),
+            r#"x | "a  |  b" c"#
+        );
+        assert_eq!(
+            merge_whitespace_with_options(r#"x |  "a  |  b"   c  |  d"#, &options),
+            r#"x |  "a  |  b"   c  | d"#
+        );
+    }
+
+    #[test]
+    fn trim_chars_only_removes_listed_chars() {
+        let options = MergeOptions::new().trim_chars(&['\n']);
+        assert_eq!(merge_whitespace_with_options("a b \n", &options), "a b ");
+        assert_eq!(merge_whitespace_with_options("a b  \n\n", &options), "a b ");
+        assert_eq!(merge_whitespace_with_options("\n\n  a b", &options), " a b");
+        assert_eq!(merge_whitespace_with_options(" \n a \n ", &options), " a ");
+        assert_eq!(merge_whitespace_with_options("\n\n", &options), "");
+
+        // The listed characters are only removed at the trimmed ends.
+        let options = options.trim(Trim::End);
+        assert_eq!(
+            merge_whitespace_with_options("\na\n\nb\n", &options),
+            " a b"
+        );
+
+        let options = MergeOptions::new().trim_chars(&[' ', '.']).quote_char('"');
+        assert_eq!(
+            merge_whitespace_with_options(". \"a  \" \n. ", &options),
+            "\"a  \" "
+        );
+    }
+
+    #[test]
+    fn quote_first_and_last_after_trimming() {
+        assert_eq!(
+            merge_whitespace_with_quotes("\"  x \"", QUOTE, ESCAPE),
+            Cow::Borrowed("\"  x \"")
+        );
+        assert_eq!(
+            merge_whitespace_with_quotes("   \"  x \"   ", QUOTE, ESCAPE),
+            Cow::Borrowed("\"  x \"")
+        );
+        assert_eq!(
+            merge_whitespace_with_quotes("\t\"a  b\"   c   \"d  e\"\n", QUOTE, ESCAPE),
+            "\"a  b\" c \"d  e\""
+        );
+        assert_eq!(
+            merge_whitespace_with_quotes("  \"\"  ", QUOTE, ESCAPE),
+            Cow::Borrowed("\"\"")
+        );
+    }
+
+    #[test]
+    fn quote_first_and_last_respects_trim_setting() {
+        let options = MergeOptions::new().quote_char('"').trim(Trim::None);
+        assert_eq!(
+            merge_whitespace_with_options("   \"  x \"   ", &options),
+            " \"  x \" "
+        );
+
+        let options = options.trim(Trim::Start);
+        assert_eq!(
+            merge_whitespace_with_options("   \"  x \"   ", &options),
+            "\"  x \" "
+        );
+
+        let options = options.trim(Trim::End);
+        assert_eq!(
+            merge_whitespace_with_options("   \"  x \"   ", &options),
+            " \"  x \""
+        );
+    }
+
+    #[test]
+    fn leading_whitespace_before_quote_for_each_trim_setting() {
+        let input = "   \"a  b\"";
+        for (trim, expected) in [
+            (Trim::Both, "\"a  b\""),
+            (Trim::Start, "\"a  b\""),
+            (Trim::End, " \"a  b\""),
+            (Trim::None, " \"a  b\""),
+        ] {
+            let options = MergeOptions::new().quote_char('"').trim(trim);
+            assert_eq!(
+                merge_whitespace_with_options(input, &options),
+                expected,
+                "{trim:?}"
+            );
+
+            // A multi-character replacement stands in for the whole run.
+            let options = options.replacement("__");
+            let expected = expected.replace(" \"", "__\"");
+            assert_eq!(
+                merge_whitespace_with_options(input, &options),
+                expected,
+                "{trim:?}"
+            );
+
+            // Runs at quote edges are dropped regardless of trimming.
+            let options = options.edge_collapse(EdgeCollapse::DropAtQuotes);
+            assert_eq!(
+                merge_whitespace_with_options(input, &options),
+                "\"a  b\"",
+                "{trim:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn leading_newline_strip() {
+        let options = MergeOptions::new().leading_newline(LeadingPolicy::Strip);
+        assert_eq!(
+            merge_whitespace_with_options("\n\n   first  line", &options),
+            "first line"
+        );
+
+        let options = options.trim(Trim::None);
+        assert_eq!(
+            merge_whitespace_with_options("\n\n   first  line  ", &options),
+            "first line "
+        );
+    }
+
+    #[test]
+    fn leading_newline_keep_one() {
+        let options = MergeOptions::new().leading_newline(LeadingPolicy::KeepOne);
+        assert_eq!(
+            merge_whitespace_with_options("\n\n   first  line", &options),
+            "\nfirst line"
+        );
+        assert_eq!(
+            merge_whitespace_with_options("   first  line", &options),
+            "first line"
+        );
+        assert_eq!(merge_whitespace_with_options(" \r\n ", &options), "\n");
+
+        let options = options.trim(Trim::None);
+        assert_eq!(
+            merge_whitespace_with_options("  \n  first  line  ", &options),
+            "\nfirst line "
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "unicode")]
+    fn case_differs_inside_and_outside_of_quotes() {
+        let options = MergeOptions::new()
+            .quote_char('"')
+            .escape_char('\\')
+            .case_unquoted(Case::Upper)
+            .case_quoted(Case::Lower);
+        assert_eq!(
+            merge_whitespace_with_options(r#"select   "ÄÖÜ  \"X\""   as   straße"#, &options),
+            r#"SELECT "äöü  \"x\"" AS STRASSE"#
+        );
+
+        let options = MergeOptions::new().quote_char('"').case_quoted(Case::Upper);
+        assert_eq!(
+            merge_whitespace_with_options("Keep   \"ǆ  ß\"   Case", &options),
+            "Keep \"Ǆ  SS\" Case"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "unicode")]
+    fn case_is_kept_by_default() {
+        let options = MergeOptions::new().quote_char('"');
+        assert!(matches!(
+            merge_whitespace_with_options("MiXeD \"CaSe\"", &options),
+            Cow::Borrowed(_)
+        ));
+
+        let options = options.case_unquoted(Case::Lower);
+        assert!(matches!(
+            merge_whitespace_with_options("lower \"CaSe\"", &options),
+            Cow::Borrowed(_)
+        ));
+    }
+
+    #[test]
+    fn to_vec_appends_merged_bytes() {
+        let input = "  a   \"b   c\"   \\  d  ";
+        let mut out = b"prefix:".to_vec();
+        merge_whitespace_to_vec(input, &mut out, QUOTE, ESCAPE);
+
+        let expected = merge_whitespace_with_quotes(input, QUOTE, ESCAPE);
+        assert_eq!(out[..7], *b"prefix:");
+        assert_eq!(out[7..], *expected.as_bytes());
+    }
+
+    #[test]
+    fn to_vec_appends_multi_byte_characters() {
+        let mut out = vec![0xFF];
+        merge_whitespace_to_vec("Grüße,  \u{2003} Welt", &mut out, None, None);
+        assert_eq!(out[0], 0xFF);
+        assert_eq!(out[1..], *"Grüße, Welt".as_bytes());
+
+        merge_whitespace_to_vec("   ", &mut out, None, None);
+        assert_eq!(out.len(), 1 + "Grüße, Welt".len());
+    }
+
+    #[test]
+    fn to_string_matches_cow() {
+        for input in [
+            "a \"b   c\" d",
+            "  a b  ",
+            "a   \"b   c\"   \\  d",
+            "",
+            "   ",
+        ] {
+            let expected = merge_whitespace_with_quotes(input, QUOTE, ESCAPE);
+            assert_eq!(merge_whitespace_to_string(input, QUOTE, ESCAPE), expected);
+        }
+        assert!(matches!(
+            merge_whitespace_with_quotes("a \"b   c\" d", QUOTE, ESCAPE),
+            Cow::Borrowed(_)
+        ));
+        assert!(matches!(
+            merge_whitespace_with_quotes("a   \"b   c\"   \\  d", QUOTE, ESCAPE),
+            Cow::Owned(_)
+        ));
+    }
+
+    #[test]
+    fn typographic_quotes_keep_whitespace() {
+        let options = MergeOptions::new().typographic_quotes(true);
+        assert_eq!(
+            merge_whitespace_with_options("  x   «  a  b  »   y  ", &options),
+            "x «  a  b  » y"
+        );
+        assert_eq!(
+            merge_whitespace_with_options("x   “  x  ”   y", &options),
+            "x “  x  ” y"
+        );
+        assert_eq!(
+            merge_whitespace_with_options("«  a  ”  b  »   “  c  »  d  ”   e", &options),
+            "«  a  ”  b  » “  c  »  d  ” e"
+        );
+    }
+
+    #[test]
+    fn typographic_quotes_track_nesting() {
+        let options = MergeOptions::new().typographic_quotes(true);
+        assert_eq!(
+            merge_whitespace_with_options("«  a  «  b  »  c  »   d", &options),
+            "«  a  «  b  »  c  » d"
+        );
+        assert_eq!(
+            merge_whitespace_with_options("“ “ “  a ” ”  b ”   c   ”   d", &options),
+            "“ “ “  a ” ”  b ” c ” d"
+        );
+    }
+
+    #[test]
+    fn typographic_quotes_combine_with_quote_char() {
+        let options = MergeOptions::new()
+            .typographic_quotes(true)
+            .quote_char('"')
+            .escape_char('\\');
+        assert_eq!(
+            merge_whitespace_with_options(r#"«  "  »   "  «  "   \«   a"#, &options),
+            r#"«  "  » "  «  " \« a"#
+        );
+    }
+
+    #[test]
+    fn preserve_parens_tracks_nesting() {
+        let options = MergeOptions::new().preserve_parens(true);
+        assert_eq!(
+            merge_whitespace_with_options("  f  ( a  ,  ( b   c )  d )   ( e )  ", &options),
+            "f ( a  ,  ( b   c )  d ) ( e )"
+        );
+        assert_eq!(
+            merge_whitespace_with_options("a   )   b   (  c", &options),
+            "a ) b (  c"
+        );
+    }
+
+    #[test]
+    fn preserve_parens_ignores_quotes_inside() {
+        let options = MergeOptions::new()
+            .preserve_parens(true)
+            .quote_char('"')
+            .escape_char('\\');
+        assert_eq!(
+            merge_whitespace_with_options(r#"(  "  a  )   "  b  "   c"#, &options),
+            r#"(  "  a  ) "  b  " c"#
+        );
+        assert_eq!(
+            merge_whitespace_with_options(r#""  (  "   (  \)  )   d"#, &options),
+            r#""  (  " (  \)  ) d"#
+        );
+    }
+
+    #[test]
+    fn drop_empty_quotes_merges_surrounding_whitespace() {
+        let options = MergeOptions::new().quote_char('"').drop_empty_quotes(true);
+        for (input, expected) in [
+            (r#"a   ""   b"#, "a b"),
+            (r#"a "x"   b"#, r#"a "x" b"#),
+            ("a  \" \t \"  \"\"b", "a b"),
+            (r#"a""b"#, "ab"),
+            (r#"  ""  a  ""  "#, "a"),
+            (r#""""#, ""),
+            (r#"a "  x "  ""#, r#"a "  x " ""#),
+        ] {
+            assert_eq!(
+                merge_whitespace_with_options(input, &options),
+                expected,
+                "{input:?}"
+            );
+        }
+
+        let options = options.trim(Trim::None);
+        assert_eq!(
+            merge_whitespace_with_options(r#" "" a "" "#, &options),
+            " a "
+        );
+    }
+
+    #[test]
+    fn drop_empty_quotes_respects_escapes() {
+        let options = MergeOptions::new()
+            .quote_char('"')
+            .escape_char('\\')
+            .drop_empty_quotes(true);
+        assert_eq!(
+            merge_whitespace_with_options(r#"a  \"\"  "\""  "\\"  b"#, &options),
+            r#"a \"\" "\"" "\\" b"#
+        );
+
+        let options = MergeOptions::new()
+            .quote_char('"')
+            .doubled_quote_escapes(true)
+            .drop_empty_quotes(true);
+        assert_eq!(
+            merge_whitespace_with_options(r#"a  """"  ""  b"#, &options),
+            r#"a """" b"#
+        );
+    }
+
+    #[test]
+    fn debug_sentinel_marks_each_collapsed_run() {
+        let options = MergeOptions::new()
+            .quote_char('"')
+            .escape_char('\\')
+            .debug_sentinel('·');
+        let input = " \t a   b c\n\nd \"e   f\"\t\"g\\   h\" \t ";
+        let output = merge_whitespace_with_options(input, &options);
+        assert_eq!(output, "a·b c·d \"e   f\"·\"g\\   h\"");
+        assert_eq!(output.matches('·').count(), 3);
+        assert_eq!(
+            output.replace('·', " "),
+            merge_whitespace_with_quotes(input, QUOTE, ESCAPE)
+        );
+
+        // Runs that are already merged are not marked.
+        assert!(matches!(
+            merge_whitespace_with_options("a b \"c   d\"", &options),
+            Cow::Borrowed(_)
+        ));
+
+        let options = options.replacement("_");
+        assert_eq!(
+            merge_whitespace_with_options("a b_c  d", &options),
+            "a·b_c·d"
+        );
+    }
+
+    #[test]
+    fn keep_singletons_only_keeps_single_characters() {
+        let options = MergeOptions::new().keep_singletons(true);
+        assert_eq!(merge_whitespace_with_options("a\tb", &options), "a\tb");
+        assert_eq!(merge_whitespace_with_options("a \t b", &options), "a b");
+        assert_eq!(
+            merge_whitespace_with_options("a\nb\u{3000}c", &options),
+            "a\nb\u{3000}c"
+        );
+        assert_eq!(
+            merge_whitespace_with_options("a\n\nb\t\tc", &options),
+            "a b c"
+        );
+        assert_eq!(merge_whitespace_with_options("\ta\t", &options), "a");
+        assert!(matches!(
+            merge_whitespace_with_options("a\tb c", &options),
+            Cow::Borrowed("a\tb c")
+        ));
+
+        let options = options.replacement("_").normalize_unicode_space(true);
+        assert_eq!(
+            merge_whitespace_with_options("a\u{3000}b\tc  d", &options),
+            "a b\tc_d"
+        );
+    }
+
+    #[test]
+    fn typographic_quotes_are_ignored_by_default() {
+        assert_eq!(
+            merge_whitespace_with_options("«  a  »   “  b  ”", &MergeOptions::new()),
+            "« a » “ b ”"
+        );
+    }
+
+    #[test]
+    fn category_replacement_uses_dominant_category() {
+        let options = MergeOptions::new().category_replacement(CategoryReplacement::new());
+        assert_eq!(
+            merge_whitespace_with_options("a   b\t\tc\n\nd", &options),
+            "a b\tc\nd"
+        );
+        assert_eq!(
+            merge_whitespace_with_options("a \t\t b\t\n\n\tc \n\n d\t\n", &options),
+            "a\tb\nc\nd"
+        );
+        assert_eq!(
+            merge_whitespace_with_options("a \u{2003}\tb\r\n  c", &options),
+            "a b\nc"
+        );
+    }
+
+    #[test]
+    fn category_replacement_with_custom_characters() {
+        let replacement = CategoryReplacement::new()
+            .space('_')
+            .tab('→')
+            .line_break('¶');
+        let options = MergeOptions::new()
+            .category_replacement(replacement)
+            .quote_char('"');
+        assert_eq!(
+            merge_whitespace_with_options("a  b\t\tc\n \nd \"e \t\n f\"", &options),
+            "a_b→c¶d_\"e \t\n f\""
+        );
+    }
+
+    #[test]
+    fn category_replacement_with_split_on_type_change() {
+        let replacement = CategoryReplacement::new().tab(' ');
+        let options = MergeOptions::new()
+            .category_replacement(replacement)
+            .split_on_type_change(true);
+        assert_eq!(
+            merge_whitespace_with_options("a  \t\t\n\n b", &options),
+            "a  \n b"
+        );
+    }
+
+    #[test]
+    fn escape_protects_run_when_enabled() {
+        let options = MergeOptions::new()
+            .quote_char('"')
+            .escape_char('\\')
+            .escape_protects_run(true);
+        assert_eq!(
+            merge_whitespace_with_options(r"a\   b", &options),
+            r"a\   b"
+        );
+        assert_eq!(
+            merge_whitespace_with_options("a\\ \t\n b   c\\x   d", &options),
+            "a\\ \t\n b c\\x d"
+        );
+        assert_eq!(
+            merge_whitespace_with_options(r#"a\   "b   c"   d\  "#, &options),
+            r#"a\   "b   c" d\  "#
+        );
+    }
+
+    #[test]
+    fn escape_protects_single_char_by_default() {
+        let options = MergeOptions::new().escape_char('\\');
+        assert_eq!(merge_whitespace_with_options(r"a\   b", &options), r"a\  b");
+        assert_eq!(merge_whitespace_with_options(r"a\   ", &options), r"a\ ");
+    }
+
+    #[test]
+    fn single_quoted_region_is_borrowed() {
+        let input = "  \"  a  b  \"\n";
+        let output = merge_whitespace_with_quotes(input, QUOTE, ESCAPE);
+        assert_eq!(output, "\"  a  b  \"");
+        let Cow::Borrowed(output) = output else {
+            panic!("expected a borrowed output");
+        };
+        assert!(std::ptr::eq(output, &input[2..12]));
+
+        let input = r#""  a \"  b \\" "#;
+        assert!(matches!(
+            merge_whitespace_with_quotes(input, QUOTE, ESCAPE),
+            Cow::Borrowed(r#""  a \"  b \\""#)
+        ));
+    }
+
+    #[test]
+    fn single_quoted_region_matches_full_merge() {
+        for input in [
+            r#""a  b""#,
+            r#""a"  "b""#,
+            r#"  "a  \"  "#,
+            r#""a  \\"  \"  b""#,
+            r#"""#,
+            r#""""#,
+            "\"a  b\"  c",
+            "\"a \u{a0} \"\"  b\"",
+        ] {
+            let base = MergeOptions::new().quote_char('"').escape_char('\\');
+            // Any other option may affect quoted text, which disables the fast path.
+            for options in [
+                base.clone(),
+                base.clone().normalize_unicode_space(true),
+                base.clone().doubled_quote_escapes(true),
+                base.clone().trim(Trim::None),
+            ] {
+                let mut sink = CowSink::new(input);
+                merge_into(input, &options, &mut sink);
+                assert_eq!(
+                    merge_whitespace_with_options(input, &options),
+                    sink.into_cow(),
+                    "{input:?}, {options:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn whitespace_around_comments_is_merged() {
+        let options = MergeOptions::new().comment_char('#');
+        assert_eq!(
+            merge_whitespace_with_options("a  # c\n  b", &options),
+            "a b"
+        );
+        assert_eq!(merge_whitespace_with_options("a# c\nb", &options), "a b");
+        assert_eq!(
+            merge_whitespace_with_options("a  # c\n  # d\n\n  b  #e", &options),
+            "a b"
+        );
+        assert_eq!(merge_whitespace_with_options("  # c\n  b", &options), "b");
+    }
+
+    #[test]
+    fn comments_in_quotes_and_escaped() {
+        let options = MergeOptions::new()
+            .comment_char('#')
+            .quote_char('"')
+            .escape_char('\\');
+        assert_eq!(
+            merge_whitespace_with_options("a  \"# b\"  # c \"d\n  e", &options),
+            "a \"# b\" e"
+        );
+        assert_eq!(
+            merge_whitespace_with_options("a  \\#  b # c", &options),
+            "a \\# b"
+        );
+    }
+
+    #[test]
+    fn comments_with_other_options() {
+        let options = MergeOptions::new()
+            .comment_char('#')
+            .preserve_newlines(true);
+        assert_eq!(
+            merge_whitespace_with_options("a  # c\n  b  # d\nc", &options),
+            "a\nb\nc"
+        );
+
+        let options = MergeOptions::new().comment_char('#').trim(Trim::None);
+        assert_eq!(
+            merge_whitespace_with_options(" # c\n a  #  d", &options),
+            " a "
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "unicode")]
+    fn format_chars_at_trimmed_ends_are_merged() {
+        let options = MergeOptions::new().strip_format_chars(true);
+        assert_eq!(
+            merge_whitespace_with_options("\u{200B}  a  \u{00AD}", &options),
+            "a"
+        );
+    }
+
+    #[test]
+    fn escape_only_specials_keeps_ordinary_escapes_literal() {
+        let options = MergeOptions::new()
+            .quote_char('"')
+            .escape_char('\\')
+            .comment_char('#')
+            .escape_only_specials(true);
+        assert_eq!(
+            merge_whitespace_with_options(r"a\b   c", &options),
+            r"a\b c"
+        );
+        assert_eq!(merge_whitespace_with_options(r"a\#b   c", &options), r"a\");
+        assert_eq!(
+            merge_whitespace_with_options(r"a\\#b   c", &options),
+            r"a\\"
+        );
+
+        let options = options.escape_only_specials(false);
+        assert_eq!(
+            merge_whitespace_with_options(r"a\#b   c", &options),
+            r"a\#b c"
+        );
+    }
+
+    #[test]
+    fn escape_only_specials_escapes_quotes_and_whitespace() {
+        let options = MergeOptions::new()
+            .quote_char('"')
+            .escape_char('\\')
+            .escape_only_specials(true);
+        assert_eq!(
+            merge_whitespace_with_options(r#"a\"b   c"#, &options),
+            r#"a\"b c"#
+        );
+        assert_eq!(
+            merge_whitespace_with_options(r#""a\"  b"   c\   d"#, &options),
+            r#""a\"  b" c\  d"#
+        );
+        assert_eq!(
+            merge_whitespace_with_options(r#"a\\"b   c""#, &options),
+            r#"a\\"b   c""#
+        );
+    }
+
+    #[test]
+    fn urls_are_protected_when_enabled() {
+        let options = MergeOptions::new()
+            .comment_char('#')
+            .table_separator('|')
+            .escape_char('\\')
+            .protect_urls(true);
+        assert_eq!(
+            merge_whitespace_with_options(
+                "see   https://example.com/a%20b#c|d\\e   and   (HTTP://x.org/#y)  # z",
+                &options
+            ),
+            "see https://example.com/a%20b#c|d\\e and (HTTP://x.org/#y)"
+        );
+        assert_eq!(
+            merge_whitespace_with_options("nohttp://example.com#x", &options),
+            "nohttp://example.com"
+        );
+
+        let options = options.protect_urls(false);
+        assert_eq!(
+            merge_whitespace_with_options("see   https://example.com/#top", &options),
+            "see https://example.com/"
+        );
+    }
+
+    #[test]
+    fn urls_in_quotes_are_quoted_text() {
+        let options = MergeOptions::new().quote_char('"').protect_urls(true);
+        assert_eq!(
+            merge_whitespace_with_options("\"a  https://x.org  b\"   https://y.org\"  c", &options),
+            "\"a  https://x.org  b\" https://y.org\" c"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "unicode")]
+    fn urls_are_protected_from_case_conversion() {
+        let options = MergeOptions::new()
+            .case_unquoted(Case::Lower)
+            .strip_format_chars(true)
+            .protect_urls(true);
+        assert_eq!(
+            merge_whitespace_with_options(
+                "Visit   https://Example.com/Path\u{00AD}X   NOW",
+                &options
+            ),
+            "visit https://Example.com/Path\u{00AD}X now"
+        );
+    }
+
+    #[test]
+    fn merged_len_matches_output_len() {
+        let inputs = [
+            "",
+            "  ",
+            "  \n \t  ",
+            "abcdefgh.ihkl-",
+            "foo bar baz",
+            "  foo \"bar\" baz\n",
+            "foo  bar\nbaz",
+            "foo   foobar   \"  bar\n\" baz",
+            "what   \\   if I quote\\ spaces",
+            r#"foo   foobar   "  \"bar   \"   "   baz"#,
+            r#"  query {  users (name: "Froozle   '78\"'   Frobnik") { id }  }  "#,
+            "a\\   b   c\\ ",
+            "trailing escape\\",
+            "  \"  x \"  ",
+            "Grüße,  \u{2003} Welt",
+            "a   \"unclosed   quote  ",
+        ];
+        for input in inputs {
+            for (quote, escape) in [(None, None), (QUOTE, None), (None, ESCAPE), (QUOTE, ESCAPE)] {
+                assert_eq!(
+                    merged_len(input, quote, escape),
+                    merge_whitespace_with_quotes(input, quote, escape).len(),
+                    "{input:?}"
+                );
+            }
+            assert_eq!(merged_len(input, None, None), merge_whitespace(input).len());
+        }
+    }
+
+    #[test]
+    fn split_on_preserves_carriage_returns() {
+        let input = "record   one \r  record two\r\r\trecord three\n  x";
+        assert_eq!(
+            merge_whitespace_with_options(input, &MergeOptions::new()),
+            "record one record two record three x"
+        );
+
+        let options = MergeOptions::new().split_on('\r');
+        assert_eq!(
+            merge_whitespace_with_options(input, &options),
+            "record one\rrecord two\r\rrecord three x"
+        );
+    }
+
+    #[test]
+    fn split_on_non_whitespace_separator() {
+        let options = MergeOptions::new()
+            .split_on('|')
+            .quote_char('"')
+            .escape_char('\\');
+        assert_eq!(
+            merge_whitespace_with_options("a  |  b   c|d |", &options),
+            "a|b c|d|"
+        );
+        assert_eq!(
+            merge_whitespace_with_options(r#""a | b"  |  c \|  d"#, &options),
+            r#""a | b"|c \| d"#
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "unicode")]
+    fn case_is_mapped_outside_of_quotes() {
+        let input = "Select   name,  'Value'  from  t";
+        assert_eq!(
+            merge_whitespace_map_case(input, Case::Upper, Some('\''), None),
+            "SELECT NAME, 'Value' FROM T"
+        );
+        assert_eq!(
+            merge_whitespace_map_case(input, Case::Lower, Some('\''), None),
+            "select name, 'Value' from t"
+        );
+        assert_eq!(
+            merge_whitespace_map_case(input, Case::None, Some('\''), None),
+            "Select name, 'Value' from t"
+        );
+        assert_eq!(
+            merge_whitespace_map_case(
+                r"Select  'it\'s  Value'",
+                Case::Upper,
+                Some('\''),
+                Some('\\')
+            ),
+            r"SELECT 'it\'s  Value'"
+        );
+    }
+
+    #[test]
+    fn small_inputs_are_trimmed() {
+        const ALPHABET: [&str; 5] = [" ", "\t", "a", "\"", "\\"];
+
+        let singles = ALPHABET.iter().map(|a| a.to_string());
+        let pairs = ALPHABET
+            .iter()
+            .flat_map(|a| ALPHABET.iter().map(move |b| format!("{a}{b}")));
+        for input in std::iter::once(String::new()).chain(singles).chain(pairs) {
+            for (quote, escape) in [(None, None), (QUOTE, None), (None, ESCAPE), (QUOTE, ESCAPE)] {
+                // Inputs this short never contain a run to merge, but an escaped trailing
+                // whitespace character is not trimmed.
+                let escaped_end =
+                    escape.is_some() && (input.ends_with("\\ ") || input.ends_with("\\\t"));
+                let expected = if escaped_end {
+                    input.trim_start()
+                } else {
+                    input.trim()
+                };
+
+                let output = merge_whitespace_with_quotes(&input, quote, escape);
+                assert_eq!(
+                    output,
+                    Cow::Borrowed(expected),
+                    "{input:?} {quote:?} {escape:?}"
+                );
+                assert_eq!(merged_len(&input, quote, escape), expected.len());
+            }
+        }
+    }
+
+    #[test]
+    fn verbatim_spans_protect_several_kinds() {
+        let options = MergeOptions::new()
+            .quote_char('"')
+            .verbatim_spans(&[("$(", ")"), ("${", "}")]);
+        assert_eq!(
+            merge_whitespace_with_options(
+                "  echo   $(  date  \"+%F\" )   ${ HOME }/  x   \"${  y  }\"   $ (  z )",
+                &options
+            ),
+            "echo $(  date  \"+%F\" ) ${ HOME }/ x \"${  y  }\" $ ( z )"
+        );
+
+        // Spans do not nest, so the first closing sentinel ends them.
+        assert_eq!(
+            merge_whitespace_with_options("$(a  ${b  )  c}  d", &options),
+            "$(a  ${b  ) c} d"
+        );
+
+        // The `verbatim_span` is used along with the others.
+        let options = options.verbatim_span("<<EOF", "EOF");
+        assert_eq!(
+            merge_whitespace_with_options("<<EOF  a  EOF   $(b   c)", &options),
+            "<<EOF  a  EOF $(b   c)"
+        );
+    }
+
+    #[test]
+    fn longest_verbatim_span_opener_wins() {
+        let spans = [("<", ">"), ("<<", ">>")];
+        let options = MergeOptions::new().verbatim_spans(&spans);
+        assert_eq!(
+            merge_whitespace_with_options("a  <<x  >  y>>  b  <c  >", &options),
+            "a <<x  >  y>> b <c  >"
+        );
+
+        let spans = [("<<", ">>"), ("<<", ">")];
+        let options = MergeOptions::new().verbatim_spans(&spans);
+        assert_eq!(
+            merge_whitespace_with_options("<<x  >  y>>  b", &options),
+            "<<x  >  y>> b"
+        );
+        let options = options.verbatim_span("<<", ">");
+        assert_eq!(
+            merge_whitespace_with_options("<<x  >  y>>  b", &options),
+            "<<x  > y>> b"
+        );
+
+        assert_eq!(
+            MergeOptions::new()
+                .verbatim_spans(&[("$(", ")"), ("", "}")])
+                .validate(),
+            Err(OptionError::Empty {
+                option: "verbatim_spans"
+            })
+        );
+    }
+
+    #[test]
+    fn verbatim_span_is_kept_as_is() {
+        let options = MergeOptions::new()
+            .quote_char('"')
+            .escape_char('\\')
+            .verbatim_span("<<EOF", "EOF");
+        let input = "cat   <<EOF\n  say \"hi\n  C:\\path\\   \\\nEOF   |   sort  \"a   b\"";
+        assert_eq!(
+            merge_whitespace_with_options(input, &options),
+            "cat <<EOF\n  say \"hi\n  C:\\path\\   \\\nEOF | sort \"a   b\""
+        );
+
+        // Sentinels in quoted text or following an escape character are ignored.
+        assert_eq!(
+            merge_whitespace_with_options("\"<<EOF\"   a   \\<<EOF   b   EOF", &options),
+            "\"<<EOF\" a \\<<EOF b EOF"
+        );
+
+        // A span without a closing sentinel extends to the end of the input.
+        assert_eq!(
+            merge_whitespace_with_options("a   <<EOF  b  \"  ", &options),
+            "a <<EOF  b  \""
+        );
+    }
+
+    #[test]
+    fn forbid_tabs_rejects_unquoted_tabs() {
+        let options = MergeOptions::new()
+            .quote_char('"')
+            .escape_char('\\')
+            .forbid_tabs(true);
+        assert_eq!(
+            try_merge_whitespace_with_options("a  b \t c", &options),
+            Err(MergeError::TabFound { at: 5 })
+        );
+        assert_eq!(
+            try_merge_whitespace_with_options("\"a\\\"\tb\"\t", &options),
+            Err(MergeError::TabFound { at: 7 })
+        );
+        assert_eq!(
+            try_merge_whitespace_with_options("a   \"\tb\t\"  c", &options).as_deref(),
+            Ok("a \"\tb\t\" c")
+        );
+        assert_eq!(
+            MergeError::TabFound { at: 5 }.to_string(),
+            "Found a tab character at byte 5"
+        );
+
+        // Tabs are merged as usual if allowed.
+        let options = options.forbid_tabs(false);
+        assert_eq!(
+            try_merge_whitespace_with_options("a  b \t c", &options).as_deref(),
+            Ok("a b c")
+        );
+    }
+
+    #[test]
+    fn contradictory_options_are_rejected() {
+        let options = MergeOptions::new();
+        assert_eq!(
+            options
+                .clone()
+                .quote_char('\\')
+                .escape_char('\\')
+                .validate(),
+            Err(OptionError::SameChar {
+                option: "quote_char",
+                with: "escape_char"
+            })
+        );
+        assert_eq!(
+            options.clone().max_spaces(2).clamp_run(2).validate(),
+            Err(OptionError::Conflicting {
+                option: "clamp_run",
+                with: "max_spaces"
+            })
+        );
+        assert_eq!(
+            options
+                .clone()
+                .collapse_to(CollapseTo::First)
+                .clamp_run(2)
+                .validate(),
+            Err(OptionError::Conflicting {
+                option: "collapse_to",
+                with: "clamp_run"
+            })
+        );
+        assert_eq!(
+            options
+                .clone()
+                .collapse_to(CollapseTo::Last)
+                .category_replacement(CategoryReplacement::new())
+                .validate(),
+            Err(OptionError::Conflicting {
+                option: "category_replacement",
+                with: "collapse_to"
+            })
+        );
+        assert_eq!(
+            options.clone().tabstop(4).validate(),
+            Err(OptionError::Requires {
+                option: "tabstop",
+                requires: "preserve_newlines"
+            })
+        );
+        assert_eq!(
+            options.clone().verbatim_span("", "EOF").validate(),
+            Err(OptionError::Empty {
+                option: "verbatim_span"
+            })
+        );
+
+        // Sensible combinations are accepted.
+        assert_eq!(options.clone().validate(), Ok(()));
+        assert_eq!(
+            options
+                .clone()
+                .quote_char('"')
+                .escape_char('\\')
+                .preserve_newlines(true)
+                .tabstop(4)
+                .clamp_run(2)
+                .validate(),
+            Ok(())
+        );
+
+        // Invalid options are reported by the fallible API.
+        let options = options.tabstop(8);
+        let error = try_merge_whitespace_with_options("a  b", &options).unwrap_err();
+        assert_eq!(
+            error,
+            MergeError::InvalidOptions(OptionError::Requires {
+                option: "tabstop",
+                requires: "preserve_newlines"
+            })
+        );
+        assert_eq!(
+            error.to_string(),
+            "Invalid options: `tabstop` has no effect unless `preserve_newlines` is enabled"
+        );
+    }
+
+    #[test]
+    fn output_is_truncated_on_char_boundaries() {
+        let options = MergeOptions::new().quote_char('"').truncate_to(6);
+        assert_eq!(
+            merge_whitespace_with_options("  hello   world  ", &options),
+            "hello "
+        );
+        assert_eq!(
+            merge_whitespace_with_options("a  wonderful", &options),
+            "a wond"
+        );
+        assert_eq!(
+            merge_whitespace_with_options("a  \"b   c\"  d", &options),
+            "a \"b  "
+        );
+        assert_eq!(
+            merge_whitespace_with_options("\"b   cd\"", &options),
+            "\"b   c"
+        );
+        assert_eq!(
+            merge_whitespace_with_options("  short  ", &options),
+            "short"
+        );
+
+        // Multi-byte characters are never split.
+        for (max_len, expected) in [
+            (0, ""),
+            (1, "a"),
+            (2, "a"),
+            (3, "aé"),
+            (4, "aé "),
+            (5, "aé "),
+        ] {
+            let options = options.clone().truncate_to(max_len);
+            let output = merge_whitespace_with_options("aé ü", &options);
+            assert_eq!(output, expected);
+            assert!(matches!(output, Cow::Borrowed(_)));
+        }
+        let options = options.truncate_to(4);
+        assert_eq!(merge_whitespace_with_options("\"ü€\"", &options), "\"ü");
+
+        let options = options.ellipsis("…");
+        assert_eq!(merge_whitespace_with_options("ab   cd", &options), "ab c…");
+        assert_eq!(merge_whitespace_with_options("a€€", &options), "a€…");
+        assert!(matches!(
+            merge_whitespace_with_options("ab  c", &options),
+            Cow::Owned(output) if output == "ab c"
+        ));
+        assert_eq!(
+            MergeOptions::new().ellipsis("…").validate(),
+            Err(OptionError::Requires {
+                option: "ellipsis",
+                requires: "truncate_to"
+            })
+        );
+    }
+
+    #[test]
+    fn every_entry_point_truncates() {
+        let options = MergeOptions::new().truncate_to(3).ellipsis("…");
+        let input = "abcdef   g";
+        assert_eq!(merge_whitespace_with_options(input, &options), "abc…");
+        assert_eq!(Merger::new(options.clone()).merge(input), "abc…");
+        assert_eq!(CompiledMerger::new(options.clone()).merge(input), "abc…");
+        assert_eq!(merge_whitespace_segments(input, &options).concat(), "abc…");
+        assert_eq!(merge_whitespace_with_stats(input, &options).0, "abc…");
+        let mut string = String::from(input);
+        merge_whitespace_in_place(&mut string, &options);
+        assert_eq!(string, "abc…");
+    }
+
+    #[test]
+    fn heredoc_body_is_preserved() {
+        let options = MergeOptions::new().quote_char('"').heredoc(true);
+        let input =
+            "  cat   <<EOF   |  sort\n    b    2\n\n  a\t\t1  \nEOF\n\n  echo   \"<<X\"   done  ";
+        assert_eq!(
+            merge_whitespace_with_options(input, &options),
+            "cat <<EOF   |  sort\n    b    2\n\n  a\t\t1  \nEOF\n echo \"<<X\" done"
+        );
+        assert_eq!(
+            merge_whitespace_with_options("cat <<EOF\nbody   text\nEOF\necho   hi", &options),
+            "cat <<EOF\nbody   text\nEOF\necho hi"
+        );
+
+        // The identifier must make up the whole closing line.
+        let input = "a  <<END\n x   END \n  END\n  ENDING \nEND\n  b  c";
+        assert_eq!(
+            merge_whitespace_with_options(input, &options),
+            "a <<END\n x   END \n  END\n  ENDING \nEND\n b c"
+        );
+
+        // Markers ending in `-` or `~` allow an indented closing line.
+        let options = options.heredoc_marker("<<-");
+        assert_eq!(
+            merge_whitespace_with_options("a  <<-END\n  b   c\n\tEND\n  d  e", &options),
+            "a <<-END\n  b   c\n\tEND\n d e"
+        );
+        let options = options.heredoc_marker("<<");
+
+        // Unterminated regions extend to the end of the input, and lone markers are plain text.
+        assert_eq!(
+            merge_whitespace_with_options("a  <<EOF\n  b   c", &options),
+            "a <<EOF\n  b   c"
+        );
+        assert_eq!(
+            merge_whitespace_with_options("a  << EOF\n  b   c  <<EOF", &options),
+            "a << EOF b c <<EOF"
+        );
+
+        // Regions are only detected if enabled.
+        let options = options.heredoc(false);
+        assert_eq!(
+            merge_whitespace_with_options("a  <<EOF\n  b   c\nEOF", &options),
+            "a <<EOF b c EOF"
+        );
+    }
+
+    #[test]
+    fn sentence_spacing_doubles_runs_after_punctuation() {
+        let options = MergeOptions::new().quote_char('"');
+        let input = "  Hi.   There!  How\n are  you?\tFine.  \"A.   b\"   c...  d  ";
+        assert_eq!(
+            merge_whitespace_with_options(input, &options),
+            "Hi. There! How are you? Fine. \"A.   b\" c... d"
+        );
+
+        let options = options.sentence_spacing(SentenceSpacing::Double);
+        assert_eq!(
+            merge_whitespace_with_options("Hi.   There", &options),
+            "Hi.  There"
+        );
+        assert_eq!(
+            merge_whitespace_with_options(input, &options),
+            "Hi.  There!  How are you?  Fine.  \"A.   b\" c...  d"
+        );
+
+        // Single spaces after punctuation are widened as well, while other runs are not.
+        assert_eq!(
+            merge_whitespace_with_options("a. b , c", &options),
+            "a.  b , c"
+        );
+    }
+
+    #[test]
+    fn edge_collapse_controls_runs_next_to_quotes() {
+        let options = MergeOptions::new().quote_char('"').escape_char('\\');
+        let input = "  foo   \"bar  \\\" baz\"   qux  \\\"  quux\"\"x\"";
+        assert_eq!(
+            merge_whitespace_with_options(input, &options),
+            "foo \"bar  \\\" baz\" qux \\\" quux\"\"x\""
+        );
+
+        // Escaped quote characters outside of quoted regions are plain text.
+        let options = options.edge_collapse(EdgeCollapse::DropAtQuotes);
+        assert_eq!(
+            merge_whitespace_with_options("foo   \"bar\"", &options),
+            "foo\"bar\""
+        );
+        assert_eq!(
+            merge_whitespace_with_options(input, &options),
+            "foo\"bar  \\\" baz\"qux \\\" quux\"\"x\""
+        );
+
+        // Closing quotes of distinct pairs and doubled quotes are detected as well.
+        let options = MergeOptions::new()
+            .quote_char('\'')
+            .typographic_quotes(true)
+            .doubled_quote_escapes(true)
+            .edge_collapse(EdgeCollapse::DropAtQuotes);
+        assert_eq!(
+            merge_whitespace_with_options("a  «b  «c»»  d  'e''f'  g", &options),
+            "a«b  «c»»d'e''f'g"
+        );
+    }
+
+    #[test]
+    fn ansi_sequences_are_preserved() {
+        let options = MergeOptions::new().quote_char('"').preserve_ansi(true);
+        let input =
+            "  \x1b[1;31m  error \x1b[0m:   \x1b[4m\"a   b\"\x1b[24m   \x1b[38;5;208m  done  ";
+        assert_eq!(
+            merge_whitespace_with_options(input, &options),
+            "\x1b[1;31m error \x1b[0m: \x1b[4m\"a   b\"\x1b[24m \x1b[38;5;208m done"
+        );
+
+        // Incomplete sequences are plain text.
+        assert_eq!(
+            merge_whitespace_with_options("a  \x1b[31\n  b  \x1b", &options),
+            "a \x1b[31 b \x1b"
+        );
+
+        // Sequences do not affect the quoting state.
+        let options = options.escape_char('\\').comment_char('#');
+        assert_eq!(
+            merge_whitespace_with_options("\"a \x1b[0m  b\"  c  # d", &options),
+            "\"a \x1b[0m  b\" c"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "unicode")]
+    fn ansi_sequences_are_not_transformed() {
+        let options = MergeOptions::new()
+            .quote_char('"')
+            .strip_format_chars(true)
+            .case_unquoted(Case::Upper)
+            .case_quoted(Case::Upper);
+        let input = "\x1b[1m  bold\x1b[0m  \"\x1b[3mit\x1b[0m\"";
+        assert_eq!(
+            merge_whitespace_with_options(input, &options),
+            "[1M BOLD[0M \"\x1b[3MIT\x1b[0M\""
+        );
+
+        let options = options.preserve_ansi(true);
+        assert_eq!(
+            merge_whitespace_with_options(input, &options),
+            "\x1b[1m BOLD\x1b[0m \"\x1b[3mIT\x1b[0m\""
+        );
+    }
+
+    #[test]
+    fn between_words_only_preserves_runs_next_to_punctuation() {
+        let options = MergeOptions::new().between_words_only(true);
+        assert_eq!(merge_whitespace_with_options("a   b", &options), "a b");
+        assert_eq!(merge_whitespace_with_options("a   ,", &options), "a   ,");
+        assert_eq!(
+            merge_whitespace_with_options("  Hi \t ,  you\n\n(there)   ok  ", &options),
+            "Hi \t ,  you\n\n(there)   ok"
+        );
+        assert!(matches!(
+            merge_whitespace_with_options("a  - b", &options),
+            Cow::Borrowed("a  - b")
+        ));
+        assert_eq!(
+            options.minimal_spacing(true).validate(),
+            Err(OptionError::Conflicting {
+                option: "between_words_only",
+                with: "minimal_spacing",
+            })
+        );
+    }
+
+    #[test]
+    fn trim_between_empties_whitespace_only_fields() {
+        let options = MergeOptions::new().trim_between(',');
+        assert_eq!(merge_whitespace_with_options("a, , ,b", &options), "a,,,b");
+        assert_eq!(
+            merge_whitespace_with_options("a, x ,b", &options),
+            "a, x ,b"
+        );
+        assert_eq!(
+            merge_whitespace_with_options(" ,\t\t, x  y ,  ,z", &options),
+            ",, x y ,,z"
+        );
+
+        let options = options.quote_char('"');
+        assert_eq!(
+            merge_whitespace_with_options(r#"a,"  ,  ",  ,b"#, &options),
+            r#"a,"  ,  ",,b"#
+        );
+    }
+
+    #[test]
+    fn word_char_controls_minimal_spacing() {
+        let options = MergeOptions::new().minimal_spacing(true);
+        assert_eq!(merge_whitespace_with_options("$a   $b", &options), "$a$b");
+        assert_eq!(
+            merge_whitespace_with_options("a_1   b . c", &options),
+            "a_1 b.c"
+        );
+
+        let options = options.word_char(&|c| c.is_alphanumeric() || matches!(c, '_' | '$'));
+        assert_eq!(merge_whitespace_with_options("$a   $b", &options), "$a $b");
+        assert_eq!(
+            merge_whitespace_with_options("a_1   b . c", &options),
+            "a_1 b.c"
+        );
+
+        let options = options.word_char(&|c| c.is_alphanumeric() || c == '.');
+        assert_eq!(
+            merge_whitespace_with_options("a_1   b . c", &options),
+            "a_1 b . c"
+        );
+    }
+
+    #[test]
+    fn word_char_may_capture_state() {
+        let extra = String::from("-@");
+        let is_word = |c: char| c.is_alphanumeric() || extra.contains(c);
+        let options = MergeOptions::new()
+            .minimal_spacing(true)
+            .word_char(&is_word);
+        assert_eq!(
+            merge_whitespace_with_options("a-b   @c , d", &options),
+            "a-b @c,d"
+        );
+
+        // The predicate is compared by identity.
+        assert_eq!(options, options.clone());
+        assert_eq!(
+            options,
+            MergeOptions::new()
+                .minimal_spacing(true)
+                .word_char(&is_word)
+        );
+        assert_ne!(options, MergeOptions::new().minimal_spacing(true));
+    }
+
+    #[test]
+    fn tight_chars_remove_adjacent_whitespace() {
+        let options = MergeOptions::new().quote_char('"').tight_before(&[',']);
+        assert_eq!(
+            merge_whitespace_with_options("a , b  ,c ,  \"d ,  e\" ,", &options),
+            "a, b,c, \"d ,  e\","
+        );
+
+        let options = options.tight_before(&[',', ')']).tight_after(&['(']);
+        assert_eq!(
+            merge_whitespace_with_options("f (  a ,  ( b )  , \"( c )\"  )  g", &options),
+            "f (a, (b), \"( c )\") g"
+        );
+
+        // Quoted parentheses do not tighten the surrounding whitespace.
+        assert_eq!(
+            merge_whitespace_with_options("\"(\"  a  \")\"", &options),
+            "\"(\" a \")\""
+        );
+    }
+
+    #[test]
+    fn only_inside_merges_within_regions() {
+        let options = MergeOptions::new().only_inside('{', '}');
+        let input =
+            "  server  {\n  listen   80;\n  location  /  {  root   /www;  }\n}\n\nport   =  8080 ";
+        assert_eq!(
+            merge_whitespace_with_options(input, &options),
+            "  server  { listen 80; location / { root /www; } }\n\nport   =  8080 "
+        );
+
+        // Unclosed regions extend to the end of the input.
+        assert_eq!(
+            merge_whitespace_with_options("a   {  b   {  c  }  d  ", &options),
+            "a   { b { c } d "
+        );
+
+        // Closing characters in quoted text do not end the region.
+        let options = options.quote_char('"');
+        assert_eq!(
+            merge_whitespace_with_options("\"  \"  {  \"}  \"  }  \"  \"", &options),
+            "\"  \"  { \"}  \" }  \"  \""
+        );
+
+        // Identical delimiters would never close the region.
+        assert_eq!(
+            MergeOptions::new().only_inside('|', '|').validate(),
+            Err(OptionError::SameDelimiters {
+                option: "only_inside"
+            })
+        );
+        assert_eq!(
+            try_merge_whitespace_with_options("a   |  b   |  c  ", &options.only_inside('|', '|')),
+            Err(MergeError::InvalidOptions(OptionError::SameDelimiters {
+                option: "only_inside"
+            }))
+        );
+    }
+
+    #[test]
+    fn empty_and_single_char_inputs_are_borrowed() {
+        // Empty input and a single whitespace character result in an empty string, while any
+        // other single character is kept, with or without a quote character.
+        for (input, expected) in [
+            ("", ""),
+            (" ", ""),
+            ("\n", ""),
+            ("\u{00A0}", ""),
+            ("a", "a"),
+            ("\"", "\""),
+            ("\\", "\\"),
+        ] {
+            assert!(matches!(merge_whitespace(input), Cow::Borrowed(output) if output == expected));
+            for quote in [None, QUOTE] {
+                let output = merge_whitespace_with_quotes(input, quote, ESCAPE);
+                assert!(matches!(output, Cow::Borrowed(output) if output == expected));
+            }
+        }
+
+        // A lone quote character opens an unclosed region, which is kept as-is.
+        let (output, balanced) = merge_whitespace_validated("\"", QUOTE, None);
+        assert_eq!(output, "\"");
+        assert!(!balanced);
+    }
+
+    #[test]
+    fn interior_runs_require_an_owned_copy() {
+        for input in ["a  b", "a\tb", "a\nb", "\"x\"  y", "a b  c"] {
+            assert!(matches!(
+                merge_whitespace_with_quotes(input, QUOTE, None),
+                Cow::Owned(_)
+            ));
+            let padded = format!("  {input}  ");
+            assert!(matches!(
+                merge_whitespace_with_quotes(&padded, QUOTE, None),
+                Cow::Owned(_)
+            ));
+        }
+        for input in ["a b", "  a b  ", "\n\ta \"b  c\" d\t\n", "\"  \""] {
+            assert!(matches!(
+                merge_whitespace_with_quotes(input, QUOTE, None),
+                Cow::Borrowed(output) if output == input.trim()
+            ));
+        }
+    }
 }
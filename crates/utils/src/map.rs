@@ -0,0 +1,112 @@
+use std::ops::Range;
+
+use crate::engine::{merge_into, Sink};
+use crate::MergeOptions;
+
+/// Remove multiple consecutive whitespaces from a given string like
+/// [`merge_whitespace_with_quotes`](crate::merge_whitespace_with_quotes), while also recording
+/// where each byte of the output originates from, e.g. to translate error positions in the
+/// merged text back to the source.
+///
+/// Bytes of kept text map to their offset in the input, while the bytes of a merged whitespace
+/// run map to the start of the run in the input.
+///
+/// ## Example
+///
+/// ```
+/// # use merge_whitespace_utils::merge_whitespace_with_map;
+/// let (output, map) = merge_whitespace_with_map("  a   b", None, None);
+/// assert_eq!(output, "a b");
+/// assert_eq!(map, [2, 3, 6]);
+/// ```
+///
+/// # Return
+///
+/// The modified string and, for each of its bytes, the byte offset in the input.
+pub fn merge_whitespace_with_map(
+    input: &str,
+    quote_char: Option<char>,
+    escape_char: Option<char>,
+) -> (String, Vec<usize>) {
+    let options = MergeOptions {
+        quote_char,
+        escape_char,
+        ..MergeOptions::new()
+    };
+    let mut sink = MapSink {
+        output: String::with_capacity(input.len()),
+        map: Vec::with_capacity(input.len()),
+    };
+    merge_into(input, &options, &mut sink);
+    (sink.output, sink.map)
+}
+
+/// A [`Sink`] collecting the output along with the input offset of each byte.
+struct MapSink {
+    output: String,
+    map: Vec<usize>,
+}
+
+impl Sink for MapSink {
+    fn keep(&mut self, offset: usize, text: &str) {
+        self.output.push_str(text);
+        self.map.extend(offset..offset + text.len());
+    }
+
+    fn replace(&mut self, range: Range<usize>, with: &str) {
+        self.output.push_str(with);
+        self.map
+            .extend(std::iter::repeat_n(range.start, with.len()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::merge_whitespace_with_quotes;
+
+    /// Verifies that the map is monotonic and that each kept byte maps to the same input byte.
+    fn assert_valid_map(input: &str, output: &str, map: &[usize]) {
+        assert_eq!(output.len(), map.len());
+        assert!(map.windows(2).all(|pair| pair[0] < pair[1]), "{map:?}");
+        for (&byte, &offset) in output.as_bytes().iter().zip(map) {
+            let original = input.as_bytes()[offset];
+            assert!(
+                byte == original || original.is_ascii_whitespace(),
+                "{byte} maps to {original} at {offset}"
+            );
+        }
+    }
+
+    #[test]
+    fn map_around_collapsed_runs() {
+        let input = "  a \t\n bc   d  ";
+        let (output, map) = merge_whitespace_with_map(input, None, None);
+        assert_eq!(output, "a bc d");
+        assert_eq!(map, [2, 3, 7, 8, 9, 12]);
+        assert_valid_map(input, &output, &map);
+    }
+
+    #[test]
+    fn map_around_quoted_regions() {
+        let input = r#"SELECT   "a  \"b\""   FROM  ü"#;
+        let (output, map) = merge_whitespace_with_map(input, Some('"'), Some('\\'));
+        assert_eq!(
+            output,
+            merge_whitespace_with_quotes(input, Some('"'), Some('\\'))
+        );
+        assert_eq!(output, r#"SELECT "a  \"b\"" FROM ü"#);
+        assert_eq!(map[..7], [0, 1, 2, 3, 4, 5, 6]);
+        assert_eq!(map[7..17], (9..19).collect::<Vec<_>>());
+        assert_eq!(map[17..], [19, 22, 23, 24, 25, 26, 28, 29]);
+        assert_valid_map(input, &output, &map);
+    }
+
+    #[test]
+    fn map_of_empty_output() {
+        assert_eq!(
+            merge_whitespace_with_map("   ", None, None),
+            (String::new(), vec![])
+        );
+    }
+}
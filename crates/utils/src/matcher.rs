@@ -0,0 +1,112 @@
+use std::borrow::Cow;
+
+use crate::engine::{merge_trimmed, CowSink, Engine, QuoteMatcher};
+use crate::MergeOptions;
+
+/// Remove multiple consecutive whitespaces from a given string and replace them with a single
+/// space, like [`merge_whitespace_with_quotes`](crate::merge_whitespace_with_quotes), while
+/// detecting quoted regions with custom functions.
+///
+/// A character for which `is_open` returns `true` starts a quoted region, which is ended by a
+/// character `c` for which `close_matches(open, c)` returns `true`, where `open` is the character
+/// that started the region. This supports asymmetric pairs such as brackets or typographic
+/// quotes. Within a region, further occurrences of `open` are nested, so the region only ends
+/// at the matching closing character.
+///
+/// ## Example
+///
+/// ```
+/// # use merge_whitespace_utils::merge_whitespace_with_matcher;
+/// let output = merge_whitespace_with_matcher(
+///     "f(a,   (b,   c))   “x   y”",
+///     |c| matches!(c, '(' | '“'),
+///     |open, close| matches!((open, close), ('(', ')') | ('“', '”')),
+///     None,
+/// );
+/// assert_eq!(output, "f(a,   (b,   c)) “x   y”");
+/// ```
+///
+/// # Return
+///
+/// The modified string.
+pub fn merge_whitespace_with_matcher(
+    input: &str,
+    is_open: impl Fn(char) -> bool,
+    close_matches: impl Fn(char, char) -> bool,
+    escape_char: Option<char>,
+) -> Cow<'_, str> {
+    let options = MergeOptions {
+        escape_char,
+        ..MergeOptions::new()
+    };
+    let matcher = QuoteMatcher {
+        is_open: &is_open,
+        close_matches: &close_matches,
+    };
+    let mut engine = Engine::new(&options).with_matcher(matcher);
+    let mut sink = CowSink::new(input);
+    merge_trimmed(&mut engine, input, options.trim, &mut sink);
+    engine.finish(&mut sink);
+    sink.into_cow()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::merge_whitespace_with_quotes;
+
+    fn is_open(c: char) -> bool {
+        matches!(c, '(' | '“' | '«')
+    }
+
+    fn close_matches(open: char, close: char) -> bool {
+        matches!((open, close), ('(', ')') | ('“', '”') | ('«', '»'))
+    }
+
+    #[test]
+    fn parentheses_are_nested() {
+        assert_eq!(
+            merge_whitespace_with_matcher(
+                "a   (b   (c   d)   e)   f   )   g",
+                is_open,
+                close_matches,
+                None
+            ),
+            "a (b   (c   d)   e) f ) g"
+        );
+        assert_eq!(
+            merge_whitespace_with_matcher("a   (b   c", is_open, close_matches, None),
+            "a (b   c"
+        );
+    }
+
+    #[test]
+    fn curly_quotes_are_paired() {
+        assert_eq!(
+            merge_whitespace_with_matcher(
+                "say   “a   ”   «b   ”   »   ”",
+                is_open,
+                close_matches,
+                None
+            ),
+            "say “a   ” «b   ”   » ”"
+        );
+    }
+
+    #[test]
+    fn escaped_closing_characters_are_ignored() {
+        assert_eq!(
+            merge_whitespace_with_matcher(r"(a   \)   b)   c", is_open, close_matches, Some('\\')),
+            r"(a   \)   b) c"
+        );
+    }
+
+    #[test]
+    fn symmetric_matcher_matches_quote_char() {
+        let input = r#"a   "b   \"   c"   d"#;
+        assert_eq!(
+            merge_whitespace_with_matcher(input, |c| c == '"', |open, c| open == c, Some('\\')),
+            merge_whitespace_with_quotes(input, Some('"'), Some('\\'))
+        );
+    }
+}
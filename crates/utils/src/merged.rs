@@ -0,0 +1,130 @@
+use std::borrow::Cow;
+use std::fmt;
+use std::ops::Deref;
+
+/// A merged string, borrowed from the input if possible, that can be used like a `&str`.
+///
+/// This is a thin wrapper around the [`Cow`] returned by the merging functions, e.g. to store or
+/// compare the output without spelling out `.as_ref()` at each use.
+///
+/// ## Example
+///
+/// ```
+/// # use merge_whitespace_utils::{merge_whitespace, MergedStr};
+/// let merged = MergedStr::from(merge_whitespace("  Hello     World  "));
+/// assert_eq!(merged, "Hello World");
+/// assert_eq!(merged.len(), 11);
+/// assert_eq!(format!("<{merged}>"), "<Hello World>");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[repr(transparent)]
+pub struct MergedStr<'a>(Cow<'a, str>);
+
+impl<'a> MergedStr<'a> {
+    /// Gets the merged string.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Unwraps the merged string.
+    pub fn into_inner(self) -> Cow<'a, str> {
+        self.0
+    }
+
+    /// Converts the merged string into an owned `String`, cloning it if it is borrowed.
+    pub fn into_owned(self) -> String {
+        self.0.into_owned()
+    }
+}
+
+impl<'a> From<Cow<'a, str>> for MergedStr<'a> {
+    fn from(merged: Cow<'a, str>) -> Self {
+        Self(merged)
+    }
+}
+
+impl<'a> From<MergedStr<'a>> for Cow<'a, str> {
+    fn from(merged: MergedStr<'a>) -> Self {
+        merged.0
+    }
+}
+
+impl Deref for MergedStr<'_> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for MergedStr<'_> {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl PartialEq<str> for MergedStr<'_> {
+    fn eq(&self, other: &str) -> bool {
+        *self.0 == *other
+    }
+}
+
+impl PartialEq<&str> for MergedStr<'_> {
+    fn eq(&self, other: &&str) -> bool {
+        *self.0 == **other
+    }
+}
+
+impl PartialEq<String> for MergedStr<'_> {
+    fn eq(&self, other: &String) -> bool {
+        *self.0 == **other
+    }
+}
+
+impl fmt::Display for MergedStr<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&*self.0, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::merge_whitespace_with_quotes;
+
+    fn merged(input: &str) -> MergedStr<'_> {
+        merge_whitespace_with_quotes(input, Some('"'), None).into()
+    }
+
+    #[test]
+    fn compares_like_a_str() {
+        let output = merged("  a   \"b   c\"  ");
+        assert_eq!(output, "a \"b   c\"");
+        assert_eq!(output, *"a \"b   c\"");
+        assert_eq!(output, String::from("a \"b   c\""));
+        assert_eq!(output, merged("a \"b   c\""));
+        assert_ne!(output, "a \"b c\"");
+        assert!(output.starts_with("a "));
+    }
+
+    #[test]
+    fn formats_like_a_str() {
+        let output = merged("  a     b  ");
+        assert_eq!(format!("[{output}]"), "[a b]");
+        assert_eq!(format!("[{output:>5}]"), "[  a b]");
+        assert_eq!(output.to_string(), "a b");
+    }
+
+    #[test]
+    fn keeps_the_borrowed_input() {
+        let input = "  a b  ";
+        let output = merged(input);
+        assert!(matches!(output.clone().into_inner(), Cow::Borrowed("a b")));
+        assert_eq!(output.as_str().as_ptr(), input[2..].as_ptr());
+        assert_eq!(output.into_owned(), "a b");
+        assert_eq!(
+            std::mem::size_of::<MergedStr>(),
+            std::mem::size_of::<Cow<str>>()
+        );
+    }
+}
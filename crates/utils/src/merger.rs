@@ -0,0 +1,254 @@
+use std::borrow::Cow;
+use std::fmt;
+
+use crate::engine::{merge_into, merge_with, CowSink, Engine, QuoteMatcher, RunAction, RunHook};
+use crate::{CollapseDecision, MergeOptions};
+
+/// A reusable whitespace merger that keeps its output buffer between calls.
+///
+/// After warming up, merging inputs no larger than the ones seen before does not allocate,
+/// which makes this type suitable for hot loops processing many strings.
+///
+/// ## Example
+///
+/// ```
+/// # use merge_whitespace_utils::{Merger, MergeOptions};
+/// let mut merger = Merger::new(MergeOptions::new().quote_char('"'));
+/// assert_eq!(merger.merge("Hello     World!"), "Hello World!");
+/// assert_eq!(merger.merge("  \"How   are\"   you?  "), "\"How   are\" you?");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Merger<'o> {
+    options: MergeOptions<'o>,
+    buffer: String,
+}
+
+impl<'o> Merger<'o> {
+    /// Creates a new merger using the specified options.
+    pub fn new(options: MergeOptions<'o>) -> Self {
+        Self {
+            options,
+            buffer: String::new(),
+        }
+    }
+
+    /// Gets the options used by this merger.
+    pub fn options(&self) -> &MergeOptions<'o> {
+        &self.options
+    }
+
+    /// Removes multiple consecutive whitespaces from the input according to the options.
+    ///
+    /// # Return
+    ///
+    /// The modified string, borrowed from the internal buffer until the next call.
+    pub fn merge(&mut self, input: &str) -> &str {
+        self.buffer.clear();
+        merge_into(input, &self.options, &mut self.buffer);
+        &self.buffer
+    }
+}
+
+/// A function deciding how a run of whitespace is handled, see [`CompiledMerger::decide`].
+type DecideFn = dyn Fn(&str) -> CollapseDecision + Send + Sync;
+
+/// A function determining whether a character opens a quoted region.
+type IsOpenFn = dyn Fn(char) -> bool + Send + Sync;
+
+/// A function determining whether a character closes the region opened by another one.
+type CloseMatchesFn = dyn Fn(char, char) -> bool + Send + Sync;
+
+/// An immutable whitespace merger configuration that can be shared between threads.
+///
+/// In addition to the [`MergeOptions`], it can hold a callback deciding how each run is handled,
+/// like [`merge_whitespace_decide`](crate::merge_whitespace_decide), and custom quote matching,
+/// like [`merge_whitespace_with_matcher`](crate::merge_whitespace_with_matcher).
+///
+/// ## Example
+///
+/// ```
+/// # use std::sync::Arc;
+/// # use merge_whitespace_utils::{CollapseDecision, CompiledMerger, MergeOptions};
+/// let merger = Arc::new(
+///     CompiledMerger::new(MergeOptions::new().quote_char('"')).decide(|run| {
+///         match run.contains('\n') {
+///             true => CollapseDecision::Preserve,
+///             false => CollapseDecision::Collapse,
+///         }
+///     }),
+/// );
+/// let worker = std::thread::spawn({
+///     let merger = Arc::clone(&merger);
+///     move || merger.merge("a   b\n  c").into_owned()
+/// });
+/// assert_eq!(worker.join().unwrap(), "a b\n  c");
+/// ```
+pub struct CompiledMerger<'o> {
+    options: MergeOptions<'o>,
+    decide: Option<Box<DecideFn>>,
+    matcher: Option<(Box<IsOpenFn>, Box<CloseMatchesFn>)>,
+}
+
+impl<'o> CompiledMerger<'o> {
+    /// Creates a new merger using the specified options.
+    pub fn new(options: MergeOptions<'o>) -> Self {
+        Self {
+            options,
+            decide: None,
+            matcher: None,
+        }
+    }
+
+    /// Sets the callback deciding how each run of whitespace is handled before the options
+    /// are applied.
+    #[must_use]
+    pub fn decide(
+        mut self,
+        decide: impl Fn(&str) -> CollapseDecision + Send + Sync + 'static,
+    ) -> Self {
+        self.decide = Some(Box::new(decide));
+        self
+    }
+
+    /// Sets the functions detecting quoted regions in place of the quote settings of the options.
+    #[must_use]
+    pub fn quote_matcher(
+        mut self,
+        is_open: impl Fn(char) -> bool + Send + Sync + 'static,
+        close_matches: impl Fn(char, char) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.matcher = Some((Box::new(is_open), Box::new(close_matches)));
+        self
+    }
+
+    /// Gets the options used by this merger.
+    pub fn options(&self) -> &MergeOptions<'o> {
+        &self.options
+    }
+
+    /// Removes multiple consecutive whitespaces from the input according to the configuration.
+    ///
+    /// # Return
+    ///
+    /// The modified string.
+    pub fn merge<'a>(&self, input: &'a str) -> Cow<'a, str> {
+        let mut engine = Engine::new(&self.options);
+        if let Some((is_open, close_matches)) = &self.matcher {
+            engine = engine.with_matcher(QuoteMatcher {
+                is_open: is_open.as_ref(),
+                close_matches: close_matches.as_ref(),
+            });
+        }
+        let mut decide = |run: &str| match &self.decide {
+            Some(decide) => decide(run),
+            None => CollapseDecision::Collapse,
+        };
+        if self.decide.is_some() {
+            engine = engine.with_run_hook(RunHook {
+                input,
+                action: RunAction::Decide(&mut decide),
+            });
+        }
+
+        let mut sink = CowSink::new(input);
+        merge_with(&mut engine, input, &mut sink);
+        sink.into_cow()
+    }
+}
+
+impl fmt::Debug for CompiledMerger<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CompiledMerger")
+            .field("options", &self.options)
+            .field("decide", &self.decide.is_some())
+            .field("matcher", &self.matcher.is_some())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::merge_whitespace_with_options;
+
+    #[test]
+    fn repeated_merges_are_correct() {
+        let options = MergeOptions::new().quote_char('"').escape_char('\\');
+        let mut merger = Merger::new(options.clone());
+        let inputs = [
+            "  foo   bar  ",
+            "",
+            "foo   foobar   \"  bar\n\" baz",
+            "what   \\   if I quote\\ spaces",
+            "a",
+        ];
+        for _ in 0..3 {
+            for input in inputs {
+                assert_eq!(
+                    merger.merge(input),
+                    merge_whitespace_with_options(input, &options)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn buffer_is_reused() {
+        let mut merger = Merger::default();
+        let long = "lorem    ipsum   dolor\n\n sit    amet ".repeat(32);
+        let expected = crate::merge_whitespace(&long);
+
+        assert_eq!(merger.merge(&long), expected);
+        let capacity = merger.buffer.capacity();
+
+        for _ in 0..100 {
+            assert_eq!(merger.merge("a   b"), "a b");
+            assert_eq!(merger.merge(&long), expected);
+            assert_eq!(merger.buffer.capacity(), capacity);
+        }
+    }
+
+    #[test]
+    fn compiled_merger_is_shared_between_threads() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<CompiledMerger<'static>>();
+
+        let options = MergeOptions::new().escape_char('\\');
+        let merger = Arc::new(
+            CompiledMerger::new(options.clone())
+                .quote_matcher(|c| c == '(', |_, c| c == ')')
+                .decide(|run| match run.contains('\t') {
+                    true => CollapseDecision::Remove,
+                    false => CollapseDecision::Collapse,
+                }),
+        );
+
+        let workers: Vec<_> = (0..4)
+            .map(|i| {
+                let merger = Arc::clone(&merger);
+                std::thread::spawn(move || {
+                    let input = format!("  a{i}   (b   c)\t\td\\  e  ");
+                    merger.merge(&input).into_owned()
+                })
+            })
+            .collect();
+        for (i, worker) in workers.into_iter().enumerate() {
+            assert_eq!(worker.join().unwrap(), format!("a{i} (b   c)d\\  e"));
+        }
+    }
+
+    #[test]
+    fn compiled_merger_matches_options() {
+        let options = MergeOptions::new().quote_char('"').replacement("_");
+        let merger = CompiledMerger::new(options.clone());
+        for input in ["", "  a   \"b   c\"  d  ", "a b"] {
+            assert_eq!(
+                merger.merge(input),
+                merge_whitespace_with_options(input, &options)
+            );
+        }
+        assert!(matches!(merger.merge("a_b"), Cow::Borrowed("a_b")));
+    }
+}
@@ -0,0 +1,1537 @@
+use std::ops::Range;
+
+use crate::{CategoryReplacement, OptionError};
+
+/// Options controlling how whitespace is merged by [`merge_whitespace_with_options`](crate::merge_whitespace_with_options).
+///
+/// ## Example
+///
+/// ```
+/// # use merge_whitespace_utils::{merge_whitespace_with_options, MergeOptions};
+/// let options = MergeOptions::new().quote_char('"').replacement("_");
+/// let output = merge_whitespace_with_options("  Hello     \"big   World\"  ", &options);
+/// assert_eq!(output, "Hello_\"big   World\"");
+/// ```
+///
+/// The [`word_char`](Self::word_char) predicates of options are compared by identity, so options
+/// with separately created predicates compare unequal even if the predicates are equivalent.
+#[derive(Clone)]
+#[non_exhaustive]
+pub struct MergeOptions<'a> {
+    /// The optional quote character. Text between a pair of these characters is kept as-is.
+    pub quote_char: Option<char>,
+    /// The optional escape character. The character following it is kept as-is.
+    pub escape_char: Option<char>,
+    /// Whether an escaped whitespace character protects the entire run of whitespace it starts,
+    /// rather than just itself. Defaults to `false`.
+    pub escape_protects_run: bool,
+    /// Whether the escape character only has an effect if it is followed by a quote character,
+    /// another escape character or whitespace. Otherwise, it is plain text and the following
+    /// character is processed as usual. Defaults to `false`.
+    pub escape_only_specials: bool,
+    /// The string each run of whitespace is replaced with. Defaults to a single space.
+    pub replacement: &'a str,
+    /// Controls which ends of the input are trimmed. Defaults to [`Trim::Both`].
+    pub trim: Trim,
+    /// If set, the characters removed from the trimmed ends of the input instead of all
+    /// whitespace. Any remaining whitespace at the ends is merged into the
+    /// [`replacement`](Self::replacement) like any other run. Defaults to `None`.
+    pub trim_chars: Option<&'a [char]>,
+    /// Whether Unicode format (e.g. `U+00AD` soft hyphen, `U+200B` zero width space) and
+    /// control characters are removed outside of quoted text. Whitespace control characters
+    /// such as `\t` or `\n` are merged as usual. Defaults to `false`.
+    #[cfg(feature = "unicode")]
+    pub strip_format_chars: bool,
+    /// Whether POSIX shell quoting rules apply. Defaults to `false`.
+    ///
+    /// In shell mode, both `'` and `"` start quoted regions (in addition to the
+    /// [`quote_char`](Self::quote_char)) and the escape character defaults to `\`.
+    /// Like in a shell, single-quoted regions do not honor the escape character, while
+    /// double-quoted regions and unquoted text do.
+    pub shell_mode: bool,
+    /// If set, each run of whitespace keeps up to this many of its original characters instead
+    /// of being replaced with the [`replacement`](Self::replacement). Defaults to `None`.
+    pub max_spaces: Option<usize>,
+    /// If set, each run of whitespace is replaced with as many copies of the
+    /// [`replacement`](Self::replacement) as it has characters, but at most this many, rather
+    /// than with exactly one. A limit of zero removes the runs entirely. This does not apply if
+    /// [`max_spaces`](Self::max_spaces) is set. Defaults to `None`.
+    pub clamp_run: Option<usize>,
+    /// If set, each run of whitespace is replaced with exactly this many copies of the
+    /// [`replacement`](Self::replacement), regardless of its width, e.g. to align output.
+    /// A count of zero removes the runs entirely. This does not apply if
+    /// [`max_spaces`](Self::max_spaces) is set. Defaults to `None`.
+    pub exact_run: Option<usize>,
+    /// Whether non-ASCII space characters such as `U+00A0` (no-break space) or `U+2003`
+    /// (em space) are rewritten to an ASCII space wherever they are kept, i.e. in quoted text,
+    /// after an escape character or when preserved by [`max_spaces`](Self::max_spaces).
+    /// Unquoted runs are replaced with the [`replacement`](Self::replacement) regardless.
+    /// Defaults to `false`.
+    pub normalize_unicode_space: bool,
+    /// Whether a doubled quote character inside a quoted region is an escaped quote rather
+    /// than the end of the region, as in SQL or CSV. This can be combined with the
+    /// [`escape_char`](Self::escape_char). Defaults to `false`.
+    pub doubled_quote_escapes: bool,
+    /// Whether a change of the [`WhitespaceCategory`](crate::WhitespaceCategory) ends a run of
+    /// whitespace. Each run then consists of a single category and is replaced with that
+    /// category's [separator](crate::WhitespaceCategory::separator) rather than the
+    /// [`replacement`](Self::replacement). Defaults to `false`.
+    pub split_on_type_change: bool,
+    /// Controls what each run of whitespace is collapsed to. Defaults to [`CollapseTo::Replacement`].
+    pub collapse_to: CollapseTo,
+    /// Whether a run consisting of a single whitespace character keeps that character, e.g. a
+    /// lone tab, rather than being replaced. Longer runs are merged as usual. Defaults to `false`.
+    pub keep_singletons: bool,
+    /// If set, the visible marker each run of whitespace is replaced with, rather than the
+    /// [`replacement`](Self::replacement), unless the run already equals the replacement.
+    /// This is intended for diagnostic output showing where merging happened. Defaults to `None`.
+    pub debug_sentinel: Option<char>,
+    /// Controls the spacing following sentence punctuation. Defaults to [`SentenceSpacing::Single`].
+    pub sentence_spacing: SentenceSpacing,
+    /// Controls whether runs of whitespace next to quoted regions are kept.
+    /// Defaults to [`EdgeCollapse::Keep`].
+    pub edge_collapse: EdgeCollapse,
+    /// If set, each run of whitespace is replaced with the character of the category most of
+    /// its characters belong to, rather than the [`replacement`](Self::replacement). Ties are
+    /// resolved in favor of line breaks, then tabs. Defaults to `None`.
+    pub category_replacement: Option<CategoryReplacement>,
+    /// Whether line breaks are kept. If enabled, every line break outside of quoted text is
+    /// kept as-is, while any other whitespace directly before or after a line break is removed.
+    /// Defaults to `false`.
+    pub preserve_newlines: bool,
+    /// If set along with [`preserve_newlines`](Self::preserve_newlines), runs of whitespace
+    /// containing tabs are expanded to spaces up to the column the tabs would advance to, with
+    /// tab stops at every multiple of this many columns. Columns are counted in characters from
+    /// the start of each output line. Runs without tabs are merged as usual. Defaults to `None`.
+    pub tabstop: Option<usize>,
+    /// The optional record separator, e.g. `\r`. Like line breaks with
+    /// [`preserve_newlines`](Self::preserve_newlines), every separator outside of quoted text is
+    /// kept as-is, even if it is whitespace, while any other whitespace directly before or after
+    /// it is removed. Defaults to `None`.
+    pub split_on: Option<char>,
+    /// Whether the output ends with a single `\n` if the input ends with a line break, e.g. to
+    /// keep files terminated by a newline. Any other trailing whitespace is removed regardless
+    /// of the [`trim`](Self::trim) setting. Defaults to `false`.
+    pub keep_trailing_newline: bool,
+    /// The optional table column separator, e.g. `|`. On each line, the text between two
+    /// occurrences of this character is kept as-is, so that the padding of the columns is
+    /// preserved. Text before the first and after the last separator of a line is merged as usual.
+    /// Separators in quoted text or following an escape character are ignored. Defaults to `None`.
+    pub table_separator: Option<char>,
+    /// The optional line comment character, e.g. `#`. Outside of quoted text, it starts a
+    /// comment extending to the end of the line, which is removed. The whitespace surrounding
+    /// a comment is merged into a single replacement. Defaults to `None`.
+    pub comment_char: Option<char>,
+    /// Whether `http://` and `https://` URLs outside of quoted text are kept verbatim up to the
+    /// next whitespace character, e.g. to keep a `#` fragment from being treated as a
+    /// [comment](Self::comment_char) or the URL from being converted to another case.
+    /// Defaults to `false`.
+    pub protect_urls: bool,
+    /// Whether ANSI escape sequences (CSI sequences such as the SGR sequence `\x1b[1;31m`) are
+    /// kept verbatim, e.g. in colored terminal output. They are then unaffected by other options
+    /// such as case conversion, even in quoted text, while the whitespace surrounding them is
+    /// merged as usual. Defaults to `false`.
+    pub preserve_ansi: bool,
+    /// The optional pair of sentinels delimiting verbatim spans, e.g. `("<<EOF", "EOF")` for
+    /// heredoc-like blocks. Outside of quoted text, everything from the opening sentinel up to
+    /// and including the next closing sentinel is kept as-is. Unlike in quoted regions, quote and
+    /// escape characters have no effect within the span. A span without a closing sentinel
+    /// extends to the end of the input. Defaults to `None`.
+    pub verbatim_span: Option<(&'a str, &'a str)>,
+    /// Further pairs of sentinels delimiting verbatim spans like the
+    /// [`verbatim_span`](Self::verbatim_span), e.g. `[("$(", ")"), ("${", "}")]` for several
+    /// kinds of interpolation syntax. If more than one opening sentinel matches, the longest
+    /// one wins, and of equally long ones, the `verbatim_span` and then the first listed pair.
+    /// Spans do not nest. Defaults to none.
+    pub verbatim_spans: &'a [(&'a str, &'a str)],
+    /// Whether heredoc-style regions are kept as-is. Outside of quoted text, a region starts at
+    /// the [`heredoc_marker`](Self::heredoc_marker) followed by an identifier of alphanumeric
+    /// characters and `_`, e.g. `<<EOF`, and ends with the line break after the next line
+    /// consisting of exactly that identifier. If the marker ends in `-` or `~`, like `<<-` and
+    /// `<<~`, the closing line may be indented. The rest of the line of the marker is part of
+    /// the region. A region without a closing line extends to the end of the input.
+    /// Defaults to `false`.
+    pub heredoc: bool,
+    /// The marker starting a region with [`heredoc`](Self::heredoc) enabled. Defaults to `<<`.
+    pub heredoc_marker: &'a str,
+    /// If set, controls how the whitespace before the first non-whitespace character of the
+    /// input is handled, regardless of the [`trim`](Self::trim) setting. Defaults to `None`.
+    pub leading_newline: Option<LeadingPolicy>,
+    /// Whether the typographic quote pairs `«`/`»` and `“`/`”` delimit quoted regions (in
+    /// addition to the [`quote_char`](Self::quote_char)). Nested pairs of the same kind are
+    /// tracked, so a region only ends at the closing quote matching its opening one.
+    /// Defaults to `false`.
+    pub typographic_quotes: bool,
+    /// Whether the whitespace inside parentheses is kept as-is, e.g. to pretty-print math
+    /// expressions. Like a quoted region, the region only ends at the `)` matching its `(`,
+    /// and quote characters inside it have no special meaning. Defaults to `false`.
+    pub preserve_parens: bool,
+    /// Whether quoted regions containing nothing but whitespace are removed along with their
+    /// quote characters, e.g. to clean up templated output. The whitespace on both sides of a
+    /// removed region is merged as a single run. Defaults to `false`.
+    pub drop_empty_quotes: bool,
+    /// Whether a tab character outside of quoted regions is an error. This is only checked by
+    /// [`try_merge_whitespace_with_options`](crate::try_merge_whitespace_with_options), which
+    /// then returns [`MergeError::TabFound`](crate::MergeError::TabFound). Defaults to `false`.
+    pub forbid_tabs: bool,
+    /// Whether runs of whitespace are removed entirely unless they separate two
+    /// [word characters](Self::word_char), e.g. to minify code. Defaults to `false`.
+    pub minimal_spacing: bool,
+    /// Whether runs of whitespace are only merged if they separate two
+    /// [word characters](Self::word_char), and kept as-is otherwise, e.g. to keep the spacing
+    /// around punctuation in prose. Defaults to `false`.
+    pub between_words_only: bool,
+    /// The characters the whitespace directly before is removed entirely rather than merged,
+    /// e.g. `,` or `)`. Defaults to none.
+    pub tight_before: &'a [char],
+    /// The characters the whitespace directly after is removed entirely rather than merged,
+    /// e.g. `(`. Defaults to none.
+    pub tight_after: &'a [char],
+    /// If set, the field delimiter whitespace between two consecutive occurrences is removed
+    /// entirely, e.g. `,` to empty whitespace-only CSV fields. Defaults to `None`.
+    pub trim_between: Option<char>,
+    /// If set, the pair of characters delimiting the regions whitespace is merged in, e.g.
+    /// `('{', '}')`. The two characters must differ. Nested pairs are tracked, so a region only
+    /// ends at its matching closing character. Any text outside of the regions, including quote and escape characters, is
+    /// kept as-is and the input is not trimmed. Defaults to `None`.
+    pub only_inside: Option<(char, char)>,
+    /// If set, the maximum length of the output in bytes. Longer output is cut off at the last
+    /// character boundary within the limit, followed by the [`ellipsis`](Self::ellipsis), and
+    /// the rest of the input is not processed. Defaults to `None`.
+    pub truncate_to: Option<usize>,
+    /// The string appended to output cut off by [`truncate_to`](Self::truncate_to), e.g. `…`.
+    /// It does not count towards the limit. Defaults to an empty string.
+    pub ellipsis: &'a str,
+    /// Determines whether a character is a word character for
+    /// [`minimal_spacing`](Self::minimal_spacing) and [`between_words_only`](Self::between_words_only).
+    /// Defaults to alphanumeric characters and `_`.
+    pub word_char: &'a (dyn Fn(char) -> bool + Sync),
+    /// If set, the case the text outside of quoted regions is converted to. Defaults to `None`.
+    #[cfg(feature = "unicode")]
+    pub case_unquoted: Option<Case>,
+    /// If set, the case the text inside of quoted regions, including the quote characters,
+    /// is converted to. Defaults to `None`.
+    #[cfg(feature = "unicode")]
+    pub case_quoted: Option<Case>,
+}
+
+impl std::fmt::Debug for MergeOptions<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Self {
+            quote_char,
+            escape_char,
+            escape_protects_run,
+            escape_only_specials,
+            replacement,
+            trim,
+            trim_chars,
+            #[cfg(feature = "unicode")]
+            strip_format_chars,
+            shell_mode,
+            max_spaces,
+            clamp_run,
+            exact_run,
+            normalize_unicode_space,
+            doubled_quote_escapes,
+            split_on_type_change,
+            collapse_to,
+            keep_singletons,
+            debug_sentinel,
+            sentence_spacing,
+            edge_collapse,
+            category_replacement,
+            preserve_newlines,
+            tabstop,
+            split_on,
+            keep_trailing_newline,
+            table_separator,
+            comment_char,
+            protect_urls,
+            preserve_ansi,
+            verbatim_span,
+            verbatim_spans,
+            heredoc,
+            heredoc_marker,
+            leading_newline,
+            typographic_quotes,
+            preserve_parens,
+            drop_empty_quotes,
+            forbid_tabs,
+            minimal_spacing,
+            between_words_only,
+            tight_before,
+            tight_after,
+            trim_between,
+            only_inside,
+            truncate_to,
+            ellipsis,
+            #[cfg(feature = "unicode")]
+            case_unquoted,
+            #[cfg(feature = "unicode")]
+            case_quoted,
+            word_char: _,
+        } = self;
+        let mut debug = f.debug_struct("MergeOptions");
+        debug
+            .field("quote_char", quote_char)
+            .field("escape_char", escape_char)
+            .field("escape_protects_run", escape_protects_run)
+            .field("escape_only_specials", escape_only_specials)
+            .field("replacement", replacement)
+            .field("trim", trim)
+            .field("trim_chars", trim_chars)
+            .field("shell_mode", shell_mode)
+            .field("max_spaces", max_spaces)
+            .field("clamp_run", clamp_run)
+            .field("exact_run", exact_run)
+            .field("normalize_unicode_space", normalize_unicode_space)
+            .field("doubled_quote_escapes", doubled_quote_escapes)
+            .field("split_on_type_change", split_on_type_change)
+            .field("collapse_to", collapse_to)
+            .field("keep_singletons", keep_singletons)
+            .field("debug_sentinel", debug_sentinel)
+            .field("sentence_spacing", sentence_spacing)
+            .field("edge_collapse", edge_collapse)
+            .field("category_replacement", category_replacement)
+            .field("preserve_newlines", preserve_newlines)
+            .field("tabstop", tabstop)
+            .field("split_on", split_on)
+            .field("keep_trailing_newline", keep_trailing_newline)
+            .field("table_separator", table_separator)
+            .field("comment_char", comment_char)
+            .field("protect_urls", protect_urls)
+            .field("preserve_ansi", preserve_ansi)
+            .field("verbatim_span", verbatim_span)
+            .field("verbatim_spans", verbatim_spans)
+            .field("heredoc", heredoc)
+            .field("heredoc_marker", heredoc_marker)
+            .field("leading_newline", leading_newline)
+            .field("typographic_quotes", typographic_quotes)
+            .field("preserve_parens", preserve_parens)
+            .field("drop_empty_quotes", drop_empty_quotes)
+            .field("forbid_tabs", forbid_tabs)
+            .field("minimal_spacing", minimal_spacing)
+            .field("between_words_only", between_words_only)
+            .field("tight_before", tight_before)
+            .field("tight_after", tight_after)
+            .field("trim_between", trim_between)
+            .field("only_inside", only_inside)
+            .field("truncate_to", truncate_to)
+            .field("ellipsis", ellipsis)
+            .field("word_char", &format_args!("<predicate>"));
+        #[cfg(feature = "unicode")]
+        debug.field("strip_format_chars", strip_format_chars);
+        #[cfg(feature = "unicode")]
+        debug.field("case_unquoted", case_unquoted);
+        #[cfg(feature = "unicode")]
+        debug.field("case_quoted", case_quoted);
+        debug.finish()
+    }
+}
+
+impl PartialEq for MergeOptions<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        // Destructuring makes sure that new fields are not forgotten.
+        let Self {
+            quote_char,
+            escape_char,
+            escape_protects_run,
+            escape_only_specials,
+            replacement,
+            trim,
+            trim_chars,
+            #[cfg(feature = "unicode")]
+            strip_format_chars,
+            shell_mode,
+            max_spaces,
+            clamp_run,
+            exact_run,
+            normalize_unicode_space,
+            doubled_quote_escapes,
+            split_on_type_change,
+            collapse_to,
+            keep_singletons,
+            debug_sentinel,
+            sentence_spacing,
+            edge_collapse,
+            category_replacement,
+            preserve_newlines,
+            tabstop,
+            split_on,
+            keep_trailing_newline,
+            table_separator,
+            comment_char,
+            protect_urls,
+            preserve_ansi,
+            verbatim_span,
+            verbatim_spans,
+            heredoc,
+            heredoc_marker,
+            leading_newline,
+            typographic_quotes,
+            preserve_parens,
+            drop_empty_quotes,
+            forbid_tabs,
+            minimal_spacing,
+            between_words_only,
+            tight_before,
+            tight_after,
+            trim_between,
+            only_inside,
+            truncate_to,
+            ellipsis,
+            #[cfg(feature = "unicode")]
+            case_unquoted,
+            #[cfg(feature = "unicode")]
+            case_quoted,
+            word_char,
+        } = self;
+        #[cfg(feature = "unicode")]
+        if strip_format_chars != &other.strip_format_chars {
+            return false;
+        }
+        #[cfg(feature = "unicode")]
+        if case_unquoted != &other.case_unquoted {
+            return false;
+        }
+        #[cfg(feature = "unicode")]
+        if case_quoted != &other.case_quoted {
+            return false;
+        }
+        quote_char == &other.quote_char
+            && escape_char == &other.escape_char
+            && escape_protects_run == &other.escape_protects_run
+            && escape_only_specials == &other.escape_only_specials
+            && replacement == &other.replacement
+            && trim == &other.trim
+            && trim_chars == &other.trim_chars
+            && shell_mode == &other.shell_mode
+            && max_spaces == &other.max_spaces
+            && clamp_run == &other.clamp_run
+            && exact_run == &other.exact_run
+            && normalize_unicode_space == &other.normalize_unicode_space
+            && doubled_quote_escapes == &other.doubled_quote_escapes
+            && split_on_type_change == &other.split_on_type_change
+            && collapse_to == &other.collapse_to
+            && keep_singletons == &other.keep_singletons
+            && debug_sentinel == &other.debug_sentinel
+            && sentence_spacing == &other.sentence_spacing
+            && edge_collapse == &other.edge_collapse
+            && category_replacement == &other.category_replacement
+            && preserve_newlines == &other.preserve_newlines
+            && tabstop == &other.tabstop
+            && split_on == &other.split_on
+            && keep_trailing_newline == &other.keep_trailing_newline
+            && table_separator == &other.table_separator
+            && comment_char == &other.comment_char
+            && protect_urls == &other.protect_urls
+            && preserve_ansi == &other.preserve_ansi
+            && verbatim_span == &other.verbatim_span
+            && verbatim_spans == &other.verbatim_spans
+            && heredoc == &other.heredoc
+            && heredoc_marker == &other.heredoc_marker
+            && leading_newline == &other.leading_newline
+            && typographic_quotes == &other.typographic_quotes
+            && preserve_parens == &other.preserve_parens
+            && drop_empty_quotes == &other.drop_empty_quotes
+            && forbid_tabs == &other.forbid_tabs
+            && minimal_spacing == &other.minimal_spacing
+            && between_words_only == &other.between_words_only
+            && tight_before == &other.tight_before
+            && tight_after == &other.tight_after
+            && trim_between == &other.trim_between
+            && only_inside == &other.only_inside
+            && truncate_to == &other.truncate_to
+            && ellipsis == &other.ellipsis
+            && std::ptr::eq(*word_char, other.word_char)
+    }
+}
+
+impl Eq for MergeOptions<'_> {}
+
+/// Specifies what a run of whitespace is collapsed to.
+///
+/// This does not apply if [`MergeOptions::max_spaces`], [`MergeOptions::clamp_run`] or
+/// [`MergeOptions::exact_run`] is set.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum CollapseTo {
+    /// Collapse the run to the [`MergeOptions::replacement`], or to the category separator if
+    /// [`MergeOptions::split_on_type_change`] is enabled.
+    #[default]
+    Replacement,
+    /// Collapse the run to its first whitespace character.
+    First,
+    /// Collapse the run to its last whitespace character, e.g. to keep the line break
+    /// separating the next block.
+    Last,
+}
+
+/// Specifies how a run of whitespace following sentence punctuation (`.`, `!` or `?`)
+/// is collapsed, e.g. for prose.
+///
+/// This only applies if the run is collapsed to the [`MergeOptions::replacement`].
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum SentenceSpacing {
+    /// Collapse the run to a single replacement, like any other run.
+    #[default]
+    Single,
+    /// Collapse the run to two replacements.
+    Double,
+}
+
+/// Specifies how a run of whitespace directly before or after a quoted region is handled.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum EdgeCollapse {
+    /// Collapse the run like any other run.
+    #[default]
+    Keep,
+    /// Remove the run entirely, so that the quoted region is joined to its neighbors.
+    DropAtQuotes,
+}
+
+/// Specifies a letter case text is converted to, using the full Unicode case mapping
+/// (e.g. `ß` is uppercased to `SS`).
+#[cfg(feature = "unicode")]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Case {
+    /// Convert the text to uppercase.
+    Upper,
+    /// Convert the text to lowercase.
+    Lower,
+    /// Keep the case of the text unchanged.
+    None,
+}
+
+/// Specifies how the leading whitespace of the input is handled.
+///
+/// See [`MergeOptions::leading_newline`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum LeadingPolicy {
+    /// Remove the leading whitespace entirely.
+    Strip,
+    /// Replace the leading whitespace with a single `\n` if it contains a line break, and remove
+    /// it otherwise.
+    KeepOne,
+}
+
+/// Specifies which ends of the input have their leading or trailing whitespace removed.
+///
+/// Whitespace at a trimmed end is removed entirely; whitespace at an end that is not trimmed
+/// is merged into the replacement like any other run. This also holds for whitespace before a
+/// quoted region at the start of the input: it is removed if the start is trimmed and merged
+/// otherwise, while the quoted region itself is kept as-is in either case.
+///
+/// ## Example
+///
+/// ```
+/// # use merge_whitespace_utils::{merge_whitespace_with_options, MergeOptions, Trim};
+/// let input = "   \"a  b\"";
+/// let options = MergeOptions::new().quote_char('"').trim(Trim::Both);
+/// assert_eq!(merge_whitespace_with_options(input, &options), "\"a  b\"");
+/// let options = options.trim(Trim::None);
+/// assert_eq!(merge_whitespace_with_options(input, &options), " \"a  b\"");
+/// ```
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Trim {
+    /// Trim both the start and the end of the input.
+    #[default]
+    Both,
+    /// Trim only the start of the input.
+    Start,
+    /// Trim only the end of the input.
+    End,
+    /// Do not trim the input.
+    None,
+}
+
+impl Trim {
+    /// Determines the byte range of the input that remains after trimming the `trim_chars`,
+    /// or all whitespace if they are not set.
+    pub(crate) fn bounds(self, input: &str, trim_chars: Option<&[char]>) -> Range<usize> {
+        let trim_start = |input: &str| match trim_chars {
+            Some(trim_chars) => input.trim_start_matches(trim_chars).len(),
+            None => input.trim_start().len(),
+        };
+        let trim_end = |input: &str| match trim_chars {
+            Some(trim_chars) => input.trim_end_matches(trim_chars).len(),
+            None => input.trim_end().len(),
+        };
+        let start = match self {
+            Trim::Both | Trim::Start => input.len() - trim_start(input),
+            Trim::End | Trim::None => 0,
+        };
+        let end = match self {
+            Trim::Both | Trim::End => trim_end(input),
+            Trim::Start | Trim::None => input.len(),
+        };
+        start..end.max(start)
+    }
+}
+
+/// The default [`MergeOptions::word_char`]: alphanumeric characters and `_`.
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+impl<'a> MergeOptions<'a> {
+    /// Creates the default options: no quote or escape character, whitespace runs are replaced
+    /// with a single space and both ends of the input are trimmed.
+    pub const fn new() -> Self {
+        Self {
+            quote_char: None,
+            escape_char: None,
+            escape_protects_run: false,
+            escape_only_specials: false,
+            replacement: " ",
+            trim: Trim::Both,
+            trim_chars: None,
+            #[cfg(feature = "unicode")]
+            strip_format_chars: false,
+            shell_mode: false,
+            max_spaces: None,
+            clamp_run: None,
+            exact_run: None,
+            normalize_unicode_space: false,
+            doubled_quote_escapes: false,
+            split_on_type_change: false,
+            collapse_to: CollapseTo::Replacement,
+            keep_singletons: false,
+            debug_sentinel: None,
+            sentence_spacing: SentenceSpacing::Single,
+            edge_collapse: EdgeCollapse::Keep,
+            category_replacement: None,
+            preserve_newlines: false,
+            tabstop: None,
+            split_on: None,
+            keep_trailing_newline: false,
+            table_separator: None,
+            comment_char: None,
+            protect_urls: false,
+            preserve_ansi: false,
+            verbatim_span: None,
+            verbatim_spans: &[],
+            heredoc: false,
+            heredoc_marker: "<<",
+            leading_newline: None,
+            typographic_quotes: false,
+            preserve_parens: false,
+            drop_empty_quotes: false,
+            forbid_tabs: false,
+            minimal_spacing: false,
+            between_words_only: false,
+            tight_before: &[],
+            tight_after: &[],
+            trim_between: None,
+            only_inside: None,
+            truncate_to: None,
+            ellipsis: "",
+            word_char: &is_word_char,
+            #[cfg(feature = "unicode")]
+            case_unquoted: None,
+            #[cfg(feature = "unicode")]
+            case_quoted: None,
+        }
+    }
+
+    /// Checks the options for contradictory combinations, such as a [`clamp_run`](Self::clamp_run)
+    /// limit along with [`max_spaces`](Self::max_spaces), which would otherwise silently have no
+    /// effect. [`try_merge_whitespace_with_options`](crate::try_merge_whitespace_with_options)
+    /// validates the options before merging.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use merge_whitespace_utils::{CollapseTo, MergeOptions, OptionError};
+    /// let options = MergeOptions::new().clamp_run(2);
+    /// assert_eq!(options.validate(), Ok(()));
+    /// assert_eq!(
+    ///     options.collapse_to(CollapseTo::Last).validate(),
+    ///     Err(OptionError::Conflicting { option: "collapse_to", with: "clamp_run" })
+    /// );
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns the first [`OptionError`] found.
+    pub fn validate(&self) -> Result<(), OptionError> {
+        if self.quote_char.is_some() && self.quote_char == self.escape_char {
+            return Err(OptionError::SameChar {
+                option: "quote_char",
+                with: "escape_char",
+            });
+        }
+        if self.clamp_run.is_some() && self.max_spaces.is_some() {
+            return Err(OptionError::Conflicting {
+                option: "clamp_run",
+                with: "max_spaces",
+            });
+        }
+        if self.exact_run.is_some() {
+            if self.max_spaces.is_some() {
+                return Err(OptionError::Conflicting {
+                    option: "exact_run",
+                    with: "max_spaces",
+                });
+            }
+            if self.clamp_run.is_some() {
+                return Err(OptionError::Conflicting {
+                    option: "clamp_run",
+                    with: "exact_run",
+                });
+            }
+        }
+        if self.collapse_to != CollapseTo::Replacement {
+            if self.exact_run.is_some() {
+                return Err(OptionError::Conflicting {
+                    option: "collapse_to",
+                    with: "exact_run",
+                });
+            }
+            if self.clamp_run.is_some() {
+                return Err(OptionError::Conflicting {
+                    option: "collapse_to",
+                    with: "clamp_run",
+                });
+            }
+            if self.category_replacement.is_some() {
+                return Err(OptionError::Conflicting {
+                    option: "category_replacement",
+                    with: "collapse_to",
+                });
+            }
+        }
+        if self.minimal_spacing && self.between_words_only {
+            return Err(OptionError::Conflicting {
+                option: "between_words_only",
+                with: "minimal_spacing",
+            });
+        }
+        if self.tabstop.is_some() && !self.preserve_newlines {
+            return Err(OptionError::Requires {
+                option: "tabstop",
+                requires: "preserve_newlines",
+            });
+        }
+        if !self.ellipsis.is_empty() && self.truncate_to.is_none() {
+            return Err(OptionError::Requires {
+                option: "ellipsis",
+                requires: "truncate_to",
+            });
+        }
+        if self.heredoc && self.heredoc_marker.is_empty() {
+            return Err(OptionError::Empty {
+                option: "heredoc_marker",
+            });
+        }
+        if self.verbatim_span.is_some_and(|(open, _)| open.is_empty()) {
+            return Err(OptionError::Empty {
+                option: "verbatim_span",
+            });
+        }
+        if self.verbatim_spans.iter().any(|(open, _)| open.is_empty()) {
+            return Err(OptionError::Empty {
+                option: "verbatim_spans",
+            });
+        }
+        if self.only_inside.is_some_and(|(open, close)| open == close) {
+            return Err(OptionError::SameDelimiters {
+                option: "only_inside",
+            });
+        }
+        Ok(())
+    }
+
+    /// Creates the options used by [`minify_json_whitespace`](crate::minify_json_whitespace):
+    /// whitespace outside of `"`-quoted strings is removed entirely, and `\\` escapes the
+    /// character following it.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use merge_whitespace_utils::{merge_whitespace_with_options, MergeOptions};
+    /// let options = MergeOptions::json().replacement(" ");
+    /// let output = merge_whitespace_with_options("{ \"a  b\":   [1,\n 2] }", &options);
+    /// assert_eq!(output, "{ \"a  b\": [1, 2] }");
+    /// ```
+    pub const fn json() -> Self {
+        Self::new()
+            .quote_char('"')
+            .escape_char('\\')
+            .replacement("")
+    }
+
+    /// Creates the options used by [`minify_graphql`](crate::minify_graphql): whitespace outside
+    /// of `"`-quoted strings is merged, `\\` escapes the character following it, `#` starts a
+    /// comment and `\"\"\"`-delimited block strings are kept as-is.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use merge_whitespace_utils::{merge_whitespace_with_options, MergeOptions};
+    /// let options = MergeOptions::graphql().preserve_newlines(true);
+    /// let output = merge_whitespace_with_options("{\n  user   # the user\n  id\n}", &options);
+    /// assert_eq!(output, "{\nuser\nid\n}");
+    /// ```
+    pub const fn graphql() -> Self {
+        Self::new()
+            .quote_char('"')
+            .escape_char('\\')
+            .comment_char('#')
+            .verbatim_span("\"\"\"", "\"\"\"")
+    }
+
+    /// Sets the quote character.
+    #[must_use]
+    pub const fn quote_char(mut self, quote_char: char) -> Self {
+        self.quote_char = Some(quote_char);
+        self
+    }
+
+    /// Sets the escape character.
+    #[must_use]
+    pub const fn escape_char(mut self, escape_char: char) -> Self {
+        self.escape_char = Some(escape_char);
+        self
+    }
+
+    /// Sets whether an escaped whitespace character protects the entire run it starts.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use merge_whitespace_utils::{merge_whitespace_with_options, MergeOptions};
+    /// let options = MergeOptions::new().escape_char('\\').escape_protects_run(true);
+    /// assert_eq!(merge_whitespace_with_options(r"a\   b   c", &options), r"a\   b c");
+    /// ```
+    #[must_use]
+    pub const fn escape_protects_run(mut self, escape_protects_run: bool) -> Self {
+        self.escape_protects_run = escape_protects_run;
+        self
+    }
+
+    /// Sets whether the escape character only has an effect if it is followed by a quote
+    /// character, another escape character or whitespace.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use merge_whitespace_utils::{merge_whitespace_with_options, MergeOptions};
+    /// let options = MergeOptions::new()
+    ///     .escape_char('\\')
+    ///     .comment_char('#')
+    ///     .escape_only_specials(true);
+    /// assert_eq!(merge_whitespace_with_options(r"a\b   \#  c", &options), r"a\b \");
+    /// ```
+    #[must_use]
+    pub const fn escape_only_specials(mut self, escape_only_specials: bool) -> Self {
+        self.escape_only_specials = escape_only_specials;
+        self
+    }
+
+    /// Sets the string each run of whitespace is replaced with.
+    #[must_use]
+    pub const fn replacement(mut self, replacement: &'a str) -> Self {
+        self.replacement = replacement;
+        self
+    }
+
+    /// Sets which ends of the input are trimmed.
+    #[must_use]
+    pub const fn trim(mut self, trim: Trim) -> Self {
+        self.trim = trim;
+        self
+    }
+
+    /// Sets the characters removed from the trimmed ends of the input.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use merge_whitespace_utils::{merge_whitespace_with_options, MergeOptions};
+    /// let options = MergeOptions::new().trim_chars(&['\n']);
+    /// assert_eq!(merge_whitespace_with_options("\n a   b  \n\n", &options), " a b ");
+    /// ```
+    #[must_use]
+    pub const fn trim_chars(mut self, trim_chars: &'a [char]) -> Self {
+        self.trim_chars = Some(trim_chars);
+        self
+    }
+
+    /// Sets whether Unicode format and control characters are removed outside of quoted text.
+    #[cfg(feature = "unicode")]
+    #[must_use]
+    pub const fn strip_format_chars(mut self, strip_format_chars: bool) -> Self {
+        self.strip_format_chars = strip_format_chars;
+        self
+    }
+
+    /// Sets whether POSIX shell quoting rules apply.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use merge_whitespace_utils::{merge_whitespace_with_options, MergeOptions};
+    /// let options = MergeOptions::new().shell_mode(true);
+    /// let output = merge_whitespace_with_options(r#"echo   'a\'   b   "c\"   d""#, &options);
+    /// assert_eq!(output, r#"echo 'a\' b "c\"   d""#);
+    /// ```
+    #[must_use]
+    pub const fn shell_mode(mut self, shell_mode: bool) -> Self {
+        self.shell_mode = shell_mode;
+        self
+    }
+
+    /// Sets the maximum number of original whitespace characters each run keeps.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use merge_whitespace_utils::{merge_whitespace_with_options, MergeOptions};
+    /// let options = MergeOptions::new().max_spaces(2);
+    /// assert_eq!(merge_whitespace_with_options("a b  c     d", &options), "a b  c  d");
+    /// ```
+    #[must_use]
+    pub const fn max_spaces(mut self, max_spaces: usize) -> Self {
+        self.max_spaces = Some(max_spaces);
+        self
+    }
+
+    /// Sets the maximum number of replacements each run is shortened to.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use merge_whitespace_utils::{merge_whitespace_with_options, MergeOptions};
+    /// let options = MergeOptions::new().clamp_run(2);
+    /// assert_eq!(merge_whitespace_with_options("a b\t\tc     d", &options), "a b  c  d");
+    /// ```
+    #[must_use]
+    pub const fn clamp_run(mut self, clamp_run: usize) -> Self {
+        self.clamp_run = Some(clamp_run);
+        self
+    }
+
+    /// Sets the exact number of replacements each run is replaced with.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use merge_whitespace_utils::{merge_whitespace_with_options, MergeOptions};
+    /// let options = MergeOptions::new().exact_run(2);
+    /// assert_eq!(merge_whitespace_with_options("a b\t\tc     d", &options), "a  b  c  d");
+    /// ```
+    #[must_use]
+    pub const fn exact_run(mut self, exact_run: usize) -> Self {
+        self.exact_run = Some(exact_run);
+        self
+    }
+
+    /// Sets whether non-ASCII space characters are rewritten to an ASCII space.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use merge_whitespace_utils::{merge_whitespace_with_options, MergeOptions};
+    /// let options = MergeOptions::new().quote_char('"').normalize_unicode_space(true);
+    /// let output = merge_whitespace_with_options("a\u{2003}\u{2003}\"b\u{00A0}c\"", &options);
+    /// assert_eq!(output, "a \"b c\"");
+    /// ```
+    #[must_use]
+    pub const fn normalize_unicode_space(mut self, normalize_unicode_space: bool) -> Self {
+        self.normalize_unicode_space = normalize_unicode_space;
+        self
+    }
+
+    /// Sets whether a doubled quote character inside a quoted region is an escaped quote.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use merge_whitespace_utils::{merge_whitespace_with_options, MergeOptions};
+    /// let options = MergeOptions::new().quote_char('\'').doubled_quote_escapes(true);
+    /// let output = merge_whitespace_with_options("SELECT   'it''s   here'   AS   x", &options);
+    /// assert_eq!(output, "SELECT 'it''s   here' AS x");
+    /// ```
+    #[must_use]
+    pub const fn doubled_quote_escapes(mut self, doubled_quote_escapes: bool) -> Self {
+        self.doubled_quote_escapes = doubled_quote_escapes;
+        self
+    }
+
+    /// Sets whether a change of the whitespace category ends a run of whitespace.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use merge_whitespace_utils::{merge_whitespace_with_options, MergeOptions};
+    /// let options = MergeOptions::new().split_on_type_change(true);
+    /// assert_eq!(merge_whitespace_with_options("a   b", &options), "a b");
+    /// assert_eq!(merge_whitespace_with_options("a  \t\t  b", &options), "a \t b");
+    /// ```
+    #[must_use]
+    pub const fn split_on_type_change(mut self, split_on_type_change: bool) -> Self {
+        self.split_on_type_change = split_on_type_change;
+        self
+    }
+
+    /// Sets the spacing following sentence punctuation.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use merge_whitespace_utils::{merge_whitespace_with_options, MergeOptions, SentenceSpacing};
+    /// let options = MergeOptions::new().sentence_spacing(SentenceSpacing::Double);
+    /// let output = merge_whitespace_with_options("Hi.   How   are you?", &options);
+    /// assert_eq!(output, "Hi.  How are you?");
+    /// ```
+    #[must_use]
+    pub const fn sentence_spacing(mut self, sentence_spacing: SentenceSpacing) -> Self {
+        self.sentence_spacing = sentence_spacing;
+        self
+    }
+
+    /// Sets how runs of whitespace next to quoted regions are handled.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use merge_whitespace_utils::{merge_whitespace_with_options, EdgeCollapse, MergeOptions};
+    /// let options = MergeOptions::new()
+    ///     .quote_char('"')
+    ///     .edge_collapse(EdgeCollapse::DropAtQuotes);
+    /// let output = merge_whitespace_with_options("foo   \"bar  baz\"   qux  quux", &options);
+    /// assert_eq!(output, "foo\"bar  baz\"qux quux");
+    /// ```
+    #[must_use]
+    pub const fn edge_collapse(mut self, edge_collapse: EdgeCollapse) -> Self {
+        self.edge_collapse = edge_collapse;
+        self
+    }
+
+    /// Sets what each run of whitespace is collapsed to.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use merge_whitespace_utils::{merge_whitespace_with_options, CollapseTo, MergeOptions};
+    /// let options = MergeOptions::new().collapse_to(CollapseTo::Last);
+    /// assert_eq!(merge_whitespace_with_options("a;  \n  \nb", &options), "a;\nb");
+    /// ```
+    #[must_use]
+    pub const fn collapse_to(mut self, collapse_to: CollapseTo) -> Self {
+        self.collapse_to = collapse_to;
+        self
+    }
+
+    /// Sets whether a run consisting of a single whitespace character keeps that character.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use merge_whitespace_utils::{merge_whitespace_with_options, MergeOptions};
+    /// let options = MergeOptions::new().keep_singletons(true);
+    /// assert_eq!(merge_whitespace_with_options("a\tb  c\n d", &options), "a\tb c d");
+    /// ```
+    #[must_use]
+    pub const fn keep_singletons(mut self, keep_singletons: bool) -> Self {
+        self.keep_singletons = keep_singletons;
+        self
+    }
+
+    /// Sets the visible marker runs of whitespace are replaced with for diagnostic output.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use merge_whitespace_utils::{merge_whitespace_with_options, MergeOptions};
+    /// let options = MergeOptions::new().quote_char('"').debug_sentinel('·');
+    /// let output = merge_whitespace_with_options("  a   \"b   c\" d\te  ", &options);
+    /// assert_eq!(output, "a·\"b   c\" d·e");
+    /// ```
+    #[must_use]
+    pub const fn debug_sentinel(mut self, debug_sentinel: char) -> Self {
+        self.debug_sentinel = Some(debug_sentinel);
+        self
+    }
+
+    /// Sets the per-category replacement of whitespace runs.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use merge_whitespace_utils::{merge_whitespace_with_options, CategoryReplacement, MergeOptions};
+    /// let options = MergeOptions::new().category_replacement(CategoryReplacement::new());
+    /// let output = merge_whitespace_with_options("a   b\t\t c \n\n d", &options);
+    /// assert_eq!(output, "a b\tc\nd");
+    /// ```
+    #[must_use]
+    pub const fn category_replacement(mut self, category_replacement: CategoryReplacement) -> Self {
+        self.category_replacement = Some(category_replacement);
+        self
+    }
+
+    /// Sets whether line breaks are kept.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use merge_whitespace_utils::{merge_whitespace_with_options, MergeOptions};
+    /// let options = MergeOptions::new().preserve_newlines(true);
+    /// let output = merge_whitespace_with_options("a   b  \n\n    c \r\n d", &options);
+    /// assert_eq!(output, "a b\n\nc\r\nd");
+    /// ```
+    #[must_use]
+    pub const fn preserve_newlines(mut self, preserve_newlines: bool) -> Self {
+        self.preserve_newlines = preserve_newlines;
+        self
+    }
+
+    /// Sets the tab stop width tabs are expanded to if line breaks are preserved.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use merge_whitespace_utils::{merge_whitespace_with_options, MergeOptions};
+    /// let options = MergeOptions::new().preserve_newlines(true).tabstop(4);
+    /// let output = merge_whitespace_with_options("a\tb  c\nabcde\tf", &options);
+    /// assert_eq!(output, "a   b c\nabcde   f");
+    /// ```
+    #[must_use]
+    pub const fn tabstop(mut self, tabstop: usize) -> Self {
+        self.tabstop = Some(tabstop);
+        self
+    }
+
+    /// Sets the record separator.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use merge_whitespace_utils::{merge_whitespace_with_options, MergeOptions};
+    /// let options = MergeOptions::new().split_on('\r');
+    /// let output = merge_whitespace_with_options("a   b \r c\r\r  d", &options);
+    /// assert_eq!(output, "a b\rc\r\rd");
+    /// ```
+    #[must_use]
+    pub const fn split_on(mut self, split_on: char) -> Self {
+        self.split_on = Some(split_on);
+        self
+    }
+
+    /// Sets whether a trailing line break of the input is kept as a single `\n`.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use merge_whitespace_utils::{merge_whitespace_with_options, MergeOptions};
+    /// let options = MergeOptions::new().keep_trailing_newline(true);
+    /// assert_eq!(merge_whitespace_with_options("a   b  \n\n", &options), "a b\n");
+    /// assert_eq!(merge_whitespace_with_options("a   b  ", &options), "a b");
+    /// ```
+    #[must_use]
+    pub const fn keep_trailing_newline(mut self, keep_trailing_newline: bool) -> Self {
+        self.keep_trailing_newline = keep_trailing_newline;
+        self
+    }
+
+    /// Sets the table column separator.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use merge_whitespace_utils::{merge_whitespace_with_options, MergeOptions};
+    /// let options = MergeOptions::new().table_separator('|').preserve_newlines(true);
+    /// let table = "  | a   | b  |  \n  | 10  | 2  |  ";
+    /// let output = merge_whitespace_with_options(table, &options);
+    /// assert_eq!(output, "| a   | b  |\n| 10  | 2  |");
+    /// ```
+    #[must_use]
+    pub const fn table_separator(mut self, table_separator: char) -> Self {
+        self.table_separator = Some(table_separator);
+        self
+    }
+
+    /// Sets the line comment character.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use merge_whitespace_utils::{merge_whitespace_with_options, MergeOptions};
+    /// let options = MergeOptions::new().comment_char('#');
+    /// let output = merge_whitespace_with_options("query {  # all users\n  users  }", &options);
+    /// assert_eq!(output, "query { users }");
+    /// ```
+    #[must_use]
+    pub const fn comment_char(mut self, comment_char: char) -> Self {
+        self.comment_char = Some(comment_char);
+        self
+    }
+
+    /// Sets whether URLs are kept verbatim.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use merge_whitespace_utils::{merge_whitespace_with_options, MergeOptions};
+    /// let options = MergeOptions::new().comment_char('#').protect_urls(true);
+    /// let output = merge_whitespace_with_options("see   https://example.com/#top   # link", &options);
+    /// assert_eq!(output, "see https://example.com/#top");
+    /// ```
+    #[must_use]
+    pub const fn protect_urls(mut self, protect_urls: bool) -> Self {
+        self.protect_urls = protect_urls;
+        self
+    }
+
+    /// Sets whether ANSI escape sequences are kept verbatim.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use merge_whitespace_utils::{merge_whitespace_with_options, MergeOptions};
+    /// let options = MergeOptions::new().preserve_ansi(true);
+    /// let output = merge_whitespace_with_options("  \x1b[1;31merror:\x1b[0m   failed  ", &options);
+    /// assert_eq!(output, "\x1b[1;31merror:\x1b[0m failed");
+    /// ```
+    #[must_use]
+    pub const fn preserve_ansi(mut self, preserve_ansi: bool) -> Self {
+        self.preserve_ansi = preserve_ansi;
+        self
+    }
+
+    /// Sets the sentinels delimiting verbatim spans.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use merge_whitespace_utils::{merge_whitespace_with_options, MergeOptions};
+    /// let options = MergeOptions::new().quote_char('"').verbatim_span("<<EOF", "EOF");
+    /// let output = merge_whitespace_with_options("cat  <<EOF\n  \"a  \\\nEOF   done", &options);
+    /// assert_eq!(output, "cat <<EOF\n  \"a  \\\nEOF done");
+    /// ```
+    #[must_use]
+    pub const fn verbatim_span(mut self, open: &'a str, close: &'a str) -> Self {
+        self.verbatim_span = Some((open, close));
+        self
+    }
+
+    /// Sets further pairs of sentinels delimiting verbatim spans.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use merge_whitespace_utils::{merge_whitespace_with_options, MergeOptions};
+    /// let options = MergeOptions::new().verbatim_spans(&[("$(", ")"), ("${", "}")]);
+    /// let output = merge_whitespace_with_options("echo   $(ls   -l)   ${A:-  b}", &options);
+    /// assert_eq!(output, "echo $(ls   -l) ${A:-  b}");
+    /// ```
+    #[must_use]
+    pub const fn verbatim_spans(mut self, verbatim_spans: &'a [(&'a str, &'a str)]) -> Self {
+        self.verbatim_spans = verbatim_spans;
+        self
+    }
+
+    /// Sets whether heredoc-style regions are kept as-is.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use merge_whitespace_utils::{merge_whitespace_with_options, MergeOptions};
+    /// let options = MergeOptions::new().heredoc(true);
+    /// let input = "cat  <<EOF  >  out\n  a   b\nEOF\necho   done";
+    /// let output = merge_whitespace_with_options(input, &options);
+    /// assert_eq!(output, "cat <<EOF  >  out\n  a   b\nEOF\necho done");
+    /// ```
+    #[must_use]
+    pub const fn heredoc(mut self, heredoc: bool) -> Self {
+        self.heredoc = heredoc;
+        self
+    }
+
+    /// Sets the marker starting a heredoc-style region.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use merge_whitespace_utils::{merge_whitespace_with_options, MergeOptions};
+    /// let options = MergeOptions::new().heredoc(true).heredoc_marker("<<~");
+    /// let input = "text  =  <<~SQL\n  SELECT  *\n  SQL";
+    /// let output = merge_whitespace_with_options(input, &options);
+    /// assert_eq!(output, "text = <<~SQL\n  SELECT  *\n  SQL");
+    /// ```
+    #[must_use]
+    pub const fn heredoc_marker(mut self, heredoc_marker: &'a str) -> Self {
+        self.heredoc_marker = heredoc_marker;
+        self
+    }
+
+    /// Sets how the leading whitespace of the input is handled.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use merge_whitespace_utils::{merge_whitespace_with_options, LeadingPolicy, MergeOptions};
+    /// let options = MergeOptions::new().leading_newline(LeadingPolicy::KeepOne);
+    /// assert_eq!(merge_whitespace_with_options("\n\n   first   line", &options), "\nfirst line");
+    /// ```
+    #[must_use]
+    pub const fn leading_newline(mut self, leading_newline: LeadingPolicy) -> Self {
+        self.leading_newline = Some(leading_newline);
+        self
+    }
+
+    /// Sets whether the typographic quote pairs `«`/`»` and `“`/`”` delimit quoted regions.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use merge_whitespace_utils::{merge_whitespace_with_options, MergeOptions};
+    /// let options = MergeOptions::new().typographic_quotes(true);
+    /// let output = merge_whitespace_with_options("a   «  b  »   “  c  ”", &options);
+    /// assert_eq!(output, "a «  b  » “  c  ”");
+    /// ```
+    #[must_use]
+    pub const fn typographic_quotes(mut self, typographic_quotes: bool) -> Self {
+        self.typographic_quotes = typographic_quotes;
+        self
+    }
+
+    /// Sets whether the whitespace inside parentheses is kept as-is.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use merge_whitespace_utils::{merge_whitespace_with_options, MergeOptions};
+    /// let options = MergeOptions::new().preserve_parens(true);
+    /// let output = merge_whitespace_with_options("x  =  ( a  +  (b  *  c) )   /  2", &options);
+    /// assert_eq!(output, "x = ( a  +  (b  *  c) ) / 2");
+    /// ```
+    #[must_use]
+    pub const fn preserve_parens(mut self, preserve_parens: bool) -> Self {
+        self.preserve_parens = preserve_parens;
+        self
+    }
+
+    /// Sets whether quoted regions containing nothing but whitespace are removed.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use merge_whitespace_utils::{merge_whitespace_with_options, MergeOptions};
+    /// let options = MergeOptions::new().quote_char('"').drop_empty_quotes(true);
+    /// let output = merge_whitespace_with_options(r#"Hello  ""  " "  "World""#, &options);
+    /// assert_eq!(output, r#"Hello "World""#);
+    /// ```
+    #[must_use]
+    pub const fn drop_empty_quotes(mut self, drop_empty_quotes: bool) -> Self {
+        self.drop_empty_quotes = drop_empty_quotes;
+        self
+    }
+
+    /// Sets whether a tab character outside of quoted regions is an error.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use merge_whitespace_utils::{try_merge_whitespace_with_options, MergeError, MergeOptions};
+    /// let options = MergeOptions::new().quote_char('"').forbid_tabs(true);
+    /// let output = try_merge_whitespace_with_options("a  \"\t\"\tb", &options);
+    /// assert_eq!(output, Err(MergeError::TabFound { at: 6 }));
+    /// ```
+    #[must_use]
+    pub const fn forbid_tabs(mut self, forbid_tabs: bool) -> Self {
+        self.forbid_tabs = forbid_tabs;
+        self
+    }
+
+    /// Sets whether runs of whitespace are removed unless they separate two word characters.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use merge_whitespace_utils::{merge_whitespace_with_options, MergeOptions};
+    /// let options = MergeOptions::new().quote_char('"').minimal_spacing(true);
+    /// let output = merge_whitespace_with_options("let  x = f( a,  \"b  c\" ) ;", &options);
+    /// assert_eq!(output, "let x=f(a,\"b  c\");");
+    /// ```
+    #[must_use]
+    pub const fn minimal_spacing(mut self, minimal_spacing: bool) -> Self {
+        self.minimal_spacing = minimal_spacing;
+        self
+    }
+
+    /// Sets whether runs of whitespace are only merged if they separate two word characters.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use merge_whitespace_utils::{merge_whitespace_with_options, MergeOptions};
+    /// let options = MergeOptions::new().between_words_only(true);
+    /// let output = merge_whitespace_with_options("Wait  ...   what   ?  No", &options);
+    /// assert_eq!(output, "Wait  ...   what   ?  No");
+    /// let output = merge_whitespace_with_options("one   two ,  three", &options);
+    /// assert_eq!(output, "one two ,  three");
+    /// ```
+    #[must_use]
+    pub const fn between_words_only(mut self, between_words_only: bool) -> Self {
+        self.between_words_only = between_words_only;
+        self
+    }
+
+    /// Sets the characters the whitespace directly before is removed entirely.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use merge_whitespace_utils::{merge_whitespace_with_options, MergeOptions};
+    /// let options = MergeOptions::new().quote_char('"').tight_before(&[',', ';']);
+    /// let output = merge_whitespace_with_options("a  , b ;  \"c ,\"", &options);
+    /// assert_eq!(output, "a, b; \"c ,\"");
+    /// ```
+    #[must_use]
+    pub const fn tight_before(mut self, tight_before: &'a [char]) -> Self {
+        self.tight_before = tight_before;
+        self
+    }
+
+    /// Sets the characters the whitespace directly after is removed entirely.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use merge_whitespace_utils::{merge_whitespace_with_options, MergeOptions};
+    /// let options = MergeOptions::new().tight_before(&[')']).tight_after(&['(']);
+    /// let output = merge_whitespace_with_options("f(  a  b  )  c", &options);
+    /// assert_eq!(output, "f(a b) c");
+    /// ```
+    #[must_use]
+    pub const fn tight_after(mut self, tight_after: &'a [char]) -> Self {
+        self.tight_after = tight_after;
+        self
+    }
+
+    /// Sets the field delimiter whitespace between two consecutive occurrences is removed.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use merge_whitespace_utils::{merge_whitespace_with_options, MergeOptions};
+    /// let options = MergeOptions::new().trim_between(',');
+    /// let output = merge_whitespace_with_options("a,  ,  b,\t,", &options);
+    /// assert_eq!(output, "a,, b,,");
+    /// ```
+    #[must_use]
+    pub const fn trim_between(mut self, trim_between: char) -> Self {
+        self.trim_between = Some(trim_between);
+        self
+    }
+
+    /// Sets the pair of characters delimiting the regions whitespace is merged in.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use merge_whitespace_utils::{merge_whitespace_with_options, MergeOptions};
+    /// let options = MergeOptions::new().only_inside('{', '}');
+    /// let output = merge_whitespace_with_options("key  =  {  a   { b }  }  ", &options);
+    /// assert_eq!(output, "key  =  { a { b } }  ");
+    /// ```
+    #[must_use]
+    pub const fn only_inside(mut self, open: char, close: char) -> Self {
+        self.only_inside = Some((open, close));
+        self
+    }
+
+    /// Sets the maximum length of the output in bytes.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use merge_whitespace_utils::{merge_whitespace_with_options, MergeOptions};
+    /// let options = MergeOptions::new().truncate_to(8);
+    /// assert_eq!(merge_whitespace_with_options("  a   short  message ", &options), "a short ");
+    /// assert_eq!(merge_whitespace_with_options("a   short", &options), "a short");
+    /// ```
+    #[must_use]
+    pub const fn truncate_to(mut self, truncate_to: usize) -> Self {
+        self.truncate_to = Some(truncate_to);
+        self
+    }
+
+    /// Sets the string appended to truncated output.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use merge_whitespace_utils::{merge_whitespace_with_options, MergeOptions};
+    /// let options = MergeOptions::new().truncate_to(7).ellipsis("…");
+    /// assert_eq!(merge_whitespace_with_options("a   long   message", &options), "a long …");
+    /// ```
+    #[must_use]
+    pub const fn ellipsis(mut self, ellipsis: &'a str) -> Self {
+        self.ellipsis = ellipsis;
+        self
+    }
+
+    /// Sets the predicate determining whether a character is a word character. Unlike a plain
+    /// function, the predicate may capture state, e.g. a set of characters determined at runtime.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use merge_whitespace_utils::{merge_whitespace_with_options, MergeOptions};
+    /// let options = MergeOptions::new()
+    ///     .minimal_spacing(true)
+    ///     .word_char(&|c| c.is_alphanumeric() || c == '$');
+    /// assert_eq!(merge_whitespace_with_options("echo   $a   $b", &options), "echo $a $b");
+    /// ```
+    #[must_use]
+    pub const fn word_char(mut self, word_char: &'a (dyn Fn(char) -> bool + Sync)) -> Self {
+        self.word_char = word_char;
+        self
+    }
+
+    /// Sets the case the text outside of quoted regions is converted to.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use merge_whitespace_utils::{merge_whitespace_with_options, Case, MergeOptions};
+    /// let options = MergeOptions::new()
+    ///     .quote_char('\'')
+    ///     .case_unquoted(Case::Upper)
+    ///     .case_quoted(Case::Lower);
+    /// let output = merge_whitespace_with_options("select   'Grüße'   from   t", &options);
+    /// assert_eq!(output, "SELECT 'grüße' FROM T");
+    /// ```
+    #[cfg(feature = "unicode")]
+    #[must_use]
+    pub const fn case_unquoted(mut self, case: Case) -> Self {
+        self.case_unquoted = Some(case);
+        self
+    }
+
+    /// Sets the case the text inside of quoted regions is converted to.
+    #[cfg(feature = "unicode")]
+    #[must_use]
+    pub const fn case_quoted(mut self, case: Case) -> Self {
+        self.case_quoted = Some(case);
+        self
+    }
+}
+
+impl MergeOptions<'_> {
+    /// Gets the escape character in effect, taking [`shell_mode`](Self::shell_mode) into account.
+    pub(crate) fn effective_escape_char(&self) -> Option<char> {
+        match self.escape_char {
+            None if self.shell_mode => Some('\\'),
+            escape_char => escape_char,
+        }
+    }
+}
+
+impl Default for MergeOptions<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
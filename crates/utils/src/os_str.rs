@@ -0,0 +1,161 @@
+use std::borrow::Cow;
+use std::ffi::OsStr;
+
+/// Remove multiple consecutive ASCII whitespaces from an [`OsStr`], such as a file name, and
+/// replace them with a single space. Leading and trailing ASCII whitespace is removed.
+///
+/// Only ASCII whitespace is merged and no quote or escape characters are supported. All other
+/// content, including data that is not valid Unicode, is kept untouched:
+///
+/// - On Unix platforms, the string is processed as raw bytes.
+/// - On Windows, the string is processed as UTF-16 code units, so unpaired surrogates
+///   are kept as well.
+/// - On other platforms, strings that are not valid Unicode are returned unchanged.
+///
+/// ## Example
+///
+/// ```
+/// # use std::ffi::OsStr;
+/// # use merge_whitespace_utils::merge_whitespace_os;
+/// let output = merge_whitespace_os(OsStr::new("  my   holiday \t photo.jpg "));
+/// assert_eq!(output, OsStr::new("my holiday photo.jpg"));
+/// ```
+///
+/// # Return
+///
+/// The modified string.
+pub fn merge_whitespace_os(input: &OsStr) -> Cow<'_, OsStr> {
+    #[cfg(unix)]
+    {
+        use std::ffi::OsString;
+        use std::os::unix::ffi::{OsStrExt, OsStringExt};
+
+        match merge_ascii_whitespace(input.as_bytes(), |b| b.is_ascii_whitespace(), b' ') {
+            Cow::Borrowed(bytes) => Cow::Borrowed(OsStr::from_bytes(bytes)),
+            Cow::Owned(bytes) => Cow::Owned(OsString::from_vec(bytes)),
+        }
+    }
+
+    #[cfg(windows)]
+    {
+        use std::ffi::OsString;
+        use std::os::windows::ffi::{OsStrExt, OsStringExt};
+
+        if let Some(input) = input.to_str() {
+            return merge_utf8(input);
+        }
+        let wide: Vec<u16> = input.encode_wide().collect();
+        let is_whitespace = |c: u16| u8::try_from(c).is_ok_and(|b| b.is_ascii_whitespace());
+        let merged = merge_ascii_whitespace(&wide, is_whitespace, u16::from(b' '));
+        Cow::Owned(OsString::from_wide(&merged))
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    {
+        match input.to_str() {
+            Some(input) => merge_utf8(input),
+            None => Cow::Borrowed(input),
+        }
+    }
+}
+
+/// Merges the ASCII whitespace of valid UTF-8 input.
+#[cfg(not(unix))]
+fn merge_utf8(input: &str) -> Cow<'_, OsStr> {
+    // ASCII bytes never occur within multi-byte UTF-8 sequences, so the result is valid UTF-8.
+    match merge_ascii_whitespace(input.as_bytes(), |b| b.is_ascii_whitespace(), b' ') {
+        Cow::Borrowed(bytes) => Cow::Borrowed(OsStr::new(
+            std::str::from_utf8(bytes).expect("merging preserves UTF-8"),
+        )),
+        Cow::Owned(bytes) => Cow::Owned(
+            String::from_utf8(bytes)
+                .expect("merging preserves UTF-8")
+                .into(),
+        ),
+    }
+}
+
+/// Trims the input and replaces each run of whitespace units with a single `space`.
+fn merge_ascii_whitespace<T: Copy + PartialEq>(
+    input: &[T],
+    is_whitespace: impl Fn(T) -> bool,
+    space: T,
+) -> Cow<'_, [T]> {
+    let start = input
+        .iter()
+        .position(|&c| !is_whitespace(c))
+        .unwrap_or(input.len());
+    let end = input
+        .iter()
+        .rposition(|&c| !is_whitespace(c))
+        .map_or(start, |end| end + 1);
+    let trimmed = &input[start..end];
+
+    // Only allocate if a run other than a single space needs to be replaced.
+    let needs_merging = trimmed.iter().any(|&c| is_whitespace(c) && c != space)
+        || trimmed
+            .windows(2)
+            .any(|pair| is_whitespace(pair[0]) && is_whitespace(pair[1]));
+    if !needs_merging {
+        return Cow::Borrowed(trimmed);
+    }
+
+    let mut output = Vec::with_capacity(trimmed.len());
+    let mut prev_was_whitespace = false;
+    for &c in trimmed {
+        if is_whitespace(c) {
+            if !prev_was_whitespace {
+                output.push(space);
+            }
+            prev_was_whitespace = true;
+        } else {
+            output.push(c);
+            prev_was_whitespace = false;
+        }
+    }
+    Cow::Owned(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn path_with_doubled_spaces() {
+        let path = Path::new("holiday  photos/beach   day.jpg");
+        assert_eq!(
+            merge_whitespace_os(path.as_os_str()),
+            OsStr::new("holiday photos/beach day.jpg")
+        );
+    }
+
+    #[test]
+    fn unchanged_path_is_borrowed() {
+        let output = merge_whitespace_os(OsStr::new(" holiday photos/beach day.jpg\n"));
+        assert_eq!(output, OsStr::new("holiday photos/beach day.jpg"));
+        #[cfg(unix)]
+        assert!(matches!(output, Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn only_ascii_whitespace_is_merged() {
+        assert_eq!(
+            merge_whitespace_os(OsStr::new("a\u{00A0}\u{00A0}b\t\r\nc")),
+            OsStr::new("a\u{00A0}\u{00A0}b c")
+        );
+        assert_eq!(merge_whitespace_os(OsStr::new(" \t ")), OsStr::new(""));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn non_utf8_bytes_are_kept() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let input = OsStr::from_bytes(b"  a  \xFF\xFE  b ");
+        assert_eq!(
+            merge_whitespace_os(input),
+            OsStr::from_bytes(b"a \xFF\xFE b")
+        );
+    }
+}
@@ -0,0 +1,100 @@
+use std::borrow::Cow;
+
+use crate::{merge_whitespace_with_options, MergeOptions};
+
+/// Remove all whitespace outside of the strings of a JSON document, using the
+/// [`MergeOptions::json`] preset.
+///
+/// ## Example
+///
+/// ```
+/// # use merge_whitespace_utils::minify_json_whitespace;
+/// let output = minify_json_whitespace("{\n  \"name\":  \"Froozle   Frobnik\",\n  \"ids\": [1, 2]\n}");
+/// assert_eq!(output, r#"{"name":"Froozle   Frobnik","ids":[1,2]}"#);
+/// ```
+///
+/// # Return
+///
+/// The minified JSON.
+pub fn minify_json_whitespace(input: &str) -> Cow<'_, str> {
+    merge_whitespace_with_options(input, &MergeOptions::json())
+}
+
+/// Remove multiple consecutive whitespaces and comments from a GraphQL document, using the
+/// [`MergeOptions::graphql`] preset.
+///
+/// ## Example
+///
+/// ```
+/// # use merge_whitespace_utils::minify_graphql;
+/// let output = minify_graphql(r#"
+///     query { # Only the first user.
+///       users (limit: 1, filter: "bought a 12\" vinyl") {
+///         id
+///       }
+///     }
+/// "#);
+/// assert_eq!(output, r#"query { users (limit: 1, filter: "bought a 12\" vinyl") { id } }"#);
+/// ```
+///
+/// # Return
+///
+/// The minified GraphQL document.
+pub fn minify_graphql(input: &str) -> Cow<'_, str> {
+    merge_whitespace_with_options(input, &MergeOptions::graphql())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_preset_matches_minify_json_whitespace() {
+        for input in [
+            "",
+            "  { }  ",
+            "{\n\t\"a  b\" : [ 1 , 2 ],\r\n \"c\": \"\\\"  \\\\\" }",
+            "[ \"unterminated  ",
+        ] {
+            assert_eq!(
+                merge_whitespace_with_options(input, &MergeOptions::json()),
+                minify_json_whitespace(input)
+            );
+        }
+        assert_eq!(
+            minify_json_whitespace("{\n\t\"a  b\" : [ 1 , 2 ],\r\n \"c\": \"\\\"  \\\\\" }"),
+            "{\"a  b\":[1,2],\"c\":\"\\\"  \\\\\"}"
+        );
+    }
+
+    #[test]
+    fn graphql_preset_matches_minify_graphql() {
+        let input = r#"
+            query Users($first: Int)  {  # The query.
+              users(first: $first, filter: "a   # b") {
+                description(format: """
+                  kept   "as"   is
+                """)
+              }
+            }
+        "#;
+        assert_eq!(
+            merge_whitespace_with_options(input, &MergeOptions::graphql()),
+            minify_graphql(input)
+        );
+        assert_eq!(
+            minify_graphql(input),
+            "query Users($first: Int) { users(first: $first, filter: \"a   # b\") { \
+             description(format: \"\"\"\n                  kept   \"as\"   is\n                \"\"\") } }"
+        );
+    }
+
+    #[test]
+    fn presets_can_be_tweaked() {
+        let options = MergeOptions::graphql().replacement("_");
+        assert_eq!(
+            merge_whitespace_with_options("{  a  # comment\n  b  }", &options),
+            "{_a_b_}"
+        );
+    }
+}
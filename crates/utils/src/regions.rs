@@ -0,0 +1,170 @@
+use std::ops::Range;
+
+use crate::engine::{Discard, Engine};
+use crate::MergeOptions;
+
+/// Determines the byte ranges of the quoted regions of the input, e.g. to highlight them.
+///
+/// Quote characters following the escape character do not start or end a region. A region
+/// that is not closed extends to the end of the input.
+///
+/// ## Example
+///
+/// ```
+/// # use merge_whitespace_utils::quoted_regions;
+/// let input = r#"a "b  c" d "e""#;
+/// assert_eq!(quoted_regions(input, '"', None, true), [2..8, 11..14]);
+/// assert_eq!(quoted_regions(input, '"', None, false), [3..7, 12..13]);
+/// ```
+///
+/// # Return
+///
+/// The ranges of the quoted regions, including the quote characters if `include_delimiters`
+/// is `true`.
+pub fn quoted_regions(
+    input: &str,
+    quote_char: char,
+    escape_char: Option<char>,
+    include_delimiters: bool,
+) -> Vec<Range<usize>> {
+    let options = MergeOptions {
+        quote_char: Some(quote_char),
+        escape_char,
+        ..MergeOptions::new()
+    };
+    let mut engine = Engine::new(&options);
+    let mut regions = Vec::new();
+    let mut start = None;
+
+    for (offset, c) in input.char_indices() {
+        engine.push(offset, c, &mut Discard);
+        match (start, engine.is_quoted()) {
+            (None, true) => start = Some(offset),
+            (Some(region_start), false) => {
+                regions.push(region(region_start, offset, quote_char, include_delimiters));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(region_start) = start {
+        let inner_start = region_start + quote_char.len_utf8();
+        regions.push(match include_delimiters {
+            true => region_start..input.len(),
+            false => inner_start..input.len(),
+        });
+    }
+    regions
+}
+
+/// Determines whether every quoted region of the input is closed, e.g. to validate the input
+/// before merging. Quote characters following the escape character do not open or close a
+/// region.
+///
+/// ## Example
+///
+/// ```
+/// # use merge_whitespace_utils::quotes_balanced;
+/// assert!(quotes_balanced(r#"a "b" "c\"d""#, '"', Some('\\')));
+/// assert!(!quotes_balanced(r#"a "b" "c"#, '"', Some('\\')));
+/// ```
+pub fn quotes_balanced(input: &str, quote_char: char, escape_char: Option<char>) -> bool {
+    let options = MergeOptions {
+        quote_char: Some(quote_char),
+        escape_char,
+        ..MergeOptions::new()
+    };
+    let mut engine = Engine::new(&options);
+    for (offset, c) in input.char_indices() {
+        engine.push(offset, c, &mut Discard);
+    }
+    !engine.is_quoted()
+}
+
+/// Gets the range of the region the quote characters at `start` and `end`.
+fn region(start: usize, end: usize, quote_char: char, include_delimiters: bool) -> Range<usize> {
+    let len = quote_char.len_utf8();
+    match include_delimiters {
+        true => start..end + len,
+        false => start + len..end,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUT: &str = r#"SELECT "a  b" FROM "c \"d\"  e" WHERE x"#;
+
+    #[test]
+    fn regions_including_delimiters() {
+        let regions = quoted_regions(INPUT, '"', Some('\\'), true);
+        assert_eq!(regions, [7..13, 19..31]);
+        assert_eq!(&INPUT[regions[0].clone()], r#""a  b""#);
+        assert_eq!(&INPUT[regions[1].clone()], r#""c \"d\"  e""#);
+    }
+
+    #[test]
+    fn regions_excluding_delimiters() {
+        let regions = quoted_regions(INPUT, '"', Some('\\'), false);
+        assert_eq!(regions, [8..12, 20..30]);
+        assert_eq!(&INPUT[regions[0].clone()], "a  b");
+        assert_eq!(&INPUT[regions[1].clone()], r#"c \"d\"  e"#);
+    }
+
+    #[test]
+    fn balanced_quotes() {
+        for input in [
+            "",
+            "a b",
+            r#""a""#,
+            r#"a "b  c" "" d"#,
+            r#""a\"b""#,
+            r#"\""#,
+            r#""a\\""#,
+        ] {
+            assert!(quotes_balanced(input, '"', Some('\\')), "{input:?}");
+        }
+    }
+
+    #[test]
+    fn unbalanced_quotes() {
+        for input in [r#"""#, r#"a "b" "c"#, r#"""""#, r#""a\""#, r#""a\\\""#] {
+            assert!(!quotes_balanced(input, '"', Some('\\')), "{input:?}");
+        }
+        assert!(!quotes_balanced(r#""a\"b""#, '"', None));
+    }
+
+    #[test]
+    fn regions_with_multi_byte_delimiters() {
+        let input = "a §b ¶§ c§ d §e";
+        assert_eq!(quoted_regions(input, '§', Some('¶'), true), [2..14, 17..20]);
+        assert_eq!(
+            quoted_regions(input, '§', Some('¶'), false),
+            [4..12, 19..20]
+        );
+        assert_eq!(&input[2..14], "§b ¶§ c§");
+    }
+
+    #[test]
+    fn regions_without_escape_char() {
+        let regions = quoted_regions(INPUT, '"', None, true);
+        assert_eq!(regions, [7..13, 19..24, 26..31]);
+    }
+
+    #[test]
+    fn unclosed_and_empty_regions() {
+        assert_eq!(
+            quoted_regions(r#"a "" b "c"#, '"', None, true),
+            [2..4, 7..9]
+        );
+        assert_eq!(
+            quoted_regions(r#"a "" b "c"#, '"', None, false),
+            [3..3, 8..9]
+        );
+        assert_eq!(quoted_regions("«a« «b c", '«', None, true), [0..5, 6..11]);
+        assert_eq!(quoted_regions("«a« «b c", '«', None, false), [2..3, 8..11]);
+        assert!(quoted_regions("a b c", '"', None, true).is_empty());
+    }
+}
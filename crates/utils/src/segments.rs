@@ -0,0 +1,149 @@
+use std::borrow::Cow;
+use std::ops::Range;
+
+use crate::engine::{merge_into, Sink};
+use crate::MergeOptions;
+
+/// Remove multiple consecutive whitespaces from a given string, returning the output as a
+/// sequence of segments rather than a single string.
+///
+/// Unchanged parts of the input are returned as [`Cow::Borrowed`] slices of it, while merged
+/// whitespace runs are returned as [`Cow::Owned`] segments. Writing the segments sequentially
+/// produces the same output as [`merge_whitespace_with_options`](crate::merge_whitespace_with_options),
+/// without having to copy everything following the first merged run into one large allocation.
+///
+/// ## Example
+///
+/// ```
+/// # use std::borrow::Cow;
+/// # use merge_whitespace_utils::{merge_whitespace_segments, MergeOptions};
+/// let segments = merge_whitespace_segments("  Hello    World!  ", &MergeOptions::new());
+/// assert_eq!(
+///     segments,
+///     [
+///         Cow::Borrowed("Hello"),
+///         Cow::Owned(String::from(" ")),
+///         Cow::Borrowed("World!"),
+///     ]
+/// );
+/// assert_eq!(segments.concat(), "Hello World!");
+/// ```
+///
+/// # Return
+///
+/// The segments of the modified string.
+pub fn merge_whitespace_segments<'a>(input: &'a str, options: &MergeOptions) -> Vec<Cow<'a, str>> {
+    let mut sink = SegmentSink {
+        input,
+        segments: Vec::new(),
+        borrowed_end: None,
+    };
+    merge_into(input, options, &mut sink);
+    sink.segments
+}
+
+/// A [`Sink`] collecting alternating borrowed and owned segments.
+struct SegmentSink<'a> {
+    input: &'a str,
+    segments: Vec<Cow<'a, str>>,
+    /// The input offset the last segment ends at, if it is borrowed.
+    borrowed_end: Option<usize>,
+}
+
+impl Sink for SegmentSink<'_> {
+    fn keep(&mut self, offset: usize, text: &str) {
+        let end = offset + text.len();
+        if let (Some(borrowed_end), Some(Cow::Borrowed(segment))) =
+            (self.borrowed_end, self.segments.last_mut())
+        {
+            if borrowed_end == offset {
+                let start = offset - segment.len();
+                *segment = &self.input[start..end];
+                self.borrowed_end = Some(end);
+                return;
+            }
+        }
+        self.segments.push(Cow::Borrowed(&self.input[offset..end]));
+        self.borrowed_end = Some(end);
+    }
+
+    fn replace(&mut self, range: Range<usize>, with: &str) {
+        if self.input[range.clone()] == *with {
+            self.keep(range.start, with);
+            return;
+        }
+        self.borrowed_end = None;
+        if with.is_empty() {
+            return;
+        }
+        match self.segments.last_mut() {
+            Some(Cow::Owned(segment)) => segment.push_str(with),
+            _ => self.segments.push(Cow::Owned(String::from(with))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{merge_whitespace_with_options, Trim};
+
+    const INPUTS: [&str; 8] = [
+        "",
+        "  \n \t  ",
+        "foo bar baz",
+        "  foo bar baz  ",
+        "foo  bar\nbaz",
+        "foo   foobar   \"  bar\n\" baz",
+        "what   \\   if I quote\\ spaces",
+        r#"foo   foobar   "  \"bar   \"   "   baz"#,
+    ];
+
+    #[test]
+    fn concatenation_equals_merged_output() {
+        let options = [
+            MergeOptions::new(),
+            MergeOptions::new().quote_char('"').escape_char('\\'),
+            MergeOptions::new().replacement("_").trim(Trim::None),
+            MergeOptions::new().replacement("").trim(Trim::Start),
+        ];
+
+        for options in &options {
+            for input in INPUTS {
+                let segments = merge_whitespace_segments(input, options);
+                assert_eq!(
+                    segments.concat(),
+                    merge_whitespace_with_options(input, options),
+                    "input: {input:?}, options: {options:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn unchanged_input_is_a_single_borrowed_segment() {
+        let segments = merge_whitespace_segments("  foo bar baz ", &MergeOptions::new());
+        assert!(matches!(segments[..], [Cow::Borrowed("foo bar baz")]));
+    }
+
+    #[test]
+    fn segments_alternate_between_borrowed_and_owned() {
+        let segments = merge_whitespace_segments("foo  bar\n\nbaz qux", &MergeOptions::new());
+        assert!(matches!(
+            segments[..],
+            [
+                Cow::Borrowed("foo"),
+                Cow::Owned(_),
+                Cow::Borrowed("bar"),
+                Cow::Owned(_),
+                Cow::Borrowed("baz qux"),
+            ]
+        ));
+        assert_eq!(segments.concat(), "foo bar baz qux");
+    }
+
+    #[test]
+    fn empty_input_has_no_segments() {
+        assert!(merge_whitespace_segments("   ", &MergeOptions::new()).is_empty());
+    }
+}
@@ -0,0 +1,84 @@
+use std::borrow::Cow;
+
+use crate::engine::{merge_into, CowSink};
+use crate::MergeOptions;
+
+/// Information about a completed merge, as reported by [`merge_whitespace_with_stats`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct MergeStats {
+    /// Whether all quoted regions were closed.
+    pub quotes_balanced: bool,
+    /// The maximum number of simultaneously open quoted regions, e.g. `2` for `“a “b” c”` with
+    /// [`MergeOptions::typographic_quotes`]. Nesting is only possible for distinct quote pairs,
+    /// so this is at most `1` otherwise, or `0` if the input has no quoted regions.
+    pub max_quote_depth: usize,
+}
+
+/// Remove multiple consecutive whitespaces from a given string like
+/// [`merge_whitespace_with_options`](crate::merge_whitespace_with_options), while also reporting
+/// statistics about the input.
+///
+/// Like [`merge_whitespace_validated`](crate::merge_whitespace_validated), unbalanced or
+/// deeply nested quotes do not cause an error.
+///
+/// ## Example
+///
+/// ```
+/// # use merge_whitespace_utils::{merge_whitespace_with_stats, MergeOptions};
+/// let options = MergeOptions::new().typographic_quotes(true);
+/// let (output, stats) = merge_whitespace_with_stats("a   «b   «c»  d»   e", &options);
+/// assert_eq!(output, "a «b   «c»  d» e");
+/// assert_eq!(stats.max_quote_depth, 2);
+/// assert!(stats.quotes_balanced);
+/// ```
+///
+/// # Return
+///
+/// The modified string and the statistics.
+pub fn merge_whitespace_with_stats<'a>(
+    input: &'a str,
+    options: &MergeOptions,
+) -> (Cow<'a, str>, MergeStats) {
+    let mut sink = CowSink::new(input);
+    let stats = merge_into(input, options, &mut sink);
+    (sink.into_cow(), stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn max_quote_depth(input: &str) -> usize {
+        let options = MergeOptions::new().quote_char('"').typographic_quotes(true);
+        merge_whitespace_with_stats(input, &options)
+            .1
+            .max_quote_depth
+    }
+
+    #[test]
+    fn flat_quotes_have_depth_one() {
+        assert_eq!(max_quote_depth("a  b  c"), 0);
+        assert_eq!(max_quote_depth("a  \"b\"  \"c\""), 1);
+        assert_eq!(max_quote_depth("«a»  “b”  \"c\""), 1);
+        // Quote characters within another pair do not open a nested region.
+        assert_eq!(max_quote_depth("«a “b” \"c\"»"), 1);
+    }
+
+    #[test]
+    fn nested_quotes_report_their_depth() {
+        assert_eq!(max_quote_depth("«a  «b»  c»"), 2);
+        assert_eq!(max_quote_depth("«a  «b»  «c»  d»  “e”"), 2);
+        assert_eq!(max_quote_depth("“a  “b  “c”  d”  e”"), 3);
+        assert_eq!(max_quote_depth("«a «b» c» «d «e «f» g» h»"), 3);
+    }
+
+    #[test]
+    fn unbalanced_nesting_is_lenient() {
+        let options = MergeOptions::new().typographic_quotes(true);
+        let (output, stats) = merge_whitespace_with_stats("  a  «b  «c»  d  ", &options);
+        assert_eq!(output, "a «b  «c»  d");
+        assert_eq!(stats.max_quote_depth, 2);
+        assert!(!stats.quotes_balanced);
+    }
+}
@@ -0,0 +1,21 @@
+//! Helpers shared by the unit tests.
+
+/// Generates `count` reproducible inputs of up to `max_len` characters drawn from the
+/// `alphabet`, using a simple linear congruential generator.
+pub(crate) fn random_inputs(
+    alphabet: &[char],
+    count: usize,
+    max_len: usize,
+) -> impl Iterator<Item = String> + '_ {
+    let mut state = 0x9E37_79B9_u32;
+    let mut next = move || {
+        state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        (state >> 8) as usize
+    };
+    (0..count).map(move |_| {
+        let len = next() % (max_len + 1);
+        (0..len)
+            .map(|_| alphabet[next() % alphabet.len()])
+            .collect()
+    })
+}
@@ -0,0 +1,301 @@
+use std::borrow::Cow;
+use std::ops::Range;
+
+use crate::engine::{Discard, Engine};
+use crate::MergeOptions;
+
+/// Splits the input into the tokens separated by whitespace in the output of
+/// [`merge_whitespace_with_quotes`](crate::merge_whitespace_with_quotes).
+///
+/// Quoted regions and escaped whitespace do not separate tokens, so each quoted region is part
+/// of a single token. If `include_delimiters` is `false`, the quote characters delimiting the
+/// regions are removed from the tokens.
+///
+/// ## Example
+///
+/// ```
+/// # use merge_whitespace_utils::tokens;
+/// let input = r#"  foo   "a   b"  bar  "#;
+/// assert_eq!(tokens(input, Some('"'), None, true), ["foo", "\"a   b\"", "bar"]);
+/// assert_eq!(tokens(input, Some('"'), None, false), ["foo", "a   b", "bar"]);
+/// ```
+///
+/// # Return
+///
+/// The tokens, borrowed from the input unless delimiters were removed.
+pub fn tokens(
+    input: &str,
+    quote_char: Option<char>,
+    escape_char: Option<char>,
+    include_delimiters: bool,
+) -> Vec<Cow<'_, str>> {
+    let options = MergeOptions {
+        quote_char,
+        escape_char,
+        ..MergeOptions::new()
+    };
+    let mut engine = Engine::new(&options);
+    let mut tokens = Vec::new();
+    let mut start = None;
+    let mut delimiters = Vec::new();
+    // The end of the last character that is not trailing whitespace, which is trimmed even in an
+    // unclosed quoted region.
+    let mut end = 0;
+
+    for (offset, c) in input.char_indices() {
+        let was_quoted = engine.is_quoted();
+        let separates = c.is_whitespace() && !was_quoted && !engine.is_escaped();
+        if engine.is_escaped() || !c.is_whitespace() {
+            end = offset + c.len_utf8();
+        }
+        engine.push(offset, c, &mut Discard);
+        if separates {
+            if let Some(start) = start.take() {
+                tokens.push(token(
+                    input,
+                    start..offset,
+                    &mut delimiters,
+                    include_delimiters,
+                ));
+            }
+            continue;
+        }
+        start.get_or_insert(offset);
+        if engine.is_quoted() != was_quoted {
+            delimiters.push(offset..offset + c.len_utf8());
+        }
+    }
+
+    if let Some(start) = start {
+        let range = start..end;
+        tokens.push(token(input, range, &mut delimiters, include_delimiters));
+    }
+    tokens
+}
+
+/// Lazily splits the input into the tokens separated by whitespace, the dual of
+/// [`merge_whitespace_with_quotes`](crate::merge_whitespace_with_quotes).
+///
+/// Each token is a maximal run of text that is either not whitespace or quoted, so a quoted
+/// region is part of a single token including its quote characters. Like in [`tokens`],
+/// escaped whitespace does not separate tokens.
+///
+/// ## Example
+///
+/// ```
+/// # use merge_whitespace_utils::whitespace_tokens;
+/// let input = r#"  foo   "a   b"c  d\ e  "#;
+/// let tokens: Vec<_> = whitespace_tokens(input, Some('"'), Some('\\')).collect();
+/// assert_eq!(tokens, ["foo", "\"a   b\"c", "d\\ e"]);
+/// ```
+///
+/// # Return
+///
+/// An iterator over the tokens, borrowed from the input.
+pub fn whitespace_tokens(
+    input: &str,
+    quote_char: Option<char>,
+    escape_char: Option<char>,
+) -> impl Iterator<Item = &str> {
+    let options = MergeOptions {
+        quote_char,
+        escape_char,
+        ..MergeOptions::new()
+    };
+    let mut engine = Engine::new(&options);
+    let mut chars = input.char_indices().peekable();
+    std::iter::from_fn(move || {
+        // Tokens only end outside of quoted regions and escapes, so whitespace here separates.
+        while chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+        let &(start, _) = chars.peek()?;
+        // The end of the last character that is not trailing whitespace, which is trimmed even
+        // in an unclosed quoted region.
+        let mut end = start;
+        for (offset, c) in chars.by_ref() {
+            if c.is_whitespace() && !engine.is_quoted() && !engine.is_escaped() {
+                return Some(&input[start..offset]);
+            }
+            if engine.is_escaped() || !c.is_whitespace() {
+                end = offset + c.len_utf8();
+            }
+            engine.push(offset, c, &mut Discard);
+        }
+        Some(&input[start..end])
+    })
+}
+
+/// Gets the token at `range` of the input, removing the `delimiters` unless they are included.
+fn token<'a>(
+    input: &'a str,
+    range: Range<usize>,
+    delimiters: &mut Vec<Range<usize>>,
+    include_delimiters: bool,
+) -> Cow<'a, str> {
+    if include_delimiters || delimiters.is_empty() {
+        delimiters.clear();
+        return Cow::Borrowed(&input[range]);
+    }
+
+    let mut token = String::with_capacity(range.len());
+    let mut kept = range.start;
+    for delimiter in delimiters.drain(..) {
+        token.push_str(&input[kept..delimiter.start]);
+        kept = delimiter.end;
+    }
+    token.push_str(&input[kept..range.end]);
+    Cow::Owned(token)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::merge_whitespace_with_quotes;
+    use crate::test_support::random_inputs;
+
+    const QUOTE: Option<char> = Some('"');
+    const ESCAPE: Option<char> = Some('\\');
+
+    #[test]
+    fn quoted_regions_are_single_tokens() {
+        let input = r#"foo  "a b"  bar"#;
+        assert_eq!(tokens(input, QUOTE, None, true), ["foo", "\"a b\"", "bar"]);
+        assert_eq!(tokens(input, QUOTE, None, false), ["foo", "a b", "bar"]);
+        assert!(matches!(
+            tokens(input, QUOTE, None, false)[1],
+            Cow::Owned(_)
+        ));
+    }
+
+    #[test]
+    fn adjacent_text_is_part_of_the_token() {
+        let input = r#"  a"b  c"d  "e\"  f"  g\ h  "#;
+        assert_eq!(
+            tokens(input, QUOTE, ESCAPE, true),
+            ["a\"b  c\"d", "\"e\\\"  f\"", "g\\ h"]
+        );
+        assert_eq!(
+            tokens(input, QUOTE, ESCAPE, false),
+            ["ab  cd", "e\\\"  f", "g\\ h"]
+        );
+    }
+
+    #[test]
+    fn unclosed_region_extends_to_the_end() {
+        assert_eq!(tokens("a \"b  c  d", QUOTE, None, true), ["a", "\"b  c  d"]);
+        assert_eq!(tokens("a \"b  c  d", QUOTE, None, false), ["a", "b  c  d"]);
+    }
+
+    #[test]
+    fn unclosed_region_is_trimmed() {
+        let input = "a \"b  c \\ \t\n";
+        assert_eq!(
+            merge_whitespace_with_quotes(input, QUOTE, ESCAPE),
+            "a \"b  c \\ "
+        );
+        assert_eq!(tokens(input, QUOTE, ESCAPE, true), ["a", "\"b  c \\ "]);
+        assert_eq!(tokens("a \"b  c  ", QUOTE, None, false), ["a", "b  c"]);
+    }
+
+    #[test]
+    fn tokens_match_merged_output() {
+        for input in [
+            "",
+            "   ",
+            "a",
+            " a  b\tc\n",
+            r#"x  "y  z"  w"#,
+            "x \"y  z  ",
+        ] {
+            let merged = merge_whitespace_with_quotes(input, QUOTE, None);
+            assert_eq!(tokens(input, QUOTE, None, true).join(" "), merged);
+        }
+    }
+
+    #[test]
+    fn whitespace_tokens_split_graphql_query() {
+        let input = r#"
+            query {
+              users (limit: 1, filter: "bought a 12\" vinyl
+                                        named \"spaces  in  space \"") {
+                id
+                todos(order_by: {created_at: desc}, limit: 5) { id  title }
+              }
+            }
+            "#;
+        let tokens: Vec<_> = whitespace_tokens(input, QUOTE, ESCAPE).collect();
+        assert_eq!(tokens.len(), 20);
+        assert_eq!(tokens[..5], ["query", "{", "users", "(limit:", "1,"]);
+        assert_eq!(
+            tokens[6],
+            "\"bought a 12\\\" vinyl\n                                        named \\\"spaces  in  space \\\"\")"
+        );
+        assert_eq!(
+            tokens[8..],
+            [
+                "id",
+                "todos(order_by:",
+                "{created_at:",
+                "desc},",
+                "limit:",
+                "5)",
+                "{",
+                "id",
+                "title",
+                "}",
+                "}",
+                "}"
+            ]
+        );
+
+        // Each token starts and ends at a boundary of the merged output.
+        let merged = merge_whitespace_with_quotes(input, QUOTE, ESCAPE);
+        assert_eq!(tokens.join(" "), merged);
+    }
+
+    #[test]
+    fn escaped_space_is_part_of_the_token() {
+        let input = r#"  a\ b  \  c\"  d  "#;
+        let tokens: Vec<_> = whitespace_tokens(input, QUOTE, ESCAPE).collect();
+        assert_eq!(tokens, ["a\\ b", "\\ ", "c\\\"", "d"]);
+        assert_eq!(tokens[0].chars().filter(|c| *c == ' ').count(), 1);
+    }
+
+    #[test]
+    fn unclosed_region_is_trimmed_like_the_merged_output() {
+        let input = "a\"\t\u{a0}a\n";
+        let merged = merge_whitespace_with_quotes(input, QUOTE, ESCAPE);
+        assert_eq!(merged, "a\"\t\u{a0}a");
+        assert!(whitespace_tokens(input, QUOTE, ESCAPE).eq(merged.split(' ')));
+
+        // An escaped whitespace character at the end is kept.
+        let tokens: Vec<_> = whitespace_tokens("a \"b \\ \t ", QUOTE, ESCAPE).collect();
+        assert_eq!(tokens, ["a", "\"b \\ "]);
+    }
+
+    #[test]
+    fn whitespace_tokens_match_merged_output() {
+        const ALPHABET: [char; 8] = [' ', ' ', '\t', '\u{a0}', 'a', '"', '\\', '\n'];
+
+        for input in random_inputs(&ALPHABET, 5000, 12) {
+            for (quote, escape) in [(None, None), (QUOTE, None), (None, ESCAPE), (QUOTE, ESCAPE)] {
+                let merged = merge_whitespace_with_quotes(&input, quote, escape);
+                let tokens: Vec<_> = whitespace_tokens(&input, quote, escape).collect();
+                assert_eq!(tokens.join(" "), merged, "{input:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn whitespace_tokens_match_tokens() {
+        for input in [
+            "",
+            "   ",
+            r#"  a"b  c"d  "e\"  f"  g\ h  "#,
+            "a \"b  c  ",
+            "x\t\ny\u{a0}z",
+        ] {
+            let expected = tokens(input, QUOTE, ESCAPE, true);
+            assert!(whitespace_tokens(input, QUOTE, ESCAPE).eq(expected.iter().map(|t| &**t)));
+        }
+    }
+}
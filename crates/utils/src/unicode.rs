@@ -0,0 +1,28 @@
+use unicode_general_category::{get_general_category, GeneralCategory};
+
+use crate::Case;
+
+/// Determines whether the character is a Unicode format (`Cf`) or control (`Cc`) character
+/// that is not also whitespace.
+pub(crate) fn is_format_or_control(c: char) -> bool {
+    !c.is_whitespace()
+        && matches!(
+            get_general_category(c),
+            GeneralCategory::Format | GeneralCategory::Control
+        )
+}
+
+/// Converts the character to the `case`, writing the result to `buf`.
+///
+/// A character maps to at most three characters, so the buffer is always large enough.
+pub(crate) fn convert_case(c: char, case: Case, buf: &mut [u8; 12]) -> &str {
+    let mut len = 0;
+    let mut push = |c: char| len += c.encode_utf8(&mut buf[len..]).len();
+    match case {
+        Case::Upper => c.to_uppercase().for_each(&mut push),
+        Case::Lower => c.to_lowercase().for_each(&mut push),
+        Case::None => push(c),
+    }
+    // Only complete UTF-8 sequences were written.
+    std::str::from_utf8(&buf[..len]).expect("valid UTF-8")
+}
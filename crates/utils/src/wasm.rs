@@ -0,0 +1,30 @@
+use wasm_bindgen::prelude::wasm_bindgen;
+
+/// Remove multiple consecutive whitespaces from a given string like
+/// [`merge_whitespace_with_quotes`](crate::merge_whitespace_with_quotes), exported for use
+/// from JavaScript.
+///
+/// # Return
+///
+/// The modified string.
+#[wasm_bindgen(js_name = mergeWhitespace)]
+pub fn merge_whitespace_wasm(
+    input: &str,
+    quote_char: Option<char>,
+    escape_char: Option<char>,
+) -> String {
+    crate::merge_whitespace_with_quotes(input, quote_char, escape_char).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn forwards_to_merge_whitespace_with_quotes() {
+        assert_eq!(
+            merge_whitespace_wasm("  a   \"b   c\"  \\   d ", Some('"'), Some('\\')),
+            "a \"b   c\" \\  d"
+        );
+    }
+}
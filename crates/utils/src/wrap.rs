@@ -0,0 +1,106 @@
+use crate::{merge_whitespace_with_quotes, whitespace_tokens};
+
+/// Merges the whitespace of the input like
+/// [`merge_whitespace_with_quotes`](crate::merge_whitespace_with_quotes) does, then wraps the
+/// result into lines of at most `width` characters, e.g. for terminal output.
+///
+/// Lines are only broken at the separators between tokens, so neither quoted regions nor other
+/// tokens are split. A token longer than `width` is placed on a line of its own.
+///
+/// ## Example
+///
+/// ```
+/// # use merge_whitespace_utils::merge_and_wrap;
+/// let lines = merge_and_wrap(r#"  a   "b   c"   d  e  "#, 10, Some('"'), None);
+/// assert_eq!(lines, [r#"a "b   c""#, "d e"]);
+/// ```
+///
+/// # Return
+///
+/// The wrapped lines, without line breaks.
+pub fn merge_and_wrap(
+    input: &str,
+    width: usize,
+    quote_char: Option<char>,
+    escape_char: Option<char>,
+) -> Vec<String> {
+    let merged = merge_whitespace_with_quotes(input, quote_char, escape_char);
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    let mut line_width = 0;
+    for token in whitespace_tokens(&merged, quote_char, escape_char) {
+        let token_width = token.chars().count();
+        if !line.is_empty() && line_width + 1 + token_width > width {
+            lines.push(std::mem::take(&mut line));
+            line_width = 0;
+        }
+        if !line.is_empty() {
+            line.push(' ');
+            line_width += 1;
+        }
+        line.push_str(token);
+        line_width += token_width;
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const QUOTE: Option<char> = Some('"');
+    const ESCAPE: Option<char> = Some('\\');
+
+    #[test]
+    fn lines_fit_the_width() {
+        let input = "  The   quick brown   fox \"jumps   over\"  the   lazy\tdog,\n\
+            \"then   keeps   on   running\"   until\\ the   end  ";
+        let lines = merge_and_wrap(input, 20, QUOTE, ESCAPE);
+        assert_eq!(
+            lines,
+            [
+                "The quick brown fox",
+                "\"jumps   over\" the",
+                "lazy dog,",
+                "\"then   keeps   on   running\"",
+                "until\\ the end",
+            ]
+        );
+        assert_eq!(
+            lines.join(" "),
+            merge_whitespace_with_quotes(input, QUOTE, ESCAPE)
+        );
+        for line in &lines {
+            let tokens = whitespace_tokens(line, QUOTE, ESCAPE).count();
+            assert!(line.chars().count() <= 20 || tokens == 1, "{line:?}");
+            // Each line contains complete quoted regions only.
+            assert_eq!(line.matches('"').count() % 2, 0, "{line:?}");
+        }
+    }
+
+    #[test]
+    fn width_counts_characters() {
+        assert_eq!(
+            merge_and_wrap("äöü  ß   é  ", 6, None, None),
+            ["äöü ß", "é"]
+        );
+    }
+
+    #[test]
+    fn empty_input_has_no_lines() {
+        assert!(merge_and_wrap("", 20, QUOTE, ESCAPE).is_empty());
+        assert!(merge_and_wrap("  \t\n ", 20, QUOTE, ESCAPE).is_empty());
+    }
+
+    #[test]
+    fn long_tokens_are_not_split() {
+        assert_eq!(
+            merge_and_wrap("a  abcdefgh  b  c", 3, QUOTE, ESCAPE),
+            ["a", "abcdefgh", "b c"]
+        );
+        assert_eq!(merge_and_wrap("a b", 0, QUOTE, ESCAPE), ["a", "b"]);
+    }
+}
@@ -0,0 +1,92 @@
+//! Runs the `merge_file` example against temporary input files.
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::sync::OnceLock;
+
+/// Locates the example binary built alongside this test.
+///
+/// `cargo test` builds the examples into the same target directory and profile as the tests,
+/// but running a single test target does not, e.g. with `--test merge_file_example`.
+fn example_path() -> &'static Path {
+    static PATH: OnceLock<PathBuf> = OnceLock::new();
+    PATH.get_or_init(|| {
+        let mut profile_dir = std::env::current_exe().expect("failed to get test executable path");
+        profile_dir.pop();
+        if profile_dir.ends_with("deps") {
+            profile_dir.pop();
+        }
+        let path = profile_dir
+            .join("examples")
+            .join(format!("merge_file{}", std::env::consts::EXE_SUFFIX));
+        assert!(
+            path.exists(),
+            "the merge_file example was not built, run `cargo build --example merge_file` first"
+        );
+        path
+    })
+}
+
+fn run_with_file(name: &str, contents: &str, args: &[&str]) -> Output {
+    let file = std::env::temp_dir().join(format!(
+        "merge-whitespace-{}-{name}.txt",
+        std::process::id()
+    ));
+    std::fs::write(&file, contents).expect("failed to write input file");
+
+    let output = Command::new(example_path())
+        .args(args)
+        .arg(&file)
+        .output()
+        .expect("failed to run example");
+
+    std::fs::remove_file(&file).ok();
+    output
+}
+
+#[test]
+fn merges_file_contents() {
+    let output = run_with_file("plain", "  Hello     World!\r\n   How  are  you?\n", &[]);
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "Hello World! How are you?\n"
+    );
+}
+
+#[test]
+fn honors_quote_and_escape_arguments() {
+    let output = run_with_file(
+        "quoted",
+        "query {\n  users (name: \"Froozle  \\\"78\\\"  Frobnik\") {\n    id\n  }\n}\n",
+        &["--quote", "\"", "--escape", "\\"],
+    );
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "query { users (name: \"Froozle  \\\"78\\\"  Frobnik\") { id } }\n"
+    );
+}
+
+#[test]
+fn fails_on_missing_file() {
+    let output = Command::new(example_path())
+        .arg("this/file/does/not/exist.txt")
+        .output()
+        .expect("failed to run example");
+    assert!(!output.status.success());
+}
+
+#[test]
+fn fails_on_invalid_arguments() {
+    let output = Command::new(example_path())
+        .args(["--quote", "ab", "file.txt"])
+        .output()
+        .expect("failed to run example");
+    assert!(!output.status.success());
+
+    let output = Command::new(example_path())
+        .output()
+        .expect("failed to run example");
+    assert!(!output.status.success());
+}